@@ -1,4 +1,5 @@
-use neo_jilang::{njis, NjilError, set_debug_mode};
+use neo_jilang::{njis, set_debug_mode};
+use neo_jilang::stack::format_stack;
 use std::env;
 use std::process;
 
@@ -40,15 +41,11 @@ fn main() {
             }
         }
         Err(err) => {
-            match err {
-                NjilError::ReturnValue(value) => {
-                    println!("程序执行成功，返回值: {}", value);
-                }
-                _ => {
-                    eprintln!("错误: {}", err);
-                    process::exit(1);
-                }
+            eprintln!("错误: {}", err);
+            if !err.stack().is_empty() {
+                eprintln!("{}", format_stack(err.stack()));
             }
+            process::exit(1);
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,688 @@
+use std::cmp::Ordering;
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+use crate::utils::path;
+use crate::utils::type_convert::{to_bool, to_number, to_string, is_equal, compare, as_range};
+use super::{BuiltinModule, get_callback, call_callback};
+
+/// Array模块，提供数组的高阶操作
+pub struct ArrayModule;
+
+impl Default for ArrayModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArrayModule {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinModule for ArrayModule {
+    fn name(&self) -> &str {
+        "array"
+    }
+
+    fn get_handlers(&self) -> Vec<&'static dyn StatementHandler> {
+        vec![
+            &ARRAY_MAP_HANDLER,
+            &ARRAY_FILTER_HANDLER,
+            &ARRAY_REDUCE_HANDLER,
+            &ARRAY_FIND_HANDLER,
+            &ARRAY_SOME_HANDLER,
+            &ARRAY_EVERY_HANDLER,
+            &ARRAY_SORT_HANDLER,
+            &ARRAY_SLICE_HANDLER,
+            &ARRAY_SPLICE_HANDLER,
+            &ARRAY_FLATTEN_HANDLER,
+            &ARRAY_ZIP_HANDLER,
+            &ARRAY_UNIQUE_HANDLER,
+            &ARRAY_GROUP_BY_HANDLER,
+            &ARRAY_CHUNK_HANDLER,
+        ]
+    }
+}
+
+// 辅助函数：获取参数对象
+fn get_args<'a>(value: &'a Value, instruction: &str) -> Result<&'a Map<String, Value>, NjilError> {
+    match value {
+        Value::Object(obj) => Ok(obj),
+        _ => Err(NjilError::ExecutionError(format!("{}需要一个包含array字段的对象", instruction))),
+    }
+}
+
+// 辅助函数：获取并评估array字段，范围会生成所有元素
+fn get_array(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Vec<Value>, NjilError> {
+    let array_value = obj.get("array")
+        .ok_or_else(|| NjilError::ExecutionError(format!("{}缺少array参数", instruction)))?;
+    to_vec(interpreter.evaluate_value(array_value)?, instruction)
+}
+
+// 辅助函数：获取并评估array字段，范围按需产生元素，不会生成整个数组
+fn get_items(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Box<dyn Iterator<Item = Value>>, NjilError> {
+    let array_value = obj.get("array")
+        .ok_or_else(|| NjilError::ExecutionError(format!("{}缺少array参数", instruction)))?;
+    match interpreter.evaluate_value(array_value)? {
+        Value::Array(arr) => Ok(Box::new(arr.into_iter())),
+        value => match as_range(&value) {
+            Some(range) => Ok(Box::new(range.iter())),
+            None => Err(NjilError::ExecutionError(format!("{}的array参数必须是数组", instruction))),
+        },
+    }
+}
+
+// 辅助函数：把数组或范围转换为元素列表
+fn to_vec(value: Value, instruction: &str) -> Result<Vec<Value>, NjilError> {
+    match value {
+        Value::Array(arr) => Ok(arr),
+        value => match as_range(&value) {
+            Some(range) => range.to_vec(),
+            None => Err(NjilError::ExecutionError(format!("{}的array参数必须是数组", instruction))),
+        },
+    }
+}
+
+// 辅助函数：获取数组，既支持直接传入数组，也支持包含array字段的对象
+fn get_array_or_args(interpreter: &mut Interpreter, value: &Value, instruction: &str) -> Result<(Vec<Value>, Map<String, Value>), NjilError> {
+    if let Value::Object(obj) = value {
+        if obj.contains_key("array") {
+            return Ok((get_array(interpreter, obj, instruction)?, obj.clone()));
+        }
+    }
+    match interpreter.evaluate_value(value)? {
+        Value::Array(arr) => Ok((arr, Map::new())),
+        value => match as_range(&value) {
+            Some(range) => Ok((range.to_vec()?, Map::new())),
+            None => Err(NjilError::ExecutionError(format!("{}需要一个数组或包含array字段的对象", instruction))),
+        },
+    }
+}
+
+// 辅助函数：获取数字参数
+fn get_number(interpreter: &mut Interpreter, obj: &Map<String, Value>, key: &str, instruction: &str) -> Result<Option<f64>, NjilError> {
+    match obj.get(key) {
+        Some(value) => {
+            let evaluated = interpreter.evaluate_value(value)?;
+            match to_number(&evaluated) {
+                Some(n) => Ok(Some(n)),
+                None => Err(NjilError::ExecutionError(format!("{}的{}参数必须是数字", instruction, key))),
+            }
+        },
+        None => Ok(None),
+    }
+}
+
+// 辅助函数：把可能为负数的位置转换为数组下标
+fn resolve_index(index: f64, len: usize) -> usize {
+    if index < 0.0 {
+        len.saturating_sub((-index) as usize)
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+// 辅助函数：按键路径获取元素的值
+fn get_key(item: &Value, key_path: &[path::PathPart]) -> Result<Value, NjilError> {
+    path::get_nested_value(item, key_path).cloned()
+}
+
+// 辅助函数：获取key参数并解析为路径
+fn get_key_path(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Option<Vec<path::PathPart>>, NjilError> {
+    match obj.get("key") {
+        Some(value) => match interpreter.evaluate_value(value)? {
+            Value::String(key) => Ok(Some(path::parse_path(&key)?)),
+            _ => Err(NjilError::ExecutionError(format!("{}的key参数必须是字符串路径", instruction))),
+        },
+        None => Ok(None),
+    }
+}
+
+/// 数组映射处理器
+pub struct ArrayMapHandler;
+
+// 静态实例
+pub static ARRAY_MAP_HANDLER: ArrayMapHandler = ArrayMapHandler;
+
+impl StatementHandler for ArrayMapHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_items(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        let mut result = Vec::new();
+        for (index, item) in array.enumerate() {
+            result.push(call_callback(interpreter, &callback, vec![item, Value::from(index)])?);
+        }
+
+        Ok(Value::Array(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.map"
+    }
+}
+
+/// 数组过滤处理器
+pub struct ArrayFilterHandler;
+
+// 静态实例
+pub static ARRAY_FILTER_HANDLER: ArrayFilterHandler = ArrayFilterHandler;
+
+impl StatementHandler for ArrayFilterHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_items(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        let mut result = Vec::new();
+        for (index, item) in array.enumerate() {
+            if to_bool(&call_callback(interpreter, &callback, vec![item.clone(), Value::from(index)])?) {
+                result.push(item);
+            }
+        }
+
+        Ok(Value::Array(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.filter"
+    }
+}
+
+/// 数组归约处理器
+pub struct ArrayReduceHandler;
+
+// 静态实例
+pub static ARRAY_REDUCE_HANDLER: ArrayReduceHandler = ArrayReduceHandler;
+
+impl StatementHandler for ArrayReduceHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_items(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        // 没有提供初始值时使用第一个元素
+        let mut items = array.enumerate();
+        let mut accumulator = match obj.get("initial") {
+            Some(initial) => interpreter.evaluate_value(initial)?,
+            None => match items.next() {
+                Some((_, first)) => first,
+                None => return Err(NjilError::ExecutionError("array.reduce不能对没有初始值的空数组进行归约".to_string())),
+            },
+        };
+
+        for (index, item) in items {
+            accumulator = call_callback(interpreter, &callback, vec![accumulator, item, Value::from(index)])?;
+        }
+
+        Ok(accumulator)
+    }
+
+    fn name(&self) -> &str {
+        "array.reduce"
+    }
+}
+
+/// 数组查找处理器，返回第一个满足条件的元素，找不到时返回null
+pub struct ArrayFindHandler;
+
+// 静态实例
+pub static ARRAY_FIND_HANDLER: ArrayFindHandler = ArrayFindHandler;
+
+impl StatementHandler for ArrayFindHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_items(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        for (index, item) in array.enumerate() {
+            if to_bool(&call_callback(interpreter, &callback, vec![item.clone(), Value::from(index)])?) {
+                return Ok(item);
+            }
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn name(&self) -> &str {
+        "array.find"
+    }
+}
+
+/// 判断数组中是否存在满足条件的元素
+pub struct ArraySomeHandler;
+
+// 静态实例
+pub static ARRAY_SOME_HANDLER: ArraySomeHandler = ArraySomeHandler;
+
+impl StatementHandler for ArraySomeHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_items(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        for (index, item) in array.enumerate() {
+            if to_bool(&call_callback(interpreter, &callback, vec![item, Value::from(index)])?) {
+                return Ok(Value::Bool(true));
+            }
+        }
+
+        Ok(Value::Bool(false))
+    }
+
+    fn name(&self) -> &str {
+        "array.some"
+    }
+}
+
+/// 判断数组中的所有元素是否都满足条件
+pub struct ArrayEveryHandler;
+
+// 静态实例
+pub static ARRAY_EVERY_HANDLER: ArrayEveryHandler = ArrayEveryHandler;
+
+impl StatementHandler for ArrayEveryHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_items(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        for (index, item) in array.enumerate() {
+            if !to_bool(&call_callback(interpreter, &callback, vec![item, Value::from(index)])?) {
+                return Ok(Value::Bool(false));
+            }
+        }
+
+        Ok(Value::Bool(true))
+    }
+
+    fn name(&self) -> &str {
+        "array.every"
+    }
+}
+
+/// 数组排序处理器
+///
+/// 支持比较函数fn（返回负数、0或正数）、按键路径key排序，以及order为desc时降序排序。
+/// 排序是稳定的。
+pub struct ArraySortHandler;
+
+// 静态实例
+pub static ARRAY_SORT_HANDLER: ArraySortHandler = ArraySortHandler;
+
+impl StatementHandler for ArraySortHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (mut array, obj) = get_array_or_args(interpreter, value, self.name())?;
+        let key_path = get_key_path(interpreter, &obj, self.name())?;
+        let descending = match obj.get("order") {
+            Some(order) => match interpreter.evaluate_value(order)? {
+                Value::String(order) if order == "asc" => false,
+                Value::String(order) if order == "desc" => true,
+                _ => return Err(NjilError::ExecutionError("array.sort的order参数必须是asc或desc".to_string())),
+            },
+            None => false,
+        };
+        // 降序时反转比较结果而不是反转排序后的数组，相等的元素保持原来的顺序
+        let order = |ordering: Ordering| if descending { ordering.reverse() } else { ordering };
+
+        if obj.contains_key("fn") {
+            let callback = get_callback(interpreter, &obj, self.name())?;
+
+            // 比较函数可能出错，出错后停止调用并在排序结束后返回错误
+            let mut error = None;
+            array.sort_by(|a, b| {
+                if error.is_some() {
+                    return Ordering::Equal;
+                }
+                match call_callback(interpreter, &callback, vec![a.clone(), b.clone()]) {
+                    Ok(result) => order(to_number(&result)
+                        .and_then(|n| n.partial_cmp(&0.0))
+                        .unwrap_or(Ordering::Equal)),
+                    Err(e) => {
+                        error = Some(e);
+                        Ordering::Equal
+                    },
+                }
+            });
+            if let Some(e) = error {
+                return Err(e);
+            }
+        } else if let Some(key_path) = key_path {
+            let mut keyed = Vec::with_capacity(array.len());
+            for item in array {
+                keyed.push((get_key(&item, &key_path)?, item));
+            }
+            keyed.sort_by(|(a, _), (b, _)| order(compare(a, b).unwrap_or(Ordering::Equal)));
+            array = keyed.into_iter().map(|(_, item)| item).collect();
+        } else {
+            array.sort_by(|a, b| order(compare(a, b).unwrap_or(Ordering::Equal)));
+        }
+
+        Ok(Value::Array(array))
+    }
+
+    fn name(&self) -> &str {
+        "array.sort"
+    }
+}
+
+/// 数组切片处理器，start和end支持负数（从末尾计算）
+pub struct ArraySliceHandler;
+
+// 静态实例
+pub static ARRAY_SLICE_HANDLER: ArraySliceHandler = ArraySliceHandler;
+
+impl StatementHandler for ArraySliceHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_array(interpreter, obj, self.name())?;
+        let len = array.len();
+
+        let start = resolve_index(get_number(interpreter, obj, "start", self.name())?.unwrap_or(0.0), len);
+        let end = match get_number(interpreter, obj, "end", self.name())? {
+            Some(end) => resolve_index(end, len),
+            None => len,
+        };
+
+        if start >= end {
+            return Ok(Value::Array(Vec::new()));
+        }
+        Ok(Value::Array(array[start..end].to_vec()))
+    }
+
+    fn name(&self) -> &str {
+        "array.slice"
+    }
+}
+
+/// 数组拼接处理器，删除指定位置的元素并插入新元素
+///
+/// 返回 `{"array": 修改后的数组, "removed": 被删除的元素}`。
+pub struct ArraySpliceHandler;
+
+// 静态实例
+pub static ARRAY_SPLICE_HANDLER: ArraySpliceHandler = ArraySpliceHandler;
+
+impl StatementHandler for ArraySpliceHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let mut array = get_array(interpreter, obj, self.name())?;
+        let len = array.len();
+
+        let start = resolve_index(get_number(interpreter, obj, "start", self.name())?.unwrap_or(0.0), len);
+        let delete_count = match get_number(interpreter, obj, "delete_count", self.name())? {
+            Some(count) => (count.max(0.0) as usize).min(len - start),
+            None => len - start,
+        };
+        let items = match obj.get("items") {
+            Some(items) => match interpreter.evaluate_value(items)? {
+                Value::Array(items) => items,
+                _ => return Err(NjilError::ExecutionError("array.splice的items参数必须是数组".to_string())),
+            },
+            None => Vec::new(),
+        };
+
+        let removed: Vec<Value> = array.splice(start..start + delete_count, items).collect();
+
+        let mut result = Map::new();
+        result.insert("array".to_string(), Value::Array(array));
+        result.insert("removed".to_string(), Value::Array(removed));
+        Ok(Value::Object(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.splice"
+    }
+}
+
+/// 数组展开处理器，depth指定展开层数（默认1）
+pub struct ArrayFlattenHandler;
+
+// 静态实例
+pub static ARRAY_FLATTEN_HANDLER: ArrayFlattenHandler = ArrayFlattenHandler;
+
+fn flatten_into(result: &mut Vec<Value>, array: Vec<Value>, depth: usize) {
+    for item in array {
+        match item {
+            Value::Array(inner) if depth > 0 => flatten_into(result, inner, depth - 1),
+            _ => result.push(item),
+        }
+    }
+}
+
+impl StatementHandler for ArrayFlattenHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (array, obj) = get_array_or_args(interpreter, value, self.name())?;
+        let depth = get_number(interpreter, &obj, "depth", self.name())?.unwrap_or(1.0).max(0.0) as usize;
+
+        let mut result = Vec::new();
+        flatten_into(&mut result, array, depth);
+        Ok(Value::Array(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.flatten"
+    }
+}
+
+/// 数组压缩处理器，把多个数组按位置组合成元组数组，长度以最短的数组为准
+pub struct ArrayZipHandler;
+
+// 静态实例
+pub static ARRAY_ZIP_HANDLER: ArrayZipHandler = ArrayZipHandler;
+
+impl StatementHandler for ArrayZipHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (arrays, _) = get_array_or_args(interpreter, value, self.name())?;
+
+        let mut columns = Vec::with_capacity(arrays.len());
+        for array in arrays {
+            match array {
+                Value::Array(arr) => columns.push(arr),
+                _ => return Err(NjilError::ExecutionError("array.zip的每个元素都必须是数组".to_string())),
+            }
+        }
+
+        let len = columns.iter().map(|column| column.len()).min().unwrap_or(0);
+        let result = (0..len)
+            .map(|i| Value::Array(columns.iter().map(|column| column[i].clone()).collect()))
+            .collect();
+        Ok(Value::Array(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.zip"
+    }
+}
+
+/// 数组去重处理器，可以通过key指定按键路径去重，保留第一次出现的元素
+pub struct ArrayUniqueHandler;
+
+// 静态实例
+pub static ARRAY_UNIQUE_HANDLER: ArrayUniqueHandler = ArrayUniqueHandler;
+
+impl StatementHandler for ArrayUniqueHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (array, obj) = get_array_or_args(interpreter, value, self.name())?;
+        let key_path = get_key_path(interpreter, &obj, self.name())?;
+
+        let mut seen: Vec<Value> = Vec::new();
+        let mut result = Vec::new();
+        for item in array {
+            let key = match &key_path {
+                Some(key_path) => get_key(&item, key_path)?,
+                None => item.clone(),
+            };
+            if !seen.iter().any(|existing| is_equal(existing, &key)) {
+                seen.push(key);
+                result.push(item);
+            }
+        }
+
+        Ok(Value::Array(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.unique"
+    }
+}
+
+/// 数组分组处理器，按键路径key或分组函数fn的结果分组，返回以分组键为键的对象
+pub struct ArrayGroupByHandler;
+
+// 静态实例
+pub static ARRAY_GROUP_BY_HANDLER: ArrayGroupByHandler = ArrayGroupByHandler;
+
+impl StatementHandler for ArrayGroupByHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_array(interpreter, obj, self.name())?;
+        let key_path = get_key_path(interpreter, obj, self.name())?;
+        let callback = if key_path.is_none() {
+            Some(get_callback(interpreter, obj, self.name())?)
+        } else {
+            None
+        };
+
+        let mut groups = Map::new();
+        for (index, item) in array.into_iter().enumerate() {
+            let key = match (&key_path, &callback) {
+                (Some(key_path), _) => get_key(&item, key_path)?,
+                (None, Some(callback)) => call_callback(interpreter, callback, vec![item.clone(), Value::from(index)])?,
+                (None, None) => unreachable!(),
+            };
+
+            let group = groups.entry(to_string(&key)).or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(group) = group {
+                group.push(item);
+            }
+        }
+
+        Ok(Value::Object(groups))
+    }
+
+    fn name(&self) -> &str {
+        "array.group_by"
+    }
+}
+
+/// 数组分块处理器，按size把数组切分为多个子数组
+pub struct ArrayChunkHandler;
+
+// 静态实例
+pub static ARRAY_CHUNK_HANDLER: ArrayChunkHandler = ArrayChunkHandler;
+
+impl StatementHandler for ArrayChunkHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let array = get_array(interpreter, obj, self.name())?;
+        let size = match get_number(interpreter, obj, "size", self.name())? {
+            Some(size) if size >= 1.0 => size as usize,
+            _ => return Err(NjilError::ExecutionError("array.chunk的size参数必须是大于0的数字".to_string())),
+        };
+
+        let result = array.chunks(size)
+            .map(|chunk| Value::Array(chunk.to_vec()))
+            .collect();
+        Ok(Value::Array(result))
+    }
+
+    fn name(&self) -> &str {
+        "array.chunk"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NjilProgram;
+    use serde_json::json;
+
+    fn run(body: Value) -> Value {
+        let program: NjilProgram = serde_json::from_value(json!({
+            "import": ["!array"],
+            "program": {
+                "main": {"body": body},
+                "is_even": {
+                    "params": ["x"],
+                    "body": [{"return": {"compare": {"left": {"mod": {"dividend": {"var": "x"}, "divisor": 2}}, "op": "==", "right": 0}}}]
+                }
+            }
+        })).unwrap();
+        Interpreter::new().execute(&program).unwrap()
+    }
+
+    #[test]
+    fn test_map_filter_reduce() {
+        let result = run(json!([
+            {"var.set": {"name": "doubled", "value": {"array.map": {
+                "array": [1, 2, 3, 4],
+                "fn": {"fn": {"params": ["x"], "body": [{"return": {"multiply": [{"var": "x"}, 2]}}]}}
+            }}}},
+            {"var.set": {"name": "evens", "value": {"array.filter": {"array": [1, 2, 3, 4], "fn": "is_even"}}}},
+            {"return": {"array.reduce": {
+                "array": {"var": "evens"},
+                "initial": {"var": "doubled"},
+                "fn": {"fn": {"params": ["acc", "x"], "body": [{"return": {"string.concat": [{"var": "acc"}, "|", {"var": "x"}]}}]}}
+            }}}
+        ]));
+
+        assert_eq!(result, json!("2, 4, 6, 8|2|4"));
+    }
+
+    #[test]
+    fn test_sort_by_key_and_comparator() {
+        let people = json!([{"name": "b", "age": 30}, {"name": "a", "age": 25}, {"name": "c", "age": 35}]);
+
+        let by_key = run(json!([{"return": {"array.sort": {"array": people, "key": "age", "order": "desc"}}}]));
+        assert_eq!(by_key, json!([{"name": "c", "age": 35}, {"name": "b", "age": 30}, {"name": "a", "age": 25}]));
+
+        // 降序排序是稳定的，键相等的元素保持原来的顺序
+        let ties = json!([{"name": "a", "age": 30}, {"name": "b", "age": 25}, {"name": "c", "age": 30}, {"name": "d", "age": 25}]);
+        let by_key = run(json!([{"return": {"array.sort": {"array": ties, "key": "age", "order": "desc"}}}]));
+        assert_eq!(by_key, json!([{"name": "a", "age": 30}, {"name": "c", "age": 30}, {"name": "b", "age": 25}, {"name": "d", "age": 25}]));
+
+        let by_fn = run(json!([{"return": {"array.sort": {"array": [3, 1, 2], "fn": {"fn": {
+            "params": ["a", "b"],
+            "body": [{"return": {"subtract": [{"var": "b"}, {"var": "a"}]}}]
+        }}}}}]));
+        assert_eq!(by_fn, json!([3, 2, 1]));
+    }
+
+    #[test]
+    fn test_structural_operations() {
+        assert_eq!(run(json!([{"return": {"array.slice": {"array": [1, 2, 3, 4], "start": -3, "end": -1}}}])), json!([2, 3]));
+        assert_eq!(
+            run(json!([{"return": {"array.splice": {"array": [1, 2, 3, 4], "start": 1, "delete_count": 2, "items": ["x"]}}}])),
+            json!({"array": [1, "x", 4], "removed": [2, 3]})
+        );
+        assert_eq!(run(json!([{"return": {"array.flatten": {"array": [1, [2, [3, [4]]]], "depth": 2}}}])), json!([1, 2, 3, [4]]));
+        assert_eq!(run(json!([{"return": {"array.zip": [[1, 2, 3], ["a", "b"]]}}])), json!([[1, "a"], [2, "b"]]));
+        assert_eq!(run(json!([{"return": {"array.unique": [1, 2, 1, 3, 2]}}])), json!([1, 2, 3]));
+        assert_eq!(run(json!([{"return": {"array.chunk": {"array": [1, 2, 3, 4, 5], "size": 2}}}])), json!([[1, 2], [3, 4], [5]]));
+        assert_eq!(
+            run(json!([{"return": {"array.group_by": {"array": [1, 2, 3, 4], "fn": "is_even"}}}])),
+            json!({"false": [1, 3], "true": [2, 4]})
+        );
+    }
+
+    #[test]
+    fn test_find_some_every() {
+        assert_eq!(run(json!([{"return": {"array.find": {"array": [1, 3, 4, 6], "fn": "is_even"}}}])), json!(4));
+        assert_eq!(run(json!([{"return": {"array.some": {"array": [1, 3], "fn": "is_even"}}}])), json!(false));
+        assert_eq!(run(json!([{"return": {"array.every": {"array": [2, 4], "fn": "is_even"}}}])), json!(true));
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(run(json!([{"return": {"array.map": {"array": {"range": [1, 4]}, "fn": "is_even"}}}])), json!([false, true, false]));
+        assert_eq!(run(json!([{"return": {"array.unique": {"range": {"from": 1, "to": 3, "inclusive": true}}}}])), json!([1, 2, 3]));
+        // 很长的范围按需计算元素，找到结果后就停止
+        assert_eq!(run(json!([{"return": {"array.find": {"array": {"range": {"from": 1, "to": 1_000_000_000_000_i64}}, "fn": "is_even"}}}])), json!(2));
+        assert_eq!(run(json!([{"return": {"array.some": {"array": {"range": [0, 1_000_000_000_000_i64, 2]}, "fn": "is_even"}}}])), json!(true));
+    }
+}
@@ -10,6 +10,12 @@ use super::BuiltinModule;
 /// DateTime模块，提供日期和时间相关功能
 pub struct DateTimeModule;
 
+impl Default for DateTimeModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DateTimeModule {
     pub fn new() -> Self {
         Self {}
@@ -17,7 +23,7 @@ impl DateTimeModule {
 }
 
 impl BuiltinModule for DateTimeModule {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime"
     }
     
@@ -62,7 +68,7 @@ impl StatementHandler for DateHandler {
         Ok(Value::String(formatted_date))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime.date"
     }
     
@@ -100,7 +106,7 @@ impl StatementHandler for TimeHandler {
         Ok(Value::String(formatted_time))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime.time"
     }
     
@@ -143,7 +149,7 @@ impl StatementHandler for NowHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime.now"
     }
     
@@ -226,7 +232,7 @@ impl StatementHandler for FormatHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime.format"
     }
     
@@ -298,7 +304,7 @@ impl StatementHandler for ParseHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime.parse"
     }
     
@@ -397,7 +403,7 @@ impl StatementHandler for MeasureHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "datetime.measure"
     }
     
@@ -12,6 +12,12 @@ use super::BuiltinModule;
 /// IO模块，提供文件读写和标准输入输出功能
 pub struct IoModule;
 
+impl Default for IoModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl IoModule {
     pub fn new() -> Self {
         Self {}
@@ -19,7 +25,7 @@ impl IoModule {
 }
 
 impl BuiltinModule for IoModule {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "io"
     }
     
@@ -47,7 +53,7 @@ impl StatementHandler for IoReadFileHandler {
                 // 简单形式：直接提供文件路径字符串
                 // 读取文件内容（默认UTF-8编码）
                 let content = fs::read_to_string(file_path)
-                    .map_err(|e| NjilError::IoError(e))?;
+                    .map_err(NjilError::IoError)?;
                 
                 Ok(Value::String(content))
             },
@@ -79,17 +85,17 @@ impl StatementHandler for IoReadFileHandler {
                     "utf8" | "utf-8" => {
                         // 使用UTF-8编码读取
                         let content = fs::read_to_string(path_str)
-                            .map_err(|e| NjilError::IoError(e))?;
+                            .map_err(NjilError::IoError)?;
                         
                         Ok(Value::String(content))
                     },
                     "gbk" | "gb2312" => {
                         // 读取文件为字节数组
                         let mut file = fs::File::open(path_str)
-                            .map_err(|e| NjilError::IoError(e))?;
+                            .map_err(NjilError::IoError)?;
                         let mut bytes = Vec::new();
                         file.read_to_end(&mut bytes)
-                            .map_err(|e| NjilError::IoError(e))?;
+                            .map_err(NjilError::IoError)?;
                         
                         // 使用encoding_rs库将GBK/GB2312转换为UTF-8
                         let (cow, _encoding_used, had_errors) = GBK.decode(&bytes);
@@ -110,7 +116,7 @@ impl StatementHandler for IoReadFileHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "io.readFile"
     }
 }
@@ -161,13 +167,13 @@ impl StatementHandler for IoWriteFileHandler {
                         .create(true)
                         .truncate(true)
                         .open(path_str)
-                        .map_err(|e| NjilError::IoError(e))?;
+                        .map_err(NjilError::IoError)?;
                         
                     let mut writer = BufWriter::new(file);
                     writer.write_all(content_str.as_bytes())
-                        .map_err(|e| NjilError::IoError(e))?;
+                        .map_err(NjilError::IoError)?;
                     writer.flush()
-                        .map_err(|e| NjilError::IoError(e))?;
+                        .map_err(NjilError::IoError)?;
                 },
                 "gbk" | "gb2312" => {
                     // 使用encoding_rs将UTF-8转换为GBK/GB2312
@@ -182,13 +188,13 @@ impl StatementHandler for IoWriteFileHandler {
                         .create(true)
                         .truncate(true)
                         .open(path_str)
-                        .map_err(|e| NjilError::IoError(e))?;
+                        .map_err(NjilError::IoError)?;
                         
                     let mut writer = BufWriter::new(file);
                     writer.write_all(&bytes)
-                        .map_err(|e| NjilError::IoError(e))?;
+                        .map_err(NjilError::IoError)?;
                     writer.flush()
-                        .map_err(|e| NjilError::IoError(e))?;
+                        .map_err(NjilError::IoError)?;
                 },
                 _ => {
                     return Err(NjilError::ExecutionError(format!("不支持的编码类型: {}", encoding)));
@@ -201,7 +207,7 @@ impl StatementHandler for IoWriteFileHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "io.writeFile"
     }
 }
@@ -216,7 +222,7 @@ impl StatementHandler for IoReadLineHandler {
     fn handle(&self, _interpreter: &mut Interpreter, _value: &Value) -> Result<Value, NjilError> {
         let mut input = String::new();
         io::stdin().read_line(&mut input)
-            .map_err(|e| NjilError::IoError(e))?;
+            .map_err(NjilError::IoError)?;
         
         // 去除末尾的换行符
         if input.ends_with('\n') {
@@ -229,7 +235,7 @@ impl StatementHandler for IoReadLineHandler {
         Ok(Value::String(input))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "io.readLine"
     }
 }
@@ -246,13 +252,13 @@ impl StatementHandler for IoInputHandler {
         if !value.is_null() {
             let prompt = interpreter.value_to_string(value);
             print!("{}", prompt);
-            io::stdout().flush().map_err(|e| NjilError::IoError(e))?;
+            io::stdout().flush().map_err(NjilError::IoError)?;
         }
         
         // 读取输入
         let mut input = String::new();
         io::stdin().read_line(&mut input)
-            .map_err(|e| NjilError::IoError(e))?;
+            .map_err(NjilError::IoError)?;
         
         // 去除末尾的换行符
         if input.ends_with('\n') {
@@ -265,7 +271,7 @@ impl StatementHandler for IoInputHandler {
         Ok(Value::String(input))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "io.input"
     }
 } 
\ No newline at end of file
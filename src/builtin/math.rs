@@ -0,0 +1,582 @@
+use std::cmp::Ordering;
+use std::f64::consts;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
+use serde_json::Value;
+use crate::error::NjilError;
+use crate::errortip::math as tip;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert::{self, integer, Decimal, Rounding};
+use crate::utils::type_convert::decimal::{as_decimal, MAX_SCALE};
+use super::BuiltinModule;
+
+/// 整数幂运算允许的最大指数
+const MAX_EXPONENT: u64 = 65536;
+
+/// Math模块，提供乘方、开方、取整、三角函数、对数、最值等数学函数，以及常量math.PI和math.E
+pub struct MathModule;
+
+impl Default for MathModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MathModule {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinModule for MathModule {
+    fn name(&self) -> &str {
+        "math"
+    }
+
+    fn get_handlers(&self) -> Vec<&'static dyn StatementHandler> {
+        vec![
+            &POW_HANDLER,
+            &SQRT_HANDLER,
+            &ABS_HANDLER,
+            &FLOOR_HANDLER,
+            &CEIL_HANDLER,
+            &TRUNC_HANDLER,
+            &ROUND_HANDLER,
+            &SIN_HANDLER,
+            &COS_HANDLER,
+            &TAN_HANDLER,
+            &ATAN2_HANDLER,
+            &LOG_HANDLER,
+            &LN_HANDLER,
+            &EXP_HANDLER,
+            &MIN_HANDLER,
+            &MAX_HANDLER,
+            &SUM_HANDLER,
+            &CLAMP_HANDLER,
+            &HYPOT_HANDLER,
+        ]
+    }
+
+    fn initialize(&self) -> fn(&mut Interpreter) -> Result<(), NjilError> {
+        |interpreter| {
+            interpreter.constants.insert("math.PI".to_string(), float_value(consts::PI));
+            interpreter.constants.insert("math.E".to_string(), float_value(consts::E));
+            Ok(())
+        }
+    }
+}
+
+// 辅助函数：求值操作数
+//
+// 数组的每一项分别求值（与math.add相同）；其他参数求值后是数组时作为操作数列表，否则作为唯一的操作数。
+fn evaluate_operands(interpreter: &mut Interpreter, value: &Value) -> Result<Vec<Value>, NjilError> {
+    match value {
+        Value::Array(operands) => operands.iter().map(|operand| interpreter.evaluate_value(operand)).collect(),
+        other => match interpreter.evaluate_value(other)? {
+            Value::Array(values) => Ok(values),
+            single => Ok(vec![single]),
+        },
+    }
+}
+
+// 辅助函数：求值固定的参数
+//
+// 参数可以按names的顺序写成数组，也可以写成以names为字段名的对象。前required个参数是必需的，
+// 缺少的可选参数为null。
+fn evaluate_arguments(interpreter: &mut Interpreter, value: &Value, instruction: &str, names: &[&str], required: usize) -> Result<Vec<Value>, NjilError> {
+    let mut arguments = match value {
+        Value::Object(obj) if obj.contains_key(names[0]) => {
+            let mut arguments = Vec::with_capacity(names.len());
+            for name in names {
+                arguments.push(match obj.get(*name) {
+                    Some(argument) => interpreter.evaluate_value(argument)?,
+                    None => Value::Null,
+                });
+            }
+            arguments
+        },
+        other => evaluate_operands(interpreter, other)?,
+    };
+    if arguments.len() < required || arguments.len() > names.len() || arguments[..required].iter().any(Value::is_null) {
+        return Err(NjilError::ExecutionError(tip::arguments(instruction, names)));
+    }
+    arguments.resize(names.len(), Value::Null);
+    Ok(arguments)
+}
+
+// 辅助函数：求值唯一的参数
+fn evaluate_single(interpreter: &mut Interpreter, value: &Value, instruction: &str) -> Result<Value, NjilError> {
+    let mut arguments = evaluate_arguments(interpreter, value, instruction, &["value"], 1)?;
+    Ok(arguments.remove(0))
+}
+
+// 辅助函数：转换为浮点数，数字、大整数、定点小数和数字字符串以外的值报错
+fn to_float(value: &Value, instruction: &str) -> Result<f64, NjilError> {
+    let numeric = type_convert::has_type(value, "number") == Some(true) || value.is_string();
+    match type_convert::to_number(value) {
+        Some(number) if numeric => Ok(number),
+        _ => Err(NjilError::TypeError(tip::number_required(instruction, type_convert::type_name(value)))),
+    }
+}
+
+// 辅助函数：浮点数结果，NaN和无穷大为null
+fn float_value(number: f64) -> Value {
+    serde_json::Number::from_f64(number).map(Value::Number).unwrap_or(Value::Null)
+}
+
+// 辅助函数：没有小数部分的浮点数转换为整数，超出64位整数范围时保持浮点数
+fn float_to_integer(number: f64) -> Value {
+    if number.is_finite() && number >= i64::MIN as f64 && number < i64::MAX as f64 {
+        Value::from(number as i64)
+    } else {
+        float_value(number)
+    }
+}
+
+// 辅助函数：整数结果，big为true时是大整数，否则超出64位整数范围时报错
+fn integer_value(number: &BigInt, big: bool, op: &str) -> Result<Value, NjilError> {
+    if big {
+        return Ok(type_convert::bigint_value(number));
+    }
+    integer::to_small_value(number).ok_or_else(|| NjilError::ExecutionError(tip::integer_overflow(op)))
+}
+
+/// 乘方：`[底数, 指数]` 或 `{"base", "exponent"}`
+///
+/// 整数的非负整数次幂是精确的整数，定点小数的非负整数次幂是定点小数，其他情况按浮点数计算。
+pub struct PowHandler;
+
+// 静态实例
+pub static POW_HANDLER: PowHandler = PowHandler;
+
+impl StatementHandler for PowHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let arguments = evaluate_arguments(interpreter, value, self.name(), &["base", "exponent"], 2)?;
+        let (base, exponent) = (&arguments[0], &arguments[1]);
+
+        if let Some(power) = integer::as_integer(exponent).filter(|power| !power.is_negative()) {
+            let power = match power.to_u64() {
+                Some(power) if power <= MAX_EXPONENT => power as usize,
+                _ => return Err(NjilError::ExecutionError(tip::exponent_too_large(MAX_EXPONENT))),
+            };
+            if let Some(base_integer) = integer::as_integer(base) {
+                let result = num_traits::pow(base_integer, power);
+                return integer_value(&result, integer::is_bigint(base), "乘方");
+            }
+            if let Some(base_decimal) = as_decimal(base) {
+                return Ok(type_convert::decimal_value(&base_decimal.pow(power as u32)));
+            }
+        }
+
+        let base = to_float(base, self.name())?;
+        let exponent = to_float(exponent, self.name())?;
+        Ok(float_value(base.powf(exponent)))
+    }
+
+    fn name(&self) -> &str {
+        "math.pow"
+    }
+}
+
+/// 绝对值，结果的类型与参数相同
+pub struct AbsHandler;
+
+// 静态实例
+pub static ABS_HANDLER: AbsHandler = AbsHandler;
+
+impl StatementHandler for AbsHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let number = evaluate_single(interpreter, value, self.name())?;
+        if let Some(integer_number) = integer::as_integer(&number) {
+            return integer_value(&integer_number.abs(), integer::is_bigint(&number), "绝对值");
+        }
+        if let Some(decimal) = as_decimal(&number) {
+            return Ok(type_convert::decimal_value(&decimal.abs()));
+        }
+        Ok(float_value(to_float(&number, self.name())?.abs()))
+    }
+
+    fn name(&self) -> &str {
+        "math.abs"
+    }
+}
+
+/// 取整方向
+#[derive(Clone, Copy)]
+enum Direction {
+    Floor,
+    Ceil,
+    Trunc,
+}
+
+// 辅助函数：取整为整数，整数保持不变，定点小数的结果是小数位数为0的定点小数，浮点数的结果是整数
+fn round_to_integer(value: &Value, instruction: &str, direction: Direction) -> Result<Value, NjilError> {
+    if integer::as_integer(value).is_some() {
+        return Ok(value.clone());
+    }
+    if let Some(decimal) = as_decimal(value) {
+        let result = match direction {
+            Direction::Floor => decimal.round(0, Rounding::Floor),
+            Direction::Ceil => decimal.neg().round(0, Rounding::Floor).neg(),
+            Direction::Trunc => Decimal::new(decimal.trunc(), 0),
+        };
+        return Ok(type_convert::decimal_value(&result));
+    }
+    let number = to_float(value, instruction)?;
+    Ok(float_to_integer(match direction {
+        Direction::Floor => number.floor(),
+        Direction::Ceil => number.ceil(),
+        Direction::Trunc => number.trunc(),
+    }))
+}
+
+/// 向下取整
+pub struct FloorHandler;
+/// 向上取整
+pub struct CeilHandler;
+/// 向零取整
+pub struct TruncHandler;
+
+// 静态实例
+pub static FLOOR_HANDLER: FloorHandler = FloorHandler;
+pub static CEIL_HANDLER: CeilHandler = CeilHandler;
+pub static TRUNC_HANDLER: TruncHandler = TruncHandler;
+
+impl StatementHandler for FloorHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let number = evaluate_single(interpreter, value, self.name())?;
+        round_to_integer(&number, self.name(), Direction::Floor)
+    }
+
+    fn name(&self) -> &str {
+        "math.floor"
+    }
+}
+
+impl StatementHandler for CeilHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let number = evaluate_single(interpreter, value, self.name())?;
+        round_to_integer(&number, self.name(), Direction::Ceil)
+    }
+
+    fn name(&self) -> &str {
+        "math.ceil"
+    }
+}
+
+impl StatementHandler for TruncHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let number = evaluate_single(interpreter, value, self.name())?;
+        round_to_integer(&number, self.name(), Direction::Trunc)
+    }
+
+    fn name(&self) -> &str {
+        "math.trunc"
+    }
+}
+
+/// 四舍五入：`值`、`[值, 小数位数]` 或 `{"value", "digits"}`
+///
+/// 0.5远离零舍入。digits默认为0，可以为负数（舍入到十位、百位等）。浮点数按最短的十进制写法舍入，
+/// 所以1.005保留两位是1.01。digits不大于0时浮点数的结果是整数。
+pub struct RoundHandler;
+
+// 静态实例
+pub static ROUND_HANDLER: RoundHandler = RoundHandler;
+
+impl StatementHandler for RoundHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let arguments = evaluate_arguments(interpreter, value, self.name(), &["value", "digits"], 1)?;
+        let number = &arguments[0];
+        let digits = match &arguments[1] {
+            Value::Null => 0,
+            digits => match digits.as_i64() {
+                Some(digits) if digits.unsigned_abs() <= MAX_SCALE as u64 => digits,
+                _ => return Err(NjilError::ExecutionError(tip::digits_invalid(MAX_SCALE))),
+            },
+        };
+
+        if let Some(decimal) = as_decimal(number) {
+            return Ok(type_convert::decimal_value(&round_decimal(&decimal, digits)));
+        }
+        if let Some(integer_number) = integer::as_integer(number) {
+            if digits >= 0 {
+                return Ok(number.clone());
+            }
+            let rounded = round_decimal(&Decimal::new(integer_number, 0), digits);
+            return integer_value(&rounded.trunc(), integer::is_bigint(number), "四舍五入");
+        }
+
+        let float = to_float(number, self.name())?;
+        let Some(decimal) = Decimal::from_f64(float) else {
+            return Ok(float_value(float));
+        };
+        let rounded = round_decimal(&decimal, digits);
+        if digits > 0 {
+            Ok(float_value(rounded.to_f64()))
+        } else {
+            Ok(float_to_integer(rounded.to_f64()))
+        }
+    }
+
+    fn name(&self) -> &str {
+        "math.round"
+    }
+}
+
+// 辅助函数：四舍五入到digits位小数，digits为负数时舍入到10^-digits的倍数
+fn round_decimal(number: &Decimal, digits: i64) -> Decimal {
+    if digits >= 0 {
+        return number.round(digits as u32, Rounding::HalfUp);
+    }
+    let factor = Decimal::parse(&format!("1e{}", -digits)).unwrap();
+    number.div(&factor, 0, Rounding::HalfUp).unwrap().mul(&factor)
+}
+
+/// 单个参数的浮点数函数
+pub struct FloatFunctionHandler {
+    name: &'static str,
+    function: fn(f64) -> f64,
+}
+
+// 静态实例
+pub static SQRT_HANDLER: FloatFunctionHandler = FloatFunctionHandler { name: "math.sqrt", function: f64::sqrt };
+pub static SIN_HANDLER: FloatFunctionHandler = FloatFunctionHandler { name: "math.sin", function: f64::sin };
+pub static COS_HANDLER: FloatFunctionHandler = FloatFunctionHandler { name: "math.cos", function: f64::cos };
+pub static TAN_HANDLER: FloatFunctionHandler = FloatFunctionHandler { name: "math.tan", function: f64::tan };
+pub static LN_HANDLER: FloatFunctionHandler = FloatFunctionHandler { name: "math.ln", function: f64::ln };
+pub static EXP_HANDLER: FloatFunctionHandler = FloatFunctionHandler { name: "math.exp", function: f64::exp };
+
+impl StatementHandler for FloatFunctionHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let number = evaluate_single(interpreter, value, self.name)?;
+        Ok(float_value((self.function)(to_float(&number, self.name)?)))
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+/// 反正切：`[y, x]` 或 `{"y", "x"}`，结果为弧度
+pub struct Atan2Handler;
+
+// 静态实例
+pub static ATAN2_HANDLER: Atan2Handler = Atan2Handler;
+
+impl StatementHandler for Atan2Handler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let arguments = evaluate_arguments(interpreter, value, self.name(), &["y", "x"], 2)?;
+        let y = to_float(&arguments[0], self.name())?;
+        let x = to_float(&arguments[1], self.name())?;
+        Ok(float_value(y.atan2(x)))
+    }
+
+    fn name(&self) -> &str {
+        "math.atan2"
+    }
+}
+
+/// 对数：`值`、`[值, 底数]` 或 `{"value", "base"}`，底数默认为10
+pub struct LogHandler;
+
+// 静态实例
+pub static LOG_HANDLER: LogHandler = LogHandler;
+
+impl StatementHandler for LogHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let arguments = evaluate_arguments(interpreter, value, self.name(), &["value", "base"], 1)?;
+        let number = to_float(&arguments[0], self.name())?;
+        let result = match &arguments[1] {
+            Value::Null => number.log10(),
+            base => match to_float(base, self.name())? {
+                2.0 => number.log2(),
+                10.0 => number.log10(),
+                base => number.ln() / base.ln(),
+            },
+        };
+        Ok(float_value(result))
+    }
+
+    fn name(&self) -> &str {
+        "math.log"
+    }
+}
+
+// 辅助函数：按比较结果选出一个操作数，没有操作数时返回null
+fn select_by(interpreter: &mut Interpreter, value: &Value, instruction: &str, wanted: Ordering) -> Result<Value, NjilError> {
+    let mut operands = evaluate_operands(interpreter, value)?.into_iter();
+    let Some(mut selected) = operands.next() else {
+        return Ok(Value::Null);
+    };
+    for operand in operands {
+        match type_convert::compare(&operand, &selected) {
+            Some(ordering) if ordering == wanted => selected = operand,
+            Some(_) => {},
+            None => return Err(NjilError::TypeError(tip::not_comparable(instruction))),
+        }
+    }
+    Ok(selected)
+}
+
+/// 最小值，参数为操作数数组或求值为数组的表达式
+pub struct MinHandler;
+/// 最大值，参数为操作数数组或求值为数组的表达式
+pub struct MaxHandler;
+
+// 静态实例
+pub static MIN_HANDLER: MinHandler = MinHandler;
+pub static MAX_HANDLER: MaxHandler = MaxHandler;
+
+impl StatementHandler for MinHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        select_by(interpreter, value, self.name(), Ordering::Less)
+    }
+
+    fn name(&self) -> &str {
+        "math.min"
+    }
+}
+
+impl StatementHandler for MaxHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        select_by(interpreter, value, self.name(), Ordering::Greater)
+    }
+
+    fn name(&self) -> &str {
+        "math.max"
+    }
+}
+
+/// 求和，参数为操作数数组或求值为数组的表达式，按math.add的规则相加
+pub struct SumHandler;
+
+// 静态实例
+pub static SUM_HANDLER: SumHandler = SumHandler;
+
+impl StatementHandler for SumHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let mut result = Value::from(0);
+        for operand in evaluate_operands(interpreter, value)? {
+            to_float(&operand, self.name())?;
+            result = type_convert::add(&result, &operand)?;
+        }
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "math.sum"
+    }
+}
+
+/// 把值限制在区间内：`[值, 最小值, 最大值]` 或 `{"value", "min", "max"}`
+pub struct ClampHandler;
+
+// 静态实例
+pub static CLAMP_HANDLER: ClampHandler = ClampHandler;
+
+impl StatementHandler for ClampHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let mut arguments = evaluate_arguments(interpreter, value, self.name(), &["value", "min", "max"], 3)?;
+        let compare = |left: &Value, right: &Value| {
+            type_convert::compare(left, right).ok_or_else(|| NjilError::TypeError(tip::not_comparable("math.clamp")))
+        };
+        let (max, min, number) = (arguments.pop().unwrap(), arguments.pop().unwrap(), arguments.pop().unwrap());
+        if compare(&min, &max)? == Ordering::Greater {
+            return Err(NjilError::ExecutionError(tip::clamp_range_invalid().to_string()));
+        }
+        if compare(&number, &min)? == Ordering::Less {
+            Ok(min)
+        } else if compare(&number, &max)? == Ordering::Greater {
+            Ok(max)
+        } else {
+            Ok(number)
+        }
+    }
+
+    fn name(&self) -> &str {
+        "math.clamp"
+    }
+}
+
+/// 欧几里得范数：所有操作数平方和的平方根，例如 `[3, 4]` 为5
+pub struct HypotHandler;
+
+// 静态实例
+pub static HYPOT_HANDLER: HypotHandler = HypotHandler;
+
+impl StatementHandler for HypotHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let mut result = 0.0_f64;
+        for operand in evaluate_operands(interpreter, value)? {
+            result = result.hypot(to_float(&operand, self.name())?);
+        }
+        Ok(float_value(result))
+    }
+
+    fn name(&self) -> &str {
+        "math.hypot"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(statement: Value) -> Result<Value, NjilError> {
+        let mut interpreter = Interpreter::new();
+        interpreter.import_all_builtin_modules()?;
+        interpreter.declare_variable("scores".to_string(), json!([72, 95.5, 88]));
+        crate::statements::handle_statement(&mut interpreter, &statement)
+    }
+
+    #[test]
+    fn test_powers_and_rounding() {
+        assert_eq!(run(json!({"math.pow": [2, 10]})).unwrap(), json!(1024));
+        assert_eq!(run(json!({"math.pow": {"base": 2, "exponent": 0.5}})).unwrap(), json!(std::f64::consts::SQRT_2));
+        assert!(run(json!({"math.pow": [10, 20]})).is_err());
+        assert_eq!(run(json!({"math.pow": [{"math.bigint": 10}, 20]})).unwrap(), json!({"$bigint": "100000000000000000000"}));
+        assert_eq!(run(json!({"math.pow": [{"math.decimal": "1.1"}, 2]})).unwrap(), json!({"$decimal": "1.21"}));
+        assert_eq!(run(json!({"math.sqrt": 16})).unwrap(), json!(4.0));
+        assert_eq!(run(json!({"math.sqrt": -1})).unwrap(), Value::Null);
+        assert_eq!(run(json!({"math.abs": -7})).unwrap(), json!(7));
+        assert_eq!(run(json!({"math.floor": -2.5})).unwrap(), json!(-3));
+        assert_eq!(run(json!({"math.ceil": 2.1})).unwrap(), json!(3));
+        assert_eq!(run(json!({"math.trunc": -2.7})).unwrap(), json!(-2));
+        assert_eq!(run(json!({"math.round": 2.5})).unwrap(), json!(3));
+        assert_eq!(run(json!({"math.round": [1.005, 2]})).unwrap(), json!(1.01));
+        assert_eq!(run(json!({"math.round": {"value": 1234, "digits": -2}})).unwrap(), json!(1200));
+        assert_eq!(run(json!({"math.round": [{"math.decimal": "2.345"}, 2]})).unwrap(), json!({"$decimal": "2.35"}));
+        assert!(run(json!({"math.sqrt": "abc"})).is_err());
+        assert!(run(json!({"math.round": {"digits": 2}})).is_err());
+    }
+
+    #[test]
+    fn test_trig_and_logs() {
+        assert_eq!(run(json!({"math.sin": 0})).unwrap(), json!(0.0));
+        assert_eq!(run(json!({"math.cos": {"const": "math.PI"}})).unwrap(), json!(-1.0));
+        assert_eq!(run(json!({"math.atan2": [1, 1]})).unwrap(), json!(std::f64::consts::FRAC_PI_4));
+        assert_eq!(run(json!({"math.log": 1000})).unwrap(), json!(3.0));
+        assert_eq!(run(json!({"math.log": [8, 2]})).unwrap(), json!(3.0));
+        assert_eq!(run(json!({"math.ln": {"const": "math.E"}})).unwrap(), json!(1.0));
+        assert_eq!(run(json!({"math.exp": 0})).unwrap(), json!(1.0));
+        assert_eq!(run(json!({"math.hypot": [3, 4]})).unwrap(), json!(5.0));
+        assert_eq!(run(json!({"expr": "math.PI * 2"})).unwrap(), json!(std::f64::consts::TAU));
+    }
+
+    #[test]
+    fn test_aggregates_and_clamp() {
+        assert_eq!(run(json!({"math.min": {"var": "scores"}})).unwrap(), json!(72));
+        assert_eq!(run(json!({"math.max": [3, {"math.add": [4, 5]}, 1]})).unwrap(), json!(9));
+        assert_eq!(run(json!({"math.sum": [1, 2, 3]})).unwrap(), json!(6));
+        assert_eq!(run(json!({"math.sum": {"var": "scores"}})).unwrap(), json!(255.5));
+        assert_eq!(run(json!({"math.min": []})).unwrap(), Value::Null);
+        assert!(run(json!({"math.sum": [1, "a"]})).is_err());
+        assert_eq!(run(json!({"math.clamp": [15, 0, 10]})).unwrap(), json!(10));
+        assert_eq!(run(json!({"math.clamp": {"value": -1, "min": 0, "max": 10}})).unwrap(), json!(0));
+        assert_eq!(run(json!({"math.clamp": [5, 0, 10]})).unwrap(), json!(5));
+        assert!(run(json!({"math.clamp": [5, 10, 0]})).is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use serde_json::{Map, Value};
 use crate::error::NjilError;
 use crate::interpreter::Interpreter;
 
@@ -6,15 +8,24 @@ pub mod io;
 pub mod datetime;
 pub mod shell;
 pub mod system;
+pub mod array;
+pub mod object;
+pub mod regex;
+pub mod math;
 
 /// 内置模块特性，定义了内置模块应该实现的方法
 pub trait BuiltinModule {
     /// 获取模块名称
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
     
     /// 获取模块中所有的语句处理器
     fn get_handlers(&self) -> Vec<&'static dyn crate::statements::StatementHandler>;
     
+    /// 获取模块中持有状态的共享语句处理器（例如宿主程序注册的闭包）
+    fn get_shared_handlers(&self) -> Vec<Arc<dyn crate::statements::StatementHandler>> {
+        Vec::new()
+    }
+    
     /// 初始化模块
     /// 由于借用冲突问题，这个方法不直接操作解释器
     /// 而是返回一个可由调用者执行的初始化函数
@@ -24,8 +35,17 @@ pub trait BuiltinModule {
 }
 
 /// 内置模块注册表，用于管理和查找内置模块
+///
+/// 模块在副本之间共享，生成器和任务中的子解释器也可以导入宿主程序注册的模块。
+#[derive(Clone)]
 pub struct BuiltinModuleRegistry {
-    modules: HashMap<String, Box<dyn BuiltinModule + Send + Sync>>,
+    modules: HashMap<String, Arc<dyn BuiltinModule + Send + Sync>>,
+}
+
+impl Default for BuiltinModuleRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BuiltinModuleRegistry {
@@ -40,6 +60,10 @@ impl BuiltinModuleRegistry {
         registry.register_module(Box::new(datetime::DateTimeModule::new()));
         registry.register_module(Box::new(shell::ShellModule::new()));
         registry.register_module(Box::new(system::SystemModule::new()));
+        registry.register_module(Box::new(array::ArrayModule::new()));
+        registry.register_module(Box::new(object::ObjectModule::new()));
+        registry.register_module(Box::new(regex::RegexModule::new()));
+        registry.register_module(Box::new(math::MathModule::new()));
         
         registry
     }
@@ -47,12 +71,12 @@ impl BuiltinModuleRegistry {
     /// 注册一个内置模块
     pub fn register_module(&mut self, module: Box<dyn BuiltinModule + Send + Sync>) {
         let name = module.name().to_string();
-        self.modules.insert(name, module);
+        self.modules.insert(name, Arc::from(module));
     }
     
     /// 获取指定名称的内置模块
-    pub fn get_module(&self, name: &str) -> Option<&Box<dyn BuiltinModule + Send + Sync>> {
-        self.modules.get(name)
+    pub fn get_module(&self, name: &str) -> Option<&(dyn BuiltinModule + Send + Sync)> {
+        self.modules.get(name).map(|module| module.as_ref())
     }
     
     /// 获取所有内置模块的名称
@@ -66,4 +90,26 @@ impl BuiltinModuleRegistry {
             modules: HashMap::new(),
         }
     }
-} 
\ No newline at end of file
+}
+
+// 辅助函数：从参数对象的fn字段获取回调函数（函数名或函数值）
+pub(crate) fn get_callback(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Value, NjilError> {
+    let callback = obj.get("fn")
+        .ok_or_else(|| NjilError::ExecutionError(format!("{}缺少fn参数", instruction)))?;
+    let callback = match callback {
+        Value::String(_) => callback.clone(),
+        _ => interpreter.evaluate_value(callback)?,
+    };
+    if !callback.is_string() && !interpreter.is_callable(&callback) {
+        return Err(NjilError::ExecutionError(format!("{}的fn参数必须是函数名或函数值", instruction)));
+    }
+    Ok(callback)
+}
+
+// 辅助函数：调用回调函数，超出函数声明参数个数的参数会被忽略
+pub(crate) fn call_callback(interpreter: &mut Interpreter, callback: &Value, mut args: Vec<Value>) -> Result<Value, NjilError> {
+    if let Some(arity) = interpreter.callable_arity(callback) {
+        args.truncate(arity);
+    }
+    interpreter.call_value(callback, &args, &Map::new())
+}
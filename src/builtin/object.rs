@@ -0,0 +1,504 @@
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+use crate::utils::path;
+use crate::utils::type_convert::to_string;
+use super::{BuiltinModule, get_callback, call_callback};
+
+/// Object模块，提供对象的常用操作
+pub struct ObjectModule;
+
+impl Default for ObjectModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ObjectModule {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinModule for ObjectModule {
+    fn name(&self) -> &str {
+        "object"
+    }
+
+    fn get_handlers(&self) -> Vec<&'static dyn StatementHandler> {
+        vec![
+            &OBJECT_KEYS_HANDLER,
+            &OBJECT_VALUES_HANDLER,
+            &OBJECT_ENTRIES_HANDLER,
+            &OBJECT_FROM_ENTRIES_HANDLER,
+            &OBJECT_MERGE_HANDLER,
+            &OBJECT_DEEP_MERGE_HANDLER,
+            &OBJECT_PICK_HANDLER,
+            &OBJECT_OMIT_HANDLER,
+            &OBJECT_HAS_HANDLER,
+            &OBJECT_DELETE_HANDLER,
+            &OBJECT_MAP_VALUES_HANDLER,
+        ]
+    }
+}
+
+// 辅助函数：获取参数对象
+fn get_args<'a>(value: &'a Value, instruction: &str) -> Result<&'a Map<String, Value>, NjilError> {
+    match value {
+        Value::Object(obj) => Ok(obj),
+        _ => Err(NjilError::ExecutionError(format!("{}需要一个包含object字段的对象", instruction))),
+    }
+}
+
+// 辅助函数：获取并评估object字段
+fn get_object(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Map<String, Value>, NjilError> {
+    let object_value = obj.get("object")
+        .ok_or_else(|| NjilError::ExecutionError(format!("{}缺少object参数", instruction)))?;
+    match interpreter.evaluate_deep(object_value)? {
+        Value::Object(object) => Ok(object),
+        _ => Err(NjilError::ExecutionError(format!("{}的object参数必须是对象", instruction))),
+    }
+}
+
+// 辅助函数：获取对象，参数可以是求值为对象的表达式，也可以是包含object字段的对象
+fn get_object_or_args(interpreter: &mut Interpreter, value: &Value, instruction: &str) -> Result<Map<String, Value>, NjilError> {
+    if let Value::Object(obj) = value {
+        if obj.len() == 1 && obj.contains_key("object") {
+            return get_object(interpreter, obj, instruction);
+        }
+    }
+    match interpreter.evaluate_deep(value)? {
+        Value::Object(object) => Ok(object),
+        _ => Err(NjilError::ExecutionError(format!("{}需要一个对象", instruction))),
+    }
+}
+
+// 辅助函数：获取键名列表
+fn get_keys(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Vec<String>, NjilError> {
+    let keys_value = obj.get("keys")
+        .ok_or_else(|| NjilError::ExecutionError(format!("{}缺少keys参数", instruction)))?;
+    match interpreter.evaluate_value(keys_value)? {
+        Value::Array(keys) => Ok(keys.iter().map(to_string).collect()),
+        Value::String(key) => Ok(vec![key]),
+        _ => Err(NjilError::ExecutionError(format!("{}的keys参数必须是键名数组", instruction))),
+    }
+}
+
+// 辅助函数：获取path参数并解析为路径
+fn get_path(interpreter: &mut Interpreter, obj: &Map<String, Value>, instruction: &str) -> Result<Vec<path::PathPart>, NjilError> {
+    let path_value = obj.get("path")
+        .ok_or_else(|| NjilError::ExecutionError(format!("{}缺少path参数", instruction)))?;
+    match interpreter.evaluate_value(path_value)? {
+        Value::String(path_str) => path::parse_path(&path_str),
+        _ => Err(NjilError::ExecutionError(format!("{}的path参数必须是字符串", instruction))),
+    }
+}
+
+/// 合并冲突时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MergeStrategy {
+    /// 后面的值覆盖前面的值（默认）
+    Overwrite,
+    /// 保留先出现的值
+    Keep,
+    /// 两个数组冲突时拼接，其他情况覆盖
+    Concat,
+    /// 出现冲突时报错
+    Error,
+}
+
+impl MergeStrategy {
+    fn parse(name: &str) -> Result<Self, NjilError> {
+        match name {
+            "overwrite" => Ok(Self::Overwrite),
+            "keep" => Ok(Self::Keep),
+            "concat" => Ok(Self::Concat),
+            "error" => Ok(Self::Error),
+            _ => Err(NjilError::ExecutionError(format!("不支持的合并策略: {}，可选值为overwrite、keep、concat、error", name))),
+        }
+    }
+}
+
+// 辅助函数：把source合并到target中，deep为true时递归合并嵌套对象
+fn merge_into(target: &mut Map<String, Value>, source: Map<String, Value>, deep: bool, strategy: MergeStrategy, prefix: &str) -> Result<(), NjilError> {
+    for (key, value) in source {
+        let key_path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        let Some(existing) = target.get_mut(&key) else {
+            target.insert(key, value);
+            continue;
+        };
+
+        match (existing, value) {
+            (Value::Object(existing), Value::Object(value)) if deep => {
+                merge_into(existing, value, deep, strategy, &key_path)?;
+            },
+            (Value::Array(existing), Value::Array(value)) if strategy == MergeStrategy::Concat => {
+                existing.extend(value);
+            },
+            (existing, value) => match strategy {
+                MergeStrategy::Overwrite | MergeStrategy::Concat => *existing = value,
+                MergeStrategy::Keep => {},
+                MergeStrategy::Error => {
+                    return Err(NjilError::ExecutionError(format!("合并对象时键 {} 冲突", key_path)));
+                },
+            },
+        }
+    }
+    Ok(())
+}
+
+// 辅助函数：合并多个对象，供object.merge和object.deep_merge共用
+fn merge_objects(interpreter: &mut Interpreter, value: &Value, deep: bool, instruction: &str) -> Result<Value, NjilError> {
+    // 支持直接传入对象数组，或者 {"objects": [...], "strategy": "..."}
+    let (objects_value, strategy) = match value {
+        Value::Object(obj) if obj.contains_key("objects") => {
+            let strategy = match obj.get("strategy") {
+                Some(strategy) => match interpreter.evaluate_value(strategy)? {
+                    Value::String(strategy) => MergeStrategy::parse(&strategy)?,
+                    _ => return Err(NjilError::ExecutionError(format!("{}的strategy参数必须是字符串", instruction))),
+                },
+                None => MergeStrategy::Overwrite,
+            };
+            (interpreter.evaluate_deep(&obj["objects"])?, strategy)
+        },
+        _ => (interpreter.evaluate_deep(value)?, MergeStrategy::Overwrite),
+    };
+
+    let objects = match objects_value {
+        Value::Array(objects) => objects,
+        _ => return Err(NjilError::ExecutionError(format!("{}需要一个对象数组", instruction))),
+    };
+
+    let mut result = Map::new();
+    for object in objects {
+        match object {
+            Value::Object(object) => merge_into(&mut result, object, deep, strategy, "")?,
+            _ => return Err(NjilError::ExecutionError(format!("{}只能合并对象", instruction))),
+        }
+    }
+
+    Ok(Value::Object(result))
+}
+
+/// 获取对象的所有键
+pub struct ObjectKeysHandler;
+
+// 静态实例
+pub static OBJECT_KEYS_HANDLER: ObjectKeysHandler = ObjectKeysHandler;
+
+impl StatementHandler for ObjectKeysHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let object = get_object_or_args(interpreter, value, self.name())?;
+        Ok(Value::Array(object.into_iter().map(|(key, _)| Value::String(key)).collect()))
+    }
+
+    fn name(&self) -> &str {
+        "object.keys"
+    }
+}
+
+/// 获取对象的所有值
+pub struct ObjectValuesHandler;
+
+// 静态实例
+pub static OBJECT_VALUES_HANDLER: ObjectValuesHandler = ObjectValuesHandler;
+
+impl StatementHandler for ObjectValuesHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let object = get_object_or_args(interpreter, value, self.name())?;
+        Ok(Value::Array(object.into_iter().map(|(_, value)| value).collect()))
+    }
+
+    fn name(&self) -> &str {
+        "object.values"
+    }
+}
+
+/// 获取对象的所有键值对，结果为 `[[键, 值], ...]`
+pub struct ObjectEntriesHandler;
+
+// 静态实例
+pub static OBJECT_ENTRIES_HANDLER: ObjectEntriesHandler = ObjectEntriesHandler;
+
+impl StatementHandler for ObjectEntriesHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let object = get_object_or_args(interpreter, value, self.name())?;
+        let entries = object.into_iter()
+            .map(|(key, value)| Value::Array(vec![Value::String(key), value]))
+            .collect();
+        Ok(Value::Array(entries))
+    }
+
+    fn name(&self) -> &str {
+        "object.entries"
+    }
+}
+
+/// 从键值对数组创建对象，键值对可以是 `[键, 值]` 或 `{"key": 键, "value": 值}`
+pub struct ObjectFromEntriesHandler;
+
+// 静态实例
+pub static OBJECT_FROM_ENTRIES_HANDLER: ObjectFromEntriesHandler = ObjectFromEntriesHandler;
+
+impl StatementHandler for ObjectFromEntriesHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let entries = match interpreter.evaluate_deep(value)? {
+            Value::Array(entries) => entries,
+            _ => return Err(NjilError::ExecutionError("object.from_entries需要一个键值对数组".to_string())),
+        };
+
+        let mut result = Map::new();
+        for entry in entries {
+            let (key, value) = match entry {
+                Value::Array(mut pair) if pair.len() == 2 => {
+                    let value = pair.pop().unwrap();
+                    (to_string(&pair[0]), value)
+                },
+                Value::Object(mut pair) if pair.contains_key("key") => {
+                    let value = pair.remove("value").unwrap_or(Value::Null);
+                    (to_string(&pair["key"]), value)
+                },
+                _ => return Err(NjilError::ExecutionError("object.from_entries的每个元素必须是[键, 值]或包含key和value的对象".to_string())),
+            };
+            result.insert(key, value);
+        }
+
+        Ok(Value::Object(result))
+    }
+
+    fn name(&self) -> &str {
+        "object.from_entries"
+    }
+}
+
+/// 浅合并多个对象
+pub struct ObjectMergeHandler;
+
+// 静态实例
+pub static OBJECT_MERGE_HANDLER: ObjectMergeHandler = ObjectMergeHandler;
+
+impl StatementHandler for ObjectMergeHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        merge_objects(interpreter, value, false, self.name())
+    }
+
+    fn name(&self) -> &str {
+        "object.merge"
+    }
+}
+
+/// 深合并多个对象，嵌套对象会递归合并
+pub struct ObjectDeepMergeHandler;
+
+// 静态实例
+pub static OBJECT_DEEP_MERGE_HANDLER: ObjectDeepMergeHandler = ObjectDeepMergeHandler;
+
+impl StatementHandler for ObjectDeepMergeHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        merge_objects(interpreter, value, true, self.name())
+    }
+
+    fn name(&self) -> &str {
+        "object.deep_merge"
+    }
+}
+
+/// 只保留指定的键
+pub struct ObjectPickHandler;
+
+// 静态实例
+pub static OBJECT_PICK_HANDLER: ObjectPickHandler = ObjectPickHandler;
+
+impl StatementHandler for ObjectPickHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let mut object = get_object(interpreter, obj, self.name())?;
+        let keys = get_keys(interpreter, obj, self.name())?;
+
+        let mut result = Map::new();
+        for key in keys {
+            if let Some(value) = object.remove(&key) {
+                result.insert(key, value);
+            }
+        }
+
+        Ok(Value::Object(result))
+    }
+
+    fn name(&self) -> &str {
+        "object.pick"
+    }
+}
+
+/// 移除指定的键
+pub struct ObjectOmitHandler;
+
+// 静态实例
+pub static OBJECT_OMIT_HANDLER: ObjectOmitHandler = ObjectOmitHandler;
+
+impl StatementHandler for ObjectOmitHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let mut object = get_object(interpreter, obj, self.name())?;
+        for key in get_keys(interpreter, obj, self.name())? {
+            object.remove(&key);
+        }
+
+        Ok(Value::Object(object))
+    }
+
+    fn name(&self) -> &str {
+        "object.omit"
+    }
+}
+
+/// 判断对象中是否存在指定路径，路径格式与变量路径相同（如 `a.b[0].c`）
+pub struct ObjectHasHandler;
+
+// 静态实例
+pub static OBJECT_HAS_HANDLER: ObjectHasHandler = ObjectHasHandler;
+
+impl StatementHandler for ObjectHasHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let object = Value::Object(get_object(interpreter, obj, self.name())?);
+        let path_parts = get_path(interpreter, obj, self.name())?;
+
+        Ok(Value::Bool(path::find_nested_value(&object, &path_parts).is_some()))
+    }
+
+    fn name(&self) -> &str {
+        "object.has"
+    }
+}
+
+/// 删除对象中指定路径的值，返回删除后的新对象
+pub struct ObjectDeleteHandler;
+
+// 静态实例
+pub static OBJECT_DELETE_HANDLER: ObjectDeleteHandler = ObjectDeleteHandler;
+
+impl StatementHandler for ObjectDeleteHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let mut object = Value::Object(get_object(interpreter, obj, self.name())?);
+        let path_parts = get_path(interpreter, obj, self.name())?;
+
+        path::remove_nested_value(&mut object, &path_parts);
+        Ok(object)
+    }
+
+    fn name(&self) -> &str {
+        "object.delete"
+    }
+}
+
+/// 对对象的每个值调用函数，回调依次收到值和键
+pub struct ObjectMapValuesHandler;
+
+// 静态实例
+pub static OBJECT_MAP_VALUES_HANDLER: ObjectMapValuesHandler = ObjectMapValuesHandler;
+
+impl StatementHandler for ObjectMapValuesHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let obj = get_args(value, self.name())?;
+        let object = get_object(interpreter, obj, self.name())?;
+        let callback = get_callback(interpreter, obj, self.name())?;
+
+        let mut result = Map::new();
+        for (key, value) in object {
+            let mapped = call_callback(interpreter, &callback, vec![value, Value::String(key.clone())])?;
+            result.insert(key, mapped);
+        }
+
+        Ok(Value::Object(result))
+    }
+
+    fn name(&self) -> &str {
+        "object.map_values"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(handler: &dyn StatementHandler, args: Value) -> Result<Value, NjilError> {
+        handler.handle(&mut Interpreter::new(), &args)
+    }
+
+    #[test]
+    fn test_keys_values_entries() {
+        let object = json!({"a": 1, "b": 2});
+        assert_eq!(run(&OBJECT_KEYS_HANDLER, object.clone()).unwrap(), json!(["a", "b"]));
+        assert_eq!(run(&OBJECT_VALUES_HANDLER, json!({"object": object.clone()})).unwrap(), json!([1, 2]));
+
+        let entries = run(&OBJECT_ENTRIES_HANDLER, object.clone()).unwrap();
+        assert_eq!(entries, json!([["a", 1], ["b", 2]]));
+        assert_eq!(run(&OBJECT_FROM_ENTRIES_HANDLER, entries).unwrap(), object);
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let objects = json!([
+            {"name": "app", "db": {"host": "localhost", "port": 5432}, "tags": ["a"]},
+            {"db": {"port": 6543}, "tags": ["b"]}
+        ]);
+
+        assert_eq!(
+            run(&OBJECT_MERGE_HANDLER, objects.clone()).unwrap(),
+            json!({"name": "app", "db": {"port": 6543}, "tags": ["b"]})
+        );
+        assert_eq!(
+            run(&OBJECT_DEEP_MERGE_HANDLER, objects.clone()).unwrap(),
+            json!({"name": "app", "db": {"host": "localhost", "port": 6543}, "tags": ["b"]})
+        );
+        assert_eq!(
+            run(&OBJECT_DEEP_MERGE_HANDLER, json!({"objects": objects.clone(), "strategy": "keep"})).unwrap(),
+            json!({"name": "app", "db": {"host": "localhost", "port": 5432}, "tags": ["a"]})
+        );
+        assert_eq!(
+            run(&OBJECT_MERGE_HANDLER, json!({"objects": objects.clone(), "strategy": "concat"})).unwrap()["tags"],
+            json!(["a", "b"])
+        );
+
+        let err = run(&OBJECT_DEEP_MERGE_HANDLER, json!({"objects": objects, "strategy": "error"})).unwrap_err();
+        assert!(err.to_string().contains("db.port"));
+    }
+
+    #[test]
+    fn test_pick_omit_has_delete() {
+        let object = json!({"a": 1, "b": {"c": [1, {"d": null}]}, "e": 3});
+
+        assert_eq!(run(&OBJECT_PICK_HANDLER, json!({"object": object.clone(), "keys": ["a", "e", "x"]})).unwrap(), json!({"a": 1, "e": 3}));
+        assert_eq!(run(&OBJECT_OMIT_HANDLER, json!({"object": object.clone(), "keys": ["b"]})).unwrap(), json!({"a": 1, "e": 3}));
+
+        assert_eq!(run(&OBJECT_HAS_HANDLER, json!({"object": object.clone(), "path": "b.c[1].d"})).unwrap(), json!(true));
+        assert_eq!(run(&OBJECT_HAS_HANDLER, json!({"object": object.clone(), "path": "b.c[2]"})).unwrap(), json!(false));
+
+        assert_eq!(
+            run(&OBJECT_DELETE_HANDLER, json!({"object": object, "path": "b.c[1]"})).unwrap(),
+            json!({"a": 1, "b": {"c": [1]}, "e": 3})
+        );
+    }
+
+    #[test]
+    fn test_nested_statements_are_evaluated() {
+        let mut interpreter = Interpreter::new();
+        interpreter.declare_variable("x".to_string(), json!({"b": 2}));
+        interpreter.declare_variable("k".to_string(), json!("c"));
+
+        let object = json!({"object": {"a": {"var": "x"}, "n": [{"var": "k"}]}});
+        assert_eq!(OBJECT_VALUES_HANDLER.handle(&mut interpreter, &object).unwrap(), json!([{"b": 2}, ["c"]]));
+        assert_eq!(
+            OBJECT_MERGE_HANDLER.handle(&mut interpreter, &json!([{"a": 1}, {"var": "x"}])).unwrap(),
+            json!({"a": 1, "b": 2})
+        );
+        assert_eq!(
+            OBJECT_FROM_ENTRIES_HANDLER.handle(&mut interpreter, &json!([[{"var": "k"}, 3]])).unwrap(),
+            json!({"c": 3})
+        );
+    }
+}
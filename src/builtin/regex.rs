@@ -0,0 +1,298 @@
+use regex::{Captures, Regex};
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert::{to_number, to_string};
+use super::BuiltinModule;
+
+/// Regex模块，提供正则表达式匹配、替换和分割功能
+pub struct RegexModule;
+
+impl Default for RegexModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegexModule {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl BuiltinModule for RegexModule {
+    fn name(&self) -> &str {
+        "regex"
+    }
+
+    fn get_handlers(&self) -> Vec<&'static dyn StatementHandler> {
+        vec![
+            &REGEX_TEST_HANDLER,
+            &REGEX_MATCH_HANDLER,
+            &REGEX_MATCH_ALL_HANDLER,
+            &REGEX_REPLACE_HANDLER,
+            &REGEX_SPLIT_HANDLER,
+        ]
+    }
+}
+
+// 辅助函数：解析参数，返回编译好的正则表达式、待匹配的文本和参数对象
+fn parse_args<'a>(interpreter: &mut Interpreter, value: &'a Value, instruction: &str) -> Result<(Regex, String, &'a Map<String, Value>), NjilError> {
+    let obj = match value {
+        Value::Object(obj) => obj,
+        _ => return Err(NjilError::ExecutionError(format!("{}需要一个包含pattern和text字段的对象", instruction))),
+    };
+
+    let pattern = match obj.get("pattern") {
+        Some(pattern) => match interpreter.evaluate_value(pattern)? {
+            Value::String(pattern) => pattern,
+            _ => return Err(NjilError::ExecutionError(format!("{}的pattern参数必须是字符串", instruction))),
+        },
+        None => return Err(NjilError::ExecutionError(format!("{}缺少pattern参数", instruction))),
+    };
+
+    let flags = match obj.get("flags") {
+        Some(flags) => match interpreter.evaluate_value(flags)? {
+            Value::String(flags) => flags,
+            _ => return Err(NjilError::ExecutionError(format!("{}的flags参数必须是字符串", instruction))),
+        },
+        None => String::new(),
+    };
+
+    let text = match obj.get("text") {
+        Some(text) => match interpreter.evaluate_value(text)? {
+            Value::String(text) => text,
+            other => to_string(&other),
+        },
+        None => return Err(NjilError::ExecutionError(format!("{}缺少text参数", instruction))),
+    };
+
+    let regex = interpreter.compile_regex(&pattern, &flags)?;
+    Ok((regex, text, obj))
+}
+
+// 辅助函数：把一次匹配转换为对象
+//
+// 结果为 {"match": 匹配的文本, "index": 起始位置, "groups": {"1": ..., "name": ...}}，
+// 未参与匹配的捕获组值为null。index按字符计算，由offset从匹配的字节位置换算。
+fn captures_to_value(regex: &Regex, text: &str, captures: &Captures, offset: &mut CharOffset) -> Value {
+    let whole = captures.get(0).unwrap();
+
+    let mut groups = Map::new();
+    for (i, name) in regex.capture_names().enumerate().skip(1) {
+        let group = captures.get(i)
+            .map(|m| Value::String(m.as_str().to_string()))
+            .unwrap_or(Value::Null);
+        if let Some(name) = name {
+            groups.insert(name.to_string(), group.clone());
+        }
+        groups.insert(i.to_string(), group);
+    }
+
+    let mut result = Map::new();
+    result.insert("match".to_string(), Value::String(whole.as_str().to_string()));
+    result.insert("index".to_string(), Value::from(offset.chars_at(text, whole.start())));
+    result.insert("groups".to_string(), Value::Object(groups));
+    Value::Object(result)
+}
+
+// 辅助函数：把递增的字节位置换算为字符位置，每次只统计上一个位置之后的字符
+#[derive(Default)]
+struct CharOffset {
+    byte: usize,
+    chars: usize,
+}
+
+impl CharOffset {
+    fn chars_at(&mut self, text: &str, byte: usize) -> usize {
+        self.chars += text[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.chars
+    }
+}
+
+/// 判断文本是否匹配正则表达式
+pub struct RegexTestHandler;
+
+// 静态实例
+pub static REGEX_TEST_HANDLER: RegexTestHandler = RegexTestHandler;
+
+impl StatementHandler for RegexTestHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (regex, text, _) = parse_args(interpreter, value, self.name())?;
+        Ok(Value::Bool(regex.is_match(&text)))
+    }
+
+    fn name(&self) -> &str {
+        "regex.test"
+    }
+}
+
+/// 查找第一个匹配，找不到时返回null
+pub struct RegexMatchHandler;
+
+// 静态实例
+pub static REGEX_MATCH_HANDLER: RegexMatchHandler = RegexMatchHandler;
+
+impl StatementHandler for RegexMatchHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (regex, text, _) = parse_args(interpreter, value, self.name())?;
+        Ok(regex.captures(&text)
+            .map(|captures| captures_to_value(&regex, &text, &captures, &mut CharOffset::default()))
+            .unwrap_or(Value::Null))
+    }
+
+    fn name(&self) -> &str {
+        "regex.match"
+    }
+}
+
+/// 查找所有匹配
+pub struct RegexMatchAllHandler;
+
+// 静态实例
+pub static REGEX_MATCH_ALL_HANDLER: RegexMatchAllHandler = RegexMatchAllHandler;
+
+impl StatementHandler for RegexMatchAllHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (regex, text, _) = parse_args(interpreter, value, self.name())?;
+        let mut offset = CharOffset::default();
+        let matches = regex.captures_iter(&text)
+            .map(|captures| captures_to_value(&regex, &text, &captures, &mut offset))
+            .collect();
+        Ok(Value::Array(matches))
+    }
+
+    fn name(&self) -> &str {
+        "regex.matchAll"
+    }
+
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["regex.match_all"]
+    }
+}
+
+/// 替换匹配的文本
+///
+/// replacement中可以使用 `$1`、`${name}` 引用捕获组（后面紧跟字母或数字时需要写成 `${1}`）。
+/// 默认替换所有匹配，`"all": false` 时只替换第一个。
+pub struct RegexReplaceHandler;
+
+// 静态实例
+pub static REGEX_REPLACE_HANDLER: RegexReplaceHandler = RegexReplaceHandler;
+
+impl StatementHandler for RegexReplaceHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (regex, text, obj) = parse_args(interpreter, value, self.name())?;
+
+        let replacement = match obj.get("replacement") {
+            Some(replacement) => match interpreter.evaluate_value(replacement)? {
+                Value::String(replacement) => replacement,
+                other => to_string(&other),
+            },
+            None => return Err(NjilError::ExecutionError("regex.replace缺少replacement参数".to_string())),
+        };
+        let replace_all = match obj.get("all") {
+            Some(all) => interpreter.evaluate_value(all)?.as_bool().unwrap_or(true),
+            None => true,
+        };
+
+        let result = if replace_all {
+            regex.replace_all(&text, replacement.as_str())
+        } else {
+            regex.replace(&text, replacement.as_str())
+        };
+        Ok(Value::String(result.into_owned()))
+    }
+
+    fn name(&self) -> &str {
+        "regex.replace"
+    }
+}
+
+/// 按正则表达式分割文本，limit限制结果的最大数量
+pub struct RegexSplitHandler;
+
+// 静态实例
+pub static REGEX_SPLIT_HANDLER: RegexSplitHandler = RegexSplitHandler;
+
+impl StatementHandler for RegexSplitHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (regex, text, obj) = parse_args(interpreter, value, self.name())?;
+
+        let parts: Vec<Value> = match obj.get("limit") {
+            Some(limit) => {
+                let limit = to_number(&interpreter.evaluate_value(limit)?)
+                    .filter(|limit| *limit >= 1.0)
+                    .ok_or_else(|| NjilError::ExecutionError("regex.split的limit参数必须是大于0的数字".to_string()))?;
+                regex.splitn(&text, limit as usize).map(|part| Value::String(part.to_string())).collect()
+            },
+            None => regex.split(&text).map(|part| Value::String(part.to_string())).collect(),
+        };
+        Ok(Value::Array(parts))
+    }
+
+    fn name(&self) -> &str {
+        "regex.split"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_match_with_groups() {
+        let mut interpreter = Interpreter::new();
+        let args = json!({
+            "pattern": r"(?P<level>[A-Z]+) (\d+)ms",
+            "text": "[app] INFO 120ms, WARN 800ms"
+        });
+
+        assert_eq!(REGEX_TEST_HANDLER.handle(&mut interpreter, &args).unwrap(), json!(true));
+        assert_eq!(
+            REGEX_MATCH_HANDLER.handle(&mut interpreter, &args).unwrap(),
+            json!({"match": "INFO 120ms", "index": 6, "groups": {"1": "INFO", "level": "INFO", "2": "120"}})
+        );
+
+        let all = REGEX_MATCH_ALL_HANDLER.handle(&mut interpreter, &args).unwrap();
+        assert_eq!(all.as_array().unwrap().len(), 2);
+        assert_eq!(all[1]["groups"]["level"], json!("WARN"));
+
+        // index按字符计算
+        let all = REGEX_MATCH_ALL_HANDLER.handle(&mut interpreter, &json!({"pattern": r"[a-z]\d", "text": "日志 a1 日志 b2"})).unwrap();
+        let indexes: Vec<&Value> = all.as_array().unwrap().iter().map(|m| &m["index"]).collect();
+        assert_eq!(indexes, vec![&json!(3), &json!(9)]);
+    }
+
+    #[test]
+    fn test_replace_and_split() {
+        let mut interpreter = Interpreter::new();
+
+        let replaced = REGEX_REPLACE_HANDLER.handle(&mut interpreter, &json!({
+            "pattern": r"(\w+)@(\w+)\.com",
+            "text": "alice@example.com, bob@test.com",
+            "replacement": "$2:$1"
+        })).unwrap();
+        assert_eq!(replaced, json!("example:alice, test:bob"));
+
+        let first_only = REGEX_REPLACE_HANDLER.handle(&mut interpreter, &json!({
+            "pattern": "a", "flags": "i", "text": "AaA", "replacement": "-", "all": false
+        })).unwrap();
+        assert_eq!(first_only, json!("-aA"));
+
+        let parts = REGEX_SPLIT_HANDLER.handle(&mut interpreter, &json!({
+            "pattern": r"\s*,\s*", "text": "a , b,c", "limit": 2
+        })).unwrap();
+        assert_eq!(parts, json!(["a", "b,c"]));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let mut interpreter = Interpreter::new();
+        let result = REGEX_TEST_HANDLER.handle(&mut interpreter, &json!({"pattern": "(", "text": ""}));
+        assert!(result.is_err());
+    }
+}
@@ -2,7 +2,6 @@ use crate::error::NjilError;
 use crate::interpreter::Interpreter;
 use crate::statements::StatementHandler;
 use serde_json::Value;
-use std::collections::HashMap;
 use super::BuiltinModule;
 
 /// Shell模块，提供终端控制功能
@@ -10,6 +9,12 @@ pub struct ShellModule {
     handlers: Vec<&'static dyn StatementHandler>,
 }
 
+impl Default for ShellModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ShellModule {
     pub fn new() -> Self {
         Self {
@@ -27,7 +32,7 @@ impl ShellModule {
 }
 
 impl BuiltinModule for ShellModule {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell"
     }
 
@@ -172,7 +177,7 @@ impl StatementHandler for ColorHandler {
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.color"
     }
 }
@@ -191,7 +196,7 @@ impl StatementHandler for ClearLineHandler {
         Ok(Value::Null)
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.clear_line"
     }
 }
@@ -214,7 +219,7 @@ impl StatementHandler for WriteHandler {
         Ok(Value::Null)
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.write"
     }
 }
@@ -237,7 +242,7 @@ impl StatementHandler for WriteLineHandler {
         Ok(Value::Null)
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.write_line"
     }
 }
@@ -260,7 +265,7 @@ impl StatementHandler for OverwriteHandler {
         Ok(Value::Null)
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.overwrite"
     }
 }
@@ -339,7 +344,7 @@ impl StatementHandler for StyleHandler {
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.style"
     }
 }
@@ -440,7 +445,7 @@ impl StatementHandler for StyleColorHandler {
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "shell.style_color"
     }
     
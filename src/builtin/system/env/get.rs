@@ -8,7 +8,7 @@ use std::env;
 pub struct EnvGetHandler;
 
 impl StatementHandler for EnvGetHandler {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.env.get"
     }
 
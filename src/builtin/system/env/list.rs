@@ -8,7 +8,7 @@ use std::env;
 pub struct EnvListHandler;
 
 impl StatementHandler for EnvListHandler {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.env.list"
     }
 
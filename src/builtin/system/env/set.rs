@@ -8,7 +8,7 @@ use std::env;
 pub struct EnvSetHandler;
 
 impl StatementHandler for EnvSetHandler {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.env.set"
     }
 
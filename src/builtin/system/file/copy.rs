@@ -66,7 +66,7 @@ impl StatementHandler for FsCopyHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.copy"
     }
     
@@ -22,7 +22,7 @@ impl StatementHandler for FsExistsHandler {
         Ok(Value::Bool(exists))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.exists"
     }
     
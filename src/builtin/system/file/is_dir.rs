@@ -22,7 +22,7 @@ impl StatementHandler for FsIsDirHandler {
         Ok(Value::Bool(is_dir))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.isDir"
     }
     
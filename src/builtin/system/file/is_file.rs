@@ -22,7 +22,7 @@ impl StatementHandler for FsIsFileHandler {
         Ok(Value::Bool(is_file))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.isFile"
     }
     
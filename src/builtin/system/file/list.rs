@@ -42,7 +42,7 @@ impl StatementHandler for FsListHandler {
         Ok(Value::Array(result))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.list"
     }
     
@@ -45,7 +45,7 @@ impl StatementHandler for FsRemoveHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.remove"
     }
     
@@ -9,7 +9,7 @@ use std::path::Path;
 pub struct FsListHandler;
 
 impl StatementHandler for FsListHandler {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.list"
     }
 
@@ -40,7 +40,7 @@ impl StatementHandler for FsMkdirHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.mkdir"
     }
     
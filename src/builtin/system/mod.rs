@@ -42,6 +42,12 @@ pub use env::list::ENV_LIST_HANDLER;
 /// System模块，提供系统和环境相关功能
 pub struct SystemModule;
 
+impl Default for SystemModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SystemModule {
     pub fn new() -> Self {
         Self {}
@@ -49,7 +55,7 @@ impl SystemModule {
 }
 
 impl super::BuiltinModule for SystemModule {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system"
     }
     
@@ -47,7 +47,7 @@ impl StatementHandler for FsMoveHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.move"
     }
     
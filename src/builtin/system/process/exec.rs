@@ -4,6 +4,7 @@ use crate::statements::StatementHandler;
 use serde_json::Value;
 use std::process::Command;
 use std::collections::HashMap;
+use super::ParsedCommand;
 
 /// 执行命令并等待完成处理器
 pub struct ProcessExecHandler;
@@ -21,17 +22,19 @@ impl StatementHandler for ProcessExecHandler {
             #[cfg(target_os = "windows")]
             let mut cmd = Command::new("cmd");
             #[cfg(target_os = "windows")]
-            cmd.args(&["/C", &command]);
+            cmd.args(["/C", &command]).args(&args);
             
+            // 参数作为位置参数传给shell并通过"$@"原样追加到命令后，不会被shell再次解析
             #[cfg(not(target_os = "windows"))]
             let mut cmd = Command::new("sh");
             #[cfg(not(target_os = "windows"))]
-            cmd.args(&["-c", &command]);
-            
-            // 添加参数和环境变量
-            if !args.is_empty() {
-                cmd.args(args);
+            if args.is_empty() {
+                cmd.args(["-c", &command]);
+            } else {
+                cmd.args(["-c", &format!("{} \"$@\"", command), "sh"]).args(args);
             }
+            
+            // 添加环境变量
             if !env.is_empty() {
                 cmd.envs(env);
             }
@@ -80,7 +83,7 @@ impl StatementHandler for ProcessExecHandler {
         Ok(Value::Object(result))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.process.exec"
     }
     
@@ -90,7 +93,7 @@ impl StatementHandler for ProcessExecHandler {
 }
 
 // 辅助函数：解析命令和参数
-fn parse_command_args(interpreter: &mut Interpreter, value: &Value) -> Result<(String, Vec<String>, HashMap<String, String>, bool), NjilError> {
+fn parse_command_args(interpreter: &mut Interpreter, value: &Value) -> Result<ParsedCommand, NjilError> {
     match value {
         Value::String(cmd) => {
             // 如果只提供了字符串，那就直接作为命令执行
@@ -222,5 +225,19 @@ mod tests {
         } else {
             panic!("Expected object result");
         }
+        
+        // 测试shell模式下参数中的特殊字符不会被shell解析
+        #[cfg(not(target_os = "windows"))]
+        {
+            let mut obj = serde_json::Map::new();
+            obj.insert("command".to_string(), Value::String("echo".to_string()));
+            obj.insert("args".to_string(), serde_json::json!(["a; echo injected", "$(echo b)"]));
+            
+            let result = PROCESS_EXEC_HANDLER.handle(
+                &mut interpreter,
+                &Value::Object(obj)
+            ).unwrap();
+            assert_eq!(result.get("stdout").and_then(Value::as_str).map(str::trim), Some("a; echo injected $(echo b)"));
+        }
     }
-} 
\ No newline at end of file
+}   
\ No newline at end of file
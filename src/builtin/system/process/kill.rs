@@ -47,7 +47,7 @@ impl StatementHandler for ProcessKillHandler {
         Ok(Value::Bool(success))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.process.kill"
     }
     
@@ -73,7 +73,7 @@ fn kill_process(pid: u64) -> Result<bool, NjilError> {
     {
         // 在Linux/macOS上使用kill命令
         let output = Command::new("kill")
-            .args(&["-9", &pid.to_string()])
+            .args(["-9", &pid.to_string()])
             .output()
             .map_err(|e| NjilError::ExecutionError(format!("执行kill命令失败: {}", e)))?;
         
@@ -120,13 +120,9 @@ mod tests {
                 ).unwrap();
                 
                 // 验证结果
-                if let Value::Bool(success) = kill_result {
-                    // 在某些情况下可能无法终止进程，所以不严格要求成功
-                    // 但至少应该返回一个布尔值
-                    assert!(success || !success); // 简单检查它是个布尔值
-                } else {
-                    panic!("Expected boolean result");
-                }
+                // 在某些情况下可能无法终止进程，所以不严格要求成功
+                // 但至少应该返回一个布尔值
+                assert!(kill_result.is_boolean(), "Expected boolean result");
             } else {
                 panic!("Expected numeric pid");
             }
@@ -19,7 +19,7 @@ impl StatementHandler for ProcessListHandler {
         Ok(Value::Array(processes))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.process.list"
     }
     
@@ -72,7 +72,7 @@ fn get_process_list() -> Result<Vec<Value>, NjilError> {
     {
         // 在Linux上使用ps命令
         let output = Command::new("ps")
-            .args(&["-e", "-o", "pid,comm,rss", "--no-headers"])
+            .args(["-e", "-o", "pid,comm,rss", "--no-headers"])
             .output()
             .map_err(|e| NjilError::ExecutionError(format!("获取进程列表失败: {}", e)))?;
         
@@ -1,5 +1,8 @@
-use crate::error::NjilError;
 use crate::statements::StatementHandler;
+use std::collections::HashMap;
+
+/// 解析后的命令参数：(命令, 参数列表, 环境变量, 是否使用shell)
+pub(crate) type ParsedCommand = (String, Vec<String>, HashMap<String, String>, bool);
 
 // 导出子模块
 pub mod exec;
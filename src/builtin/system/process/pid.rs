@@ -19,7 +19,7 @@ impl StatementHandler for ProcessPidHandler {
         Ok(Value::Number(serde_json::Number::from(pid)))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.process.pid"
     }
     
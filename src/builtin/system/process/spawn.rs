@@ -4,6 +4,7 @@ use crate::statements::StatementHandler;
 use serde_json::Value;
 use std::process::{Command, Stdio};
 use std::collections::HashMap;
+use super::ParsedCommand;
 
 /// 启动命令但不等待处理器
 pub struct ProcessSpawnHandler;
@@ -26,7 +27,7 @@ impl StatementHandler for ProcessSpawnHandler {
             #[cfg(not(target_os = "windows"))]
             let mut cmd = Command::new("sh");
             #[cfg(not(target_os = "windows"))]
-            cmd.args(&["-c", &command]);
+            cmd.args(["-c", &command]);
             
             // 添加参数和环境变量
             if !args.is_empty() {
@@ -76,7 +77,7 @@ impl StatementHandler for ProcessSpawnHandler {
         Ok(Value::Object(result))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.process.spawn"
     }
     
@@ -86,7 +87,7 @@ impl StatementHandler for ProcessSpawnHandler {
 }
 
 // 辅助函数：解析命令和参数
-fn parse_command_args(interpreter: &mut Interpreter, value: &Value) -> Result<(String, Vec<String>, HashMap<String, String>, bool), NjilError> {
+fn parse_command_args(interpreter: &mut Interpreter, value: &Value) -> Result<ParsedCommand, NjilError> {
     match value {
         Value::String(cmd) => {
             // 如果只提供了字符串，那就直接作为命令执行
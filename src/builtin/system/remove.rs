@@ -45,7 +45,7 @@ impl StatementHandler for FsRemoveHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "system.fs.remove"
     }
     
@@ -58,7 +58,7 @@ impl StatementHandler for FsRemoveHandler {
 mod tests {
     use super::*;
     use crate::builtin::system::mkdir::FS_MKDIR_HANDLER;
-    use std::fs::File;
+    
     use tempfile::tempdir;
     
     #[test]
@@ -0,0 +1,512 @@
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::generator::{self, Generator};
+use crate::scope::{Scope, ScopeKind, ScopeRef};
+use crate::task;
+use crate::types::Function;
+
+/// 闭包值在JSON中的标记键
+///
+/// 闭包以 `{"$closure": id}` 的形式作为普通JSON值存储和传递，
+/// 实际的函数体和捕获的作用域保存在解释器的闭包表中。
+/// 编号是随机的，脚本不能通过手写标记得到其他闭包。
+pub const CLOSURE_KEY: &str = "$closure";
+
+/// 闭包编号的取值范围，保证编号可以用JSON数字精确表示
+const CLOSURE_ID_MASK: u64 = (1 << 53) - 1;
+
+/// 闭包表中的对象数达到这个值之前不回收
+const MIN_COLLECT_THRESHOLD: usize = 256;
+
+/// 闭包：函数定义加上定义时所在的作用域
+#[derive(Debug, Clone)]
+pub struct Closure {
+    /// 函数定义
+    pub function: Arc<Function>,
+
+    /// 定义闭包时捕获的作用域
+    pub scope: ScopeRef,
+
+    /// 函数名（匿名函数为None），用于错误提示
+    pub name: Option<String>,
+}
+
+impl Closure {
+    /// 获取用于错误提示的函数名
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("<匿名函数>")
+    }
+}
+
+// 闭包表中的一项，pins是被解释器固定的次数
+struct Entry {
+    handle: Handle,
+    pins: AtomicUsize,
+}
+
+// 闭包表中保存的对象
+enum Handle {
+    Closure(Closure),
+    // 生成器和它的函数帧，正在执行的生成器被取出，对应的值为None
+    Generator(Mutex<Option<Generator>>, Weak<Mutex<Scope>>),
+    // 任务，结果保存在任务表中，任务结束后设置标记
+    Task(Arc<AtomicBool>),
+}
+
+// 闭包表的内容
+struct Entries {
+    handles: HashMap<u64, Entry>,
+    next: u64,
+    threshold: usize,
+}
+
+// 登记的作用域（不含全局作用域），作用域数超过阈值时清理已经释放的
+struct Scopes {
+    scopes: Vec<Weak<Mutex<Scope>>>,
+    threshold: usize,
+}
+
+/// 闭包表，保存闭包、生成器和任务，与子解释器共享
+///
+/// 闭包值、生成器值和任务值只是带有编号的标记，表中的对象满足以下条件之一时存活：
+/// 被仍在使用的作用域（全局作用域、执行中的函数帧和块、存活的闭包捕获的作用域、存活的生成器的函数帧）中的变量引用，
+/// 或者被解释器固定（正在执行的语句持有的临时值、交给宿主程序的结果）。
+/// 对象数超过阈值时，加入新对象之前回收其余的对象；被回收的生成器像被丢弃一样关闭，
+/// 执行中的任务总是存活，结束后不再被引用的任务由任务表移除。
+pub(crate) struct ClosureTable {
+    entries: RwLock<Entries>,
+    scopes: Mutex<Scopes>,
+    generators: Arc<AtomicUsize>,
+    seed: RandomState,
+}
+
+impl Default for ClosureTable {
+    fn default() -> Self {
+        Self {
+            entries: RwLock::new(Entries { handles: HashMap::new(), next: 0, threshold: MIN_COLLECT_THRESHOLD }),
+            scopes: Mutex::new(Scopes { scopes: Vec::new(), threshold: MIN_COLLECT_THRESHOLD }),
+            generators: Arc::new(AtomicUsize::new(0)),
+            seed: RandomState::new(),
+        }
+    }
+}
+
+impl ClosureTable {
+    /// 获取编号对应的闭包
+    pub(crate) fn get(&self, id: u64) -> Option<Closure> {
+        match &self.entries.read().unwrap().handles.get(&id)?.handle {
+            Handle::Closure(closure) => Some(closure.clone()),
+            Handle::Generator(..) | Handle::Task(_) => None,
+        }
+    }
+    
+    /// 闭包表中是否有编号对应的闭包
+    pub(crate) fn contains(&self, id: u64) -> bool {
+        self.get(id).is_some()
+    }
+    
+    /// 闭包表中的对象数
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.read().unwrap().handles.len()
+    }
+    
+    /// 把闭包加入闭包表并固定到pins中，返回闭包值
+    ///
+    /// 对象数达到阈值时先回收不再使用的对象，global是解释器的全局作用域。
+    pub(crate) fn insert(&self, closure: Closure, pins: &mut Pins, global: &ScopeRef) -> Value {
+        closure_value(self.insert_handle(Handle::Closure(closure), pins, global))
+    }
+    
+    /// 把生成器加入闭包表并固定到pins中，返回生成器值
+    ///
+    /// frame是生成器的函数帧。生成器暂停时，函数帧和其中的块作用域只有在生成器存活时才被标记。
+    pub(crate) fn insert_generator(&self, generator: Generator, frame: &ScopeRef, pins: &mut Pins, global: &ScopeRef) -> Value {
+        let handle = Handle::Generator(Mutex::new(Some(generator)), Arc::downgrade(frame));
+        generator::generator_value(self.insert_handle(handle, pins, global))
+    }
+    
+    // 辅助函数：把对象加入闭包表并固定，返回编号
+    fn insert_handle(&self, handle: Handle, pins: &mut Pins, global: &ScopeRef) -> u64 {
+        let mut entries = self.entries.write().unwrap();
+        let removed = if entries.handles.len() >= entries.threshold {
+            self.collect_entries(&mut entries, global)
+        } else {
+            Vec::new()
+        };
+        
+        let id = loop {
+            let mut hasher = self.seed.build_hasher();
+            hasher.write_u64(entries.next);
+            entries.next += 1;
+            let id = hasher.finish() & CLOSURE_ID_MASK;
+            if !entries.handles.contains_key(&id) {
+                break id;
+            }
+        };
+        entries.handles.insert(id, Entry { handle, pins: AtomicUsize::new(1) });
+        pins.ids.push(id);
+        // 被回收的生成器在释放锁之后关闭，关闭时执行的语句也要使用闭包表
+        drop(entries);
+        drop(removed);
+        id
+    }
+    
+    /// 把任务加入闭包表并固定到pins中，返回编号，done在任务结束时设置
+    pub(crate) fn insert_task(&self, done: Arc<AtomicBool>, pins: &mut Pins, global: &ScopeRef) -> u64 {
+        self.insert_handle(Handle::Task(done), pins, global)
+    }
+    
+    /// 闭包表中是否有编号对应的任务，没有时任务的结果不会再被等待
+    pub(crate) fn contains_task(&self, id: u64) -> bool {
+        matches!(self.entries.read().unwrap().handles.get(&id), Some(Entry { handle: Handle::Task(_), .. }))
+    }
+    
+    /// 取出编号对应的生成器，执行完毕后由 `restore_generator` 放回
+    ///
+    /// 没有这个生成器时返回None，生成器正在执行时返回 `Some(None)`。
+    pub(crate) fn take_generator(&self, id: u64) -> Option<Option<Generator>> {
+        match &self.entries.read().unwrap().handles.get(&id)?.handle {
+            Handle::Generator(generator, _) => Some(generator.lock().unwrap().take()),
+            Handle::Closure(_) | Handle::Task(_) => None,
+        }
+    }
+    
+    /// 放回取出的生成器
+    pub(crate) fn restore_generator(&self, id: u64, generator: Generator) {
+        if let Some(Entry { handle: Handle::Generator(slot, _), .. }) = self.entries.read().unwrap().handles.get(&id) {
+            *slot.lock().unwrap() = Some(generator);
+        }
+    }
+    
+    /// 关闭并丢弃所有生成器
+    pub(crate) fn drop_generators(&self) {
+        let removed = {
+            let mut entries = self.entries.write().unwrap();
+            let ids: Vec<u64> = entries.handles.iter()
+                .filter(|(_, entry)| matches!(entry.handle, Handle::Generator(..)))
+                .map(|(id, _)| *id)
+                .collect();
+            remove_entries(&mut entries.handles, ids)
+        };
+        drop(removed);
+    }
+    
+    /// 未结束的生成器数的计数器，由生成器在创建和结束时维护
+    pub(crate) fn generator_counter(&self) -> Arc<AtomicUsize> {
+        self.generators.clone()
+    }
+    
+    /// 未结束的生成器数，每个未结束的生成器占用一个线程
+    pub(crate) fn live_generators(&self) -> usize {
+        self.generators.load(Ordering::Relaxed)
+    }
+    
+    /// 登记新创建的作用域，回收时据此找出表外的解释器正在使用的作用域
+    pub(crate) fn register_scope(&self, scope: &ScopeRef) {
+        let mut scopes = self.scopes.lock().unwrap();
+        if scopes.scopes.len() >= scopes.threshold {
+            scopes.scopes.retain(|scope| scope.strong_count() > 0);
+            scopes.threshold = (scopes.scopes.len() * 2).max(MIN_COLLECT_THRESHOLD);
+        }
+        scopes.scopes.push(Arc::downgrade(scope));
+    }
+    
+    /// 立即回收不再使用的闭包和生成器
+    pub(crate) fn collect(&self, global: &ScopeRef) {
+        let removed = self.collect_entries(&mut self.entries.write().unwrap(), global);
+        drop(removed);
+    }
+    
+    // 辅助函数：回收并重新计算下一次回收的阈值，返回移出的对象，由调用者在释放锁之后丢弃
+    fn collect_entries(&self, entries: &mut Entries, global: &ScopeRef) -> Vec<Entry> {
+        let scopes: Vec<ScopeRef> = self.scopes.lock().unwrap().scopes.iter().filter_map(Weak::upgrade).collect();
+        let removed = collect(&mut entries.handles, global, &scopes);
+        entries.threshold = (entries.handles.len() * 2).max(MIN_COLLECT_THRESHOLD);
+        removed
+    }
+    
+    // 辅助函数：解除一组固定
+    fn unpin(&self, ids: &[u64]) {
+        if ids.is_empty() {
+            return;
+        }
+        let entries = self.entries.read().unwrap();
+        for id in ids {
+            if let Some(entry) = entries.handles.get(id) {
+                entry.pins.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// 解释器固定的闭包、生成器和任务，按固定的顺序排列
+///
+/// 语句执行期间取得的值（读取的变量、创建的闭包、调用的返回值）中的闭包、生成器和任务被固定，
+/// 语句结束时只保留语句的值中的对象，其余的固定被解除。丢弃时解除所有固定。
+pub(crate) struct Pins {
+    table: Arc<ClosureTable>,
+    ids: Vec<u64>,
+}
+
+impl Pins {
+    /// 创建空的固定列表
+    pub(crate) fn new(table: Arc<ClosureTable>) -> Self {
+        Self { table, ids: Vec::new() }
+    }
+    
+    /// 固定值中的闭包，用于把值交给其他线程，由接收方通过 `adopt` 接管
+    pub(crate) fn of(table: &Arc<ClosureTable>, value: Option<&Value>) -> Self {
+        let mut pins = Self::new(table.clone());
+        if let Some(value) = value {
+            pin_value(&table.entries.read().unwrap(), value, &mut pins.ids);
+        }
+        pins
+    }
+    
+    /// 当前的固定数，作为之后 `release` 的位置
+    pub(crate) fn mark(&self) -> usize {
+        self.ids.len()
+    }
+    
+    /// 读取一个值并固定其中的闭包，读取和固定之间不会发生回收
+    pub(crate) fn read(&mut self, read: impl FnOnce() -> Option<Value>) -> Option<Value> {
+        let entries = self.table.entries.read().unwrap();
+        let value = read()?;
+        pin_value(&entries, &value, &mut self.ids);
+        Some(value)
+    }
+    
+    /// 解除mark之后的固定，keep中引用的闭包保持固定
+    pub(crate) fn release(&mut self, mark: usize, keep: Option<&Value>) {
+        if self.ids.len() <= mark {
+            return;
+        }
+        let mut kept = Vec::new();
+        if let Some(keep) = keep {
+            collect_ids(keep, &mut kept);
+        }
+        let mut kept: HashSet<u64> = kept.into_iter().collect();
+        let mut released = Vec::new();
+        let mut index = mark;
+        for i in mark..self.ids.len() {
+            let id = self.ids[i];
+            if kept.remove(&id) {
+                self.ids[index] = id;
+                index += 1;
+            } else {
+                released.push(id);
+            }
+        }
+        self.ids.truncate(index);
+        self.table.unpin(&released);
+    }
+    
+    /// 解除最早的count个固定，返回剩下的固定数
+    pub(crate) fn retire(&mut self, count: usize) -> usize {
+        let released: Vec<u64> = self.ids.drain(..count.min(self.ids.len())).collect();
+        self.table.unpin(&released);
+        self.ids.len()
+    }
+    
+    /// 接管其他线程交来的固定
+    pub(crate) fn adopt(&mut self, mut other: Pins) {
+        self.ids.append(&mut other.ids);
+    }
+}
+
+impl Drop for Pins {
+    fn drop(&mut self) {
+        self.table.unpin(&self.ids);
+    }
+}
+
+/// 创建指向闭包表中指定闭包的JSON值
+pub fn closure_value(id: u64) -> Value {
+    let mut marker = Map::new();
+    marker.insert(CLOSURE_KEY.to_string(), Value::from(id));
+    Value::Object(marker)
+}
+
+/// 如果值是闭包，返回其在闭包表中的编号
+pub fn closure_id(value: &Value) -> Option<u64> {
+    match value {
+        Value::Object(obj) if obj.len() == 1 => obj.get(CLOSURE_KEY)?.as_u64(),
+        _ => None,
+    }
+}
+
+/// 语句结束后仍然被使用的值：语句的值或抛出的值
+pub(crate) fn kept_value<'a>(result: Result<Option<&'a Value>, &'a NjilError>) -> Option<&'a Value> {
+    match result {
+        Ok(value) => value,
+        Err(error) => match error.root() {
+            NjilError::ThrowException(value) => Some(value),
+            _ => None,
+        },
+    }
+}
+
+// 辅助函数：固定值中引用的闭包、生成器和任务，把编号追加到ids
+fn pin_value(entries: &Entries, value: &Value, ids: &mut Vec<u64>) {
+    if entries.handles.is_empty() {
+        return;
+    }
+    let mut found = Vec::new();
+    collect_ids(value, &mut found);
+    for id in found {
+        if let Some(entry) = entries.handles.get(&id) {
+            entry.pins.fetch_add(1, Ordering::Relaxed);
+            ids.push(id);
+        }
+    }
+}
+
+// 辅助函数：收集值中引用的所有闭包、生成器和任务编号
+fn collect_ids(value: &Value, ids: &mut Vec<u64>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_ids(item, ids)),
+        Value::Object(obj) => match closure_id(value).or_else(|| generator::generator_id(value)).or_else(|| task::task_id(value)) {
+            Some(id) => ids.push(id),
+            None => obj.values().for_each(|item| collect_ids(item, ids)),
+        },
+        _ => {},
+    }
+}
+
+// 辅助函数：回收不再使用的闭包和生成器
+//
+// scopes是登记的作用域。引用计数多于闭包表和内层作用域对它的引用的作用域被表外的解释器使用，作为根；
+// 暂停中的生成器的函数帧和其中的块作用域只被生成器线程使用，不作为根，生成器被标记时才标记它们。
+// 从全局作用域、根作用域、被固定的对象、正在执行的生成器和任务出发，沿变量中的闭包值和生成器值、
+// 闭包捕获的作用域和外层作用域标记存活的对象，其余的对象被移出闭包表。
+// 被回收的生成器在释放闭包表的锁之后关闭并执行finally和defer，其中用到的闭包保留到下一次回收。
+fn collect(handles: &mut HashMap<u64, Entry>, global: &ScopeRef, scopes: &[ScopeRef]) -> Vec<Entry> {
+    let key = |scope: &ScopeRef| Arc::as_ptr(scope) as usize;
+    
+    // 作用域在闭包表和内层作用域中被引用的次数，以及作用域的类型和外层作用域
+    let mut internal: HashMap<usize, usize> = HashMap::new();
+    let mut links: HashMap<usize, (ScopeKind, Option<usize>)> = HashMap::new();
+    for scope in scopes {
+        let scope_ref = scope.lock().unwrap();
+        let parent = scope_ref.parent.as_ref().map(key);
+        if let Some(parent) = parent {
+            *internal.entry(parent).or_default() += 1;
+        }
+        links.insert(key(scope), (scope_ref.kind, parent));
+    }
+    
+    // 暂停中的生成器的函数帧，正在执行的生成器和任务作为根
+    let mut frames: HashMap<usize, u64> = HashMap::new();
+    let mut marked: HashSet<u64> = HashSet::new();
+    for (id, entry) in handles.iter() {
+        match &entry.handle {
+            Handle::Closure(closure) => *internal.entry(key(&closure.scope)).or_default() += 1,
+            Handle::Generator(generator, frame) => {
+                if generator.lock().unwrap().is_none() {
+                    marked.insert(*id);
+                } else {
+                    frames.insert(Weak::as_ptr(frame) as usize, *id);
+                }
+            },
+            Handle::Task(done) => {
+                if !done.load(Ordering::Relaxed) {
+                    marked.insert(*id);
+                }
+            },
+        }
+        if entry.pins.load(Ordering::Relaxed) > 0 {
+            marked.insert(*id);
+        }
+    }
+    
+    // 沿块作用域向外找到所属的暂停中的生成器
+    let mut owners: HashMap<usize, Option<u64>> = HashMap::new();
+    let mut owned: HashMap<u64, Vec<ScopeRef>> = HashMap::new();
+    let mut pending: Vec<ScopeRef> = vec![global.clone()];
+    for scope in scopes {
+        let mut path = Vec::new();
+        let mut current = Some(key(scope));
+        let owner = loop {
+            let Some(scope_key) = current else { break None };
+            if let Some(owner) = owners.get(&scope_key) {
+                break *owner;
+            }
+            path.push(scope_key);
+            if let Some(id) = frames.get(&scope_key) {
+                break Some(*id);
+            }
+            current = match links.get(&scope_key) {
+                Some((ScopeKind::Block, parent)) => *parent,
+                _ => None,
+            };
+        };
+        for scope_key in path {
+            owners.insert(scope_key, owner);
+        }
+        match owner {
+            Some(id) => owned.entry(id).or_default().push(scope.clone()),
+            None if Arc::strong_count(scope) > internal.get(&key(scope)).copied().unwrap_or(0) + 1 => pending.push(scope.clone()),
+            None => {},
+        }
+    }
+    
+    for id in &marked {
+        pending.extend(scopes_of(handles, &owned, *id));
+    }
+    trace(handles, &owned, pending, &mut marked);
+    
+    // 被回收的生成器关闭时仍会用到它的作用域中的闭包
+    let reclaimed: HashSet<u64> = handles.iter()
+        .filter(|(id, entry)| matches!(entry.handle, Handle::Generator(..)) && !marked.contains(id))
+        .map(|(id, _)| *id)
+        .collect();
+    let pending = reclaimed.iter().flat_map(|id| owned.get(id).cloned().unwrap_or_default()).collect();
+    trace(handles, &owned, pending, &mut marked);
+    
+    let ids: Vec<u64> = handles.keys()
+        .filter(|id| !marked.contains(id) || reclaimed.contains(id))
+        .copied()
+        .collect();
+    remove_entries(handles, ids)
+}
+
+// 辅助函数：把一组对象移出闭包表
+fn remove_entries(handles: &mut HashMap<u64, Entry>, ids: Vec<u64>) -> Vec<Entry> {
+    ids.into_iter().filter_map(|id| handles.remove(&id)).collect()
+}
+
+// 辅助函数：从pending中的作用域出发标记存活的对象
+fn trace(handles: &HashMap<u64, Entry>, owned: &HashMap<u64, Vec<ScopeRef>>, mut pending: Vec<ScopeRef>, marked: &mut HashSet<u64>) {
+    let mut visited: HashSet<usize> = HashSet::new();
+    while let Some(scope) = pending.pop() {
+        if !visited.insert(Arc::as_ptr(&scope) as usize) {
+            continue;
+        }
+        let scope = scope.lock().unwrap();
+        let mut ids = Vec::new();
+        for value in scope.variables.values() {
+            collect_ids(value, &mut ids);
+        }
+        for id in ids {
+            if handles.contains_key(&id) && marked.insert(id) {
+                pending.extend(scopes_of(handles, owned, id));
+            }
+        }
+        pending.extend(scope.parent.clone());
+    }
+}
+
+// 辅助函数：对象存活时需要继续标记的作用域
+fn scopes_of(handles: &HashMap<u64, Entry>, owned: &HashMap<u64, Vec<ScopeRef>>, id: u64) -> Vec<ScopeRef> {
+    match handles.get(&id).map(|entry| &entry.handle) {
+        Some(Handle::Closure(closure)) => vec![closure.scope.clone()],
+        Some(Handle::Generator(..)) => owned.get(&id).cloned().unwrap_or_default(),
+        Some(Handle::Task(_)) | None => Vec::new(),
+    }
+}
@@ -0,0 +1,61 @@
+use serde_json::Value;
+use crate::error::NjilError;
+use crate::errortip::control_flow as tip;
+
+/// 语句的完成状态
+///
+/// 语句正常结束时带有语句的值；return、loop.break和loop.continue会提前结束所在的语句序列，
+/// 分别由函数（或脚本）和循环处理。异常（throw和运行时错误）不属于完成状态，
+/// 而是通过 `Err(NjilError)` 传递，由try处理。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Completion {
+    /// 正常结束，带有语句的值
+    Normal(Value),
+    /// return，带有返回值
+    Return(Value),
+    /// loop.break，带有目标循环的标签，没有标签时指向最内层的循环
+    Break(Option<String>),
+    /// loop.continue，带有目标循环的标签，没有标签时指向最内层的循环
+    Continue(Option<String>),
+}
+
+impl Completion {
+    /// 正常结束或return时带有的值
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            Completion::Normal(value) | Completion::Return(value) => Some(value),
+            _ => None,
+        }
+    }
+    
+    /// 函数体或脚本中一条语句的结果：return时为返回值，正常结束时为None
+    ///
+    /// loop.break和loop.continue不能离开函数，在这里是错误。
+    pub fn into_returned(self) -> Result<Option<Value>, NjilError> {
+        match self {
+            Completion::Normal(_) => Ok(None),
+            Completion::Return(value) => Ok(Some(value)),
+            signal => signal.into_value().map(Some),
+        }
+    }
+    
+    /// 在需要值的位置（参数、表达式、函数体之外）使用语句的结果
+    ///
+    /// 只有正常结束的语句才有值，return、loop.break和loop.continue出现在这些位置时报错。
+    pub fn into_value(self) -> Result<Value, NjilError> {
+        match self {
+            Completion::Normal(value) => Ok(value),
+            Completion::Return(_) => Err(NjilError::ExecutionError(tip::return_in_expression().to_string())),
+            Completion::Break(label) => Err(loop_signal_error("loop.break", label)),
+            Completion::Continue(label) => Err(loop_signal_error("loop.continue", label)),
+        }
+    }
+}
+
+// 辅助函数：loop.break或loop.continue没有被循环处理时的错误
+fn loop_signal_error(name: &str, label: Option<String>) -> NjilError {
+    match label {
+        Some(label) => NjilError::ExecutionError(tip::label_not_found(name, &label)),
+        None => NjilError::ExecutionError(tip::outside_loop(name)),
+    }
+}
@@ -1,6 +1,9 @@
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::error::Error;
 use std::fmt;
+use crate::source::Location;
+use crate::errortip;
+use crate::stack::{self, StackFrame};
 
 #[derive(Debug)]
 pub enum NjilError {
@@ -8,10 +11,103 @@ pub enum NjilError {
     ParseError(serde_json::Error),
     ParseStringError(String),
     ExecutionError(String),
-    ReturnValue(Value),
-    LoopBreak,
-    LoopContinue,
+    /// 访问未定义的变量，参数为变量名
+    UndefinedVariable(String),
+    /// 值的类型不符合要求
+    TypeError(String),
     ThrowException(Value),
+    /// 超出执行策略的限制
+    LimitExceeded(String),
+    /// 带有源码位置和调用栈的错误
+    Traced {
+        error: Box<NjilError>,
+        location: Option<Location>,
+        /// 最内层的调用在前
+        stack: Vec<StackFrame>,
+    },
+}
+
+impl NjilError {
+    /// 去掉位置和调用栈信息后的原始错误
+    pub fn root(&self) -> &NjilError {
+        match self {
+            NjilError::Traced { error, .. } => error.root(),
+            other => other,
+        }
+    }
+    
+    /// 去掉位置和调用栈信息，取出原始错误
+    pub fn into_root(self) -> NjilError {
+        match self {
+            NjilError::Traced { error, .. } => error.into_root(),
+            other => other,
+        }
+    }
+    
+    /// 错误发生的源码位置
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            NjilError::Traced { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+    
+    /// 错误类型，即catch中异常对象的type字段
+    pub fn error_type(&self) -> &'static str {
+        match self.root() {
+            NjilError::IoError(_) => "io",
+            NjilError::ParseError(_) | NjilError::ParseStringError(_) => "parse",
+            NjilError::UndefinedVariable(_) => "undefined_variable",
+            NjilError::TypeError(_) => "type_error",
+            NjilError::ThrowException(_) => "user",
+            NjilError::LimitExceeded(_) => "limit",
+            _ => "execution",
+        }
+    }
+    
+    /// 不带类型前缀的错误信息
+    pub fn message(&self) -> String {
+        match self.root() {
+            NjilError::IoError(err) => err.to_string(),
+            NjilError::ParseError(err) => err.to_string(),
+            NjilError::ParseStringError(msg) | NjilError::ExecutionError(msg) | NjilError::TypeError(msg) | NjilError::LimitExceeded(msg) => msg.clone(),
+            NjilError::UndefinedVariable(name) => errortip::var::undefined_variable(name),
+            NjilError::ThrowException(Value::String(msg)) => msg.clone(),
+            NjilError::ThrowException(value) => value.to_string(),
+            other => other.to_string(),
+        }
+    }
+    
+    /// 转换为catch中的异常对象：{type, message, code, data, stack}
+    ///
+    /// 抛出的对象中已有的字段保持不变，缺少的字段会被补全。
+    pub fn to_value(&self) -> Value {
+        let (mut object, code, data) = match self.root() {
+            NjilError::ThrowException(Value::Object(thrown)) => (thrown.clone(), Value::Null, Value::Null),
+            NjilError::ThrowException(thrown) => (Map::new(), Value::Null, thrown.clone()),
+            NjilError::IoError(err) => (Map::new(), Value::String(format!("{:?}", err.kind())), Value::Null),
+            NjilError::UndefinedVariable(name) => {
+                let mut data = Map::new();
+                data.insert("name".to_string(), Value::String(name.clone()));
+                (Map::new(), Value::Null, Value::Object(data))
+            },
+            _ => (Map::new(), Value::Null, Value::Null),
+        };
+        object.entry("type").or_insert_with(|| Value::String(self.error_type().to_string()));
+        object.entry("message").or_insert_with(|| Value::String(self.message()));
+        object.entry("code").or_insert(code);
+        object.entry("data").or_insert(data);
+        object.entry("stack").or_insert_with(|| stack::stack_to_value(self.stack()));
+        Value::Object(object)
+    }
+    
+    /// 错误发生时的调用栈，最内层的调用在前
+    pub fn stack(&self) -> &[StackFrame] {
+        match self {
+            NjilError::Traced { stack, .. } => stack,
+            _ => &[],
+        }
+    }
 }
 
 impl fmt::Display for NjilError {
@@ -21,10 +117,14 @@ impl fmt::Display for NjilError {
             NjilError::ParseError(err) => write!(f, "解析错误: {}", err),
             NjilError::ParseStringError(msg) => write!(f, "解析错误: {}", msg),
             NjilError::ExecutionError(msg) => write!(f, "执行错误: {}", msg),
-            NjilError::ReturnValue(_) => write!(f, "返回值"),
-            NjilError::LoopBreak => write!(f, "循环中断"),
-            NjilError::LoopContinue => write!(f, "循环继续"),
+            NjilError::UndefinedVariable(name) => write!(f, "执行错误: {}", errortip::var::undefined_variable(name)),
+            NjilError::TypeError(msg) => write!(f, "类型错误: {}", msg),
             NjilError::ThrowException(value) => write!(f, "异常: {}", value),
+            NjilError::LimitExceeded(msg) => write!(f, "超出限制: {}", msg),
+            NjilError::Traced { error, location: Some(location), .. } => {
+                write!(f, "{}\n  --> {}\n{}", error, location, location.snippet())
+            },
+            NjilError::Traced { error, location: None, .. } => write!(f, "{}", error),
         }
     }
 }
@@ -1,11 +1,16 @@
-/// 错误提示信息模块
-/// 集中管理所有错误提示，便于国际化和维护
+// 错误提示信息模块
+// 集中管理所有错误提示，便于国际化和维护
 
 /// 未知指令错误
 pub fn unknown_instruction(instruction: &str) -> String {
     format!("未知的指令: {}", instruction)
 }
 
+/// NJIS脚本不是数组
+pub fn script_requires_array(kind: &str) -> String {
+    format!("{}必须是JSON数组", kind)
+}
+
 /// 变量相关错误
 pub mod var {
     /// 变量未定义错误
@@ -22,6 +27,99 @@ pub mod var {
     pub fn var_requires_string() -> &'static str {
         "var指令需要一个字符串参数"
     }
+    
+    /// global参数类型错误
+    pub fn global_requires_names() -> &'static str {
+        "global需要一个变量名字符串或变量名数组"
+    }
+    
+    /// var.set缺少必要字段
+    pub fn var_set_missing_fields() -> &'static str {
+        "var.set需要name（或pattern）和value字段"
+    }
+    
+    /// 解构模式与值的类型不匹配
+    pub fn destructure_type_mismatch(expected: &str, actual: &str) -> String {
+        format!("解构模式需要{}，实际是{}", expected, actual)
+    }
+    
+    /// 无效的解构模式
+    pub fn destructure_invalid_pattern(pattern: &str) -> String {
+        format!("无效的解构模式: {}", pattern)
+    }
+}
+
+/// 函数调用相关错误
+pub mod function {
+    /// 缺少必需参数
+    pub fn missing_argument(function: &str, param: &str) -> String {
+        format!("调用函数 {} 时缺少参数: {}", function, param)
+    }
+    
+    /// 未知的命名参数
+    pub fn unknown_argument(function: &str, param: &str) -> String {
+        format!("函数 {} 没有名为 {} 的参数", function, param)
+    }
+    
+    /// 参数同时通过位置和名称提供
+    pub fn duplicate_argument(function: &str, param: &str) -> String {
+        format!("函数 {} 的参数 {} 被重复提供", function, param)
+    }
+    
+    /// 位置参数过多
+    pub fn too_many_arguments(function: &str, expected: usize, actual: usize) -> String {
+        format!("函数 {} 最多接受 {} 个参数，实际传入 {} 个", function, expected, actual)
+    }
+    
+    /// 参数类型不匹配
+    pub fn argument_type_mismatch(function: &str, param: &str, expected: &str, actual: &str) -> String {
+        format!("函数 {} 的参数 {} 需要 {} 类型，实际为 {}", function, param, expected, actual)
+    }
+    
+    /// 未知的参数类型声明
+    pub fn unknown_param_type(function: &str, param: &str, type_name: &str) -> String {
+        format!("函数 {} 的参数 {} 声明了未知类型: {}", function, param, type_name)
+    }
+    
+    /// 函数字面量格式错误
+    pub fn invalid_fn_literal() -> &'static str {
+        "fn需要一个函数名字符串或包含body（可选params）的对象"
+    }
+    
+    /// kwargs参数类型错误
+    pub fn kwargs_requires_object() -> &'static str {
+        "函数命名参数kwargs必须是对象"
+    }
+    
+    /// 位置参数不是元组或数组
+    pub fn call_requires_tuple() -> &'static str {
+        "位置参数必须是元组或数组，命名参数请使用call_named"
+    }
+    
+    /// 函数未定义
+    pub fn undefined_function(name: &str) -> String {
+        format!("找不到函数: {}", name)
+    }
+    
+    /// 被调用的值不是函数
+    pub fn not_callable(value: &str) -> String {
+        format!("不是可调用的值: {}", value)
+    }
+    
+    /// 闭包已经被回收或者不存在
+    pub fn invalid_closure(id: u64) -> String {
+        format!("无效的闭包: {}", id)
+    }
+    
+    /// JSON值无法转换为宿主程序需要的Rust类型
+    pub fn from_value_failed(error: &str) -> String {
+        format!("参数类型转换失败: {}", error)
+    }
+    
+    /// Rust值无法转换为JSON值
+    pub fn to_value_failed(error: &str) -> String {
+        format!("结果类型转换失败: {}", error)
+    }
 }
 
 /// 字符串操作相关错误
@@ -61,7 +159,7 @@ pub mod control_flow {
     
     /// for循环缺少必要字段
     pub fn for_missing_fields() -> &'static str {
-        "for循环需要count和body字段"
+        "for循环需要body字段，以及count或from/to字段"
     }
     
     /// foreach循环参数类型错误
@@ -86,7 +184,77 @@ pub mod control_flow {
     
     /// 集合类型错误
     pub fn collection_requires_array_or_object() -> &'static str {
-        "foreach循环的集合必须是数组或对象"
+        "foreach循环的集合必须是数组、范围或对象"
+    }
+    
+    /// match语句参数类型错误
+    pub fn match_requires_object() -> &'static str {
+        "match语句需要一个对象参数"
+    }
+    
+    /// match语句缺少必要字段
+    pub fn match_missing_fields() -> &'static str {
+        "match语句需要value和cases字段，cases必须是数组"
+    }
+    
+    /// match分支格式错误
+    pub fn match_case_requires_pattern() -> &'static str {
+        "match的每个分支都必须是包含pattern字段的对象"
+    }
+    
+    /// 未知的模式操作符
+    pub fn unknown_pattern(name: &str) -> String {
+        format!("未知的模式: {}", name)
+    }
+    
+    /// 模式中的变量名类型错误
+    pub fn pattern_name_requires_string(name: &str) -> String {
+        format!("模式{}的变量名必须是字符串", name)
+    }
+    
+    /// 剩余元素模式位置错误
+    pub fn pattern_rest_not_last() -> &'static str {
+        "剩余元素（\"...变量名\"或$rest）只能作为数组模式的最后一项"
+    }
+    
+    /// $type模式的类型名错误
+    pub fn pattern_unknown_type(type_name: &str) -> String {
+        format!("$type模式中未知的类型: {}", type_name)
+    }
+    
+    /// $range模式格式错误
+    pub fn pattern_range_invalid() -> &'static str {
+        "$range模式需要[最小值, 最大值]形式的数组，边界为数字或null"
+    }
+    
+    /// $regex模式格式错误
+    pub fn pattern_regex_invalid() -> &'static str {
+        "$regex模式需要一个正则表达式字符串，或包含pattern和flags的对象"
+    }
+    
+    /// return出现在表达式中
+    pub fn return_in_expression() -> &'static str {
+        "return只能作为函数或脚本中的语句使用，不能用在表达式中"
+    }
+    
+    /// loop.break或loop.continue出现在循环之外
+    pub fn outside_loop(name: &str) -> String {
+        format!("{}只能作为循环体中的语句使用", name)
+    }
+    
+    /// 循环标签类型错误
+    pub fn label_requires_string() -> &'static str {
+        "循环的label必须是字符串"
+    }
+    
+    /// loop.break或loop.continue的参数类型错误
+    pub fn label_argument_invalid(name: &str) -> String {
+        format!("{}的参数必须是null、标签字符串或包含label字段的对象", name)
+    }
+    
+    /// loop.break或loop.continue的标签不存在
+    pub fn label_not_found(name: &str, label: &str) -> String {
+        format!("{}找不到标签为'{}'的循环，标签只能指向包含这条语句的循环", name, label)
     }
 }
 
@@ -150,11 +318,315 @@ pub mod exception {
     
     /// try/catch语句缺少必要字段
     pub fn try_catch_missing_fields() -> &'static str {
-        "try/catch语句需要try字段，且可选地包含catch字段"
+        "try/catch语句需要try字段，且可选地包含catch和finally字段"
     }
     
     /// catch块var字段错误
     pub fn catch_var_requires_string() -> &'static str {
         "catch块的var字段（异常变量）必须是字符串"
     }
-} 
\ No newline at end of file
+    
+    /// catch块stack字段错误
+    pub fn catch_stack_requires_string() -> &'static str {
+        "catch块的stack字段（调用栈变量）必须是字符串"
+    }
+    
+    /// defer语句不在函数中
+    pub fn defer_outside_function() -> &'static str {
+        "defer语句只能在函数或脚本中使用"
+    }
+    
+    /// catch子句类型错误
+    pub fn catch_clause_requires_object() -> &'static str {
+        "catch子句数组中的每一项都必须是对象"
+    }
+    
+    /// catch子句type字段错误
+    pub fn catch_type_invalid() -> &'static str {
+        "catch子句的type字段必须是字符串或字符串数组"
+    }
+} 
+/// 执行限制相关错误信息
+pub mod limit {
+    use std::time::Duration;
+    
+    /// 执行的语句数超出限制
+    pub fn too_many_statements(limit: u64) -> String {
+        format!("执行的语句数超过了上限 {}", limit)
+    }
+    
+    /// 执行时间超出限制
+    pub fn timeout(timeout: Duration) -> String {
+        format!("执行时间超过了上限 {}毫秒", timeout.as_millis())
+    }
+    
+    /// 函数调用嵌套过深
+    pub fn call_too_deep(limit: usize) -> String {
+        format!("函数调用深度超过了上限 {}", limit)
+    }
+    
+    /// 字符串过长
+    pub fn string_too_long(length: usize, limit: usize) -> String {
+        format!("字符串长度 {} 超过了上限 {}", length, limit)
+    }
+    
+    /// 数组过长
+    pub fn array_too_long(length: usize, limit: usize) -> String {
+        format!("数组长度 {} 超过了上限 {}", length, limit)
+    }
+    
+    /// 未结束的生成器过多
+    pub fn too_many_generators(limit: usize) -> String {
+        format!("未结束的生成器数超过了上限 {}", limit)
+    }
+    
+    /// 执行中的任务过多
+    pub fn too_many_tasks(limit: usize) -> String {
+        format!("同时执行的任务数超过了上限 {}", limit)
+    }
+    
+    /// 模块被禁用
+    pub fn module_denied(module: &str) -> String {
+        format!("模块 {} 已被执行策略禁用", module)
+    }
+    
+    /// 指令被禁用
+    pub fn instruction_denied(instruction: &str) -> String {
+        format!("指令 {} 已被执行策略禁用", instruction)
+    }
+}
+
+/// 中缀表达式相关错误信息
+pub mod expr {
+    /// 表达式语法错误，position从1开始，按字符计数
+    pub fn syntax_error(source: &str, position: usize, message: &str) -> String {
+        format!("表达式 `{}` 第{}个字符处: {}", source, position, message)
+    }
+    
+    /// 表达式嵌套过深
+    pub fn too_deep(limit: usize) -> String {
+        format!("表达式嵌套超过{}层", limit)
+    }
+    
+    /// 无法识别的字符
+    pub fn unexpected_char(c: char) -> String {
+        format!("无法识别的字符 '{}'", c)
+    }
+    
+    /// 意外的记号
+    pub fn unexpected_token(token: &str) -> String {
+        format!("意外的 {}", token)
+    }
+    
+    /// 缺少期望的记号
+    pub fn expected(expected: &str, found: &str) -> String {
+        format!("期望 {}，实际是 {}", expected, found)
+    }
+    
+    /// 无效的数字
+    pub fn invalid_number(text: &str) -> String {
+        format!("无效的数字 {}", text)
+    }
+    
+    /// 字符串没有结束
+    pub fn unterminated_string() -> &'static str {
+        "字符串缺少结束引号"
+    }
+    
+    /// 表达式结尾
+    pub fn end_of_expression() -> &'static str {
+        "表达式结尾"
+    }
+    
+    /// expr参数类型错误
+    pub fn expr_requires_string() -> &'static str {
+        "expr指令需要一个表达式字符串"
+    }
+}
+
+/// 范围相关错误信息
+pub mod range {
+    /// 范围的边界或步长不是数字
+    pub fn bound_requires_number(name: &str) -> String {
+        format!("范围的{}必须是有限的数字", name)
+    }
+    
+    /// 范围的步长为0
+    pub fn step_zero() -> &'static str {
+        "范围的step不能为0"
+    }
+    
+    /// 范围的元素个数超出上限
+    pub fn too_long() -> &'static str {
+        "范围的元素个数太多"
+    }
+    
+    /// 范围太长，不能转换为数组
+    pub fn too_long_to_materialize(len: u64, max: u64) -> String {
+        format!("范围有{}个元素，超过了转换为数组的上限{}，请直接在loop.foreach中遍历", len, max)
+    }
+    
+    /// range指令的参数格式错误
+    pub fn arguments() -> &'static str {
+        "range需要元素个数、[起点, 终点, 步长]数组，或包含from、to、step、inclusive、reverse字段的对象"
+    }
+    
+    /// inclusive或reverse不是布尔值
+    pub fn flag_requires_bool(name: &str) -> String {
+        format!("范围的{}必须是布尔值", name)
+    }
+}
+
+/// 生成器相关错误信息
+pub mod generator {
+    /// yield出现在生成器函数之外
+    pub fn yield_outside_generator() -> &'static str {
+        "yield只能在函数中使用，包含yield的函数调用后返回生成器"
+    }
+    
+    /// 值不是生成器
+    pub fn not_a_generator(value: &serde_json::Value) -> String {
+        format!("不是生成器: {}", value)
+    }
+    
+    /// 生成器不属于当前解释器
+    pub fn unknown_generator(id: u64) -> String {
+        format!("无效的生成器: {}", id)
+    }
+    
+    /// 生成器正在执行时又被恢复
+    pub fn generator_running() -> &'static str {
+        "生成器正在执行，不能再次恢复"
+    }
+    
+    /// 关闭生成器时函数体又执行了yield
+    pub fn yield_while_closing() -> &'static str {
+        "生成器在关闭时不能再执行yield"
+    }
+    
+    /// 无法创建生成器线程
+    pub fn spawn_failed(reason: &str) -> String {
+        format!("无法创建生成器线程: {}", reason)
+    }
+    
+    /// 生成器线程意外结束
+    pub fn thread_lost() -> &'static str {
+        "生成器线程意外结束"
+    }
+}
+
+/// 并行与任务相关错误信息
+pub mod task {
+    /// 值不是任务
+    pub fn not_a_task(value: &serde_json::Value) -> String {
+        format!("不是任务: {}", value)
+    }
+    
+    /// 任务不存在，或者结果已经被取走
+    pub fn unknown_task(id: u64) -> String {
+        format!("无效的任务或任务已经被等待过: {}", id)
+    }
+    
+    /// 任务已被取消
+    pub fn cancelled() -> &'static str {
+        "任务已被取消"
+    }
+    
+    /// parallel或task.all的参数格式错误
+    pub fn tasks_required(name: &str) -> String {
+        format!("{}需要任务数组，或包含tasks数组和errors字段的对象", name)
+    }
+    
+    /// 未知的错误处理方式
+    pub fn unknown_error_policy(value: &serde_json::Value) -> String {
+        format!("errors必须是\"fail_fast\"或\"collect\": {}", value)
+    }
+    
+    /// 无法创建任务线程
+    pub fn spawn_failed(reason: &str) -> String {
+        format!("无法创建任务线程: {}", reason)
+    }
+}
+
+/// 整数运算相关错误信息
+pub mod math {
+    /// 整数运算结果超出64位整数范围
+    pub fn integer_overflow(op: &str) -> String {
+        format!("整数{}溢出，结果超出了64位整数的范围，可以先用math.bigint转换为大整数", op)
+    }
+    
+    /// 位运算的操作数不是整数
+    pub fn integer_required(op: &str) -> String {
+        format!("{}需要整数操作数", op)
+    }
+    
+    /// 位移的位数无效
+    pub fn shift_out_of_range(max: u64) -> String {
+        format!("位移的位数必须是0到{}之间的整数", max)
+    }
+    
+    /// 运算需要的操作数不足
+    pub fn operands_required(op: &str) -> String {
+        format!("{}需要一个至少包含两个操作数的数组", op)
+    }
+    
+    /// 无法转换为大整数
+    pub fn bigint_invalid(value: &str) -> String {
+        format!("无法转换为大整数: {}", value)
+    }
+    
+    /// 无法转换为定点小数
+    pub fn decimal_invalid(value: &str) -> String {
+        format!("无法转换为定点小数: {}", value)
+    }
+    
+    /// 小数位数无效
+    pub fn decimal_scale_invalid(max: u32) -> String {
+        format!("scale必须是0到{}之间的整数", max)
+    }
+    
+    /// 舍入方式无效
+    pub fn rounding_invalid(name: &str) -> String {
+        format!("不支持的舍入方式: {}，可用的舍入方式为half_up、half_even和floor", name)
+    }
+    
+    /// 定点小数指令的参数格式错误
+    pub fn decimal_arguments(instruction: &str) -> String {
+        format!("{}需要一个操作数数组，或包含operands字段的对象", instruction)
+    }
+    
+    /// 缺少value字段
+    pub fn decimal_value_required(instruction: &str) -> String {
+        format!("{}需要value字段", instruction)
+    }
+    
+    /// 数学函数的参数数量或格式错误
+    pub fn arguments(instruction: &str, names: &[&str]) -> String {
+        format!("{}需要参数 {}，可以写成数组或对象", instruction, names.join("、"))
+    }
+    
+    /// 参数不是数字
+    pub fn number_required(instruction: &str, actual: &str) -> String {
+        format!("{}需要数字参数，实际是{}", instruction, actual)
+    }
+    
+    /// 参数无法比较大小
+    pub fn not_comparable(instruction: &str) -> String {
+        format!("{}的参数无法比较大小", instruction)
+    }
+    
+    /// 整数指数过大
+    pub fn exponent_too_large(max: u64) -> String {
+        format!("整数指数不能超过{}", max)
+    }
+    
+    /// 小数位数参数无效
+    pub fn digits_invalid(max: u32) -> String {
+        format!("digits必须是-{}到{}之间的整数", max, max)
+    }
+    
+    /// clamp的区间无效
+    pub fn clamp_range_invalid() -> &'static str {
+        "math.clamp的min不能大于max"
+    }
+}
@@ -0,0 +1,637 @@
+use std::cmp::Ordering;
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::errortip::expr as tip;
+use crate::interpreter::Interpreter;
+use crate::utils::type_convert::{self, IntegerOp};
+
+/// 中缀表达式的语法树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// 数字、字符串、true、false、null
+    Literal(Value),
+    /// 变量或常量
+    Variable(String),
+    /// 字段访问：`a.b`
+    Member(Box<Expr>, String),
+    /// 下标访问：`a[i]`
+    Index(Box<Expr>, Box<Expr>),
+    /// 函数调用：`f(a, b)`
+    Call(Box<Expr>, Vec<Expr>),
+    /// 数组字面量：`[a, b]`
+    Array(Vec<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+// 很长的二元运算链是向左嵌套的，逐个拆开左侧释放，避免递归释放耗尽调用栈
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let Expr::Binary(_, left, _) = self else {
+            return;
+        };
+        let mut left = std::mem::replace(&mut **left, Expr::Literal(Value::Null));
+        while let Expr::Binary(_, next, _) = &mut left {
+            let next = std::mem::replace(&mut **next, Expr::Literal(Value::Null));
+            left = next;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// 整数除法：`//`
+    IntDiv,
+    Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    // 辅助函数：运算符的优先级，数值越大结合越紧
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Ne => 3,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 4,
+            BinaryOp::BitOr => 5,
+            BinaryOp::BitXor => 6,
+            BinaryOp::BitAnd => 7,
+            BinaryOp::Shl | BinaryOp::Shr => 8,
+            BinaryOp::Add | BinaryOp::Sub => 9,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::IntDiv | BinaryOp::Mod => 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Value),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    End,
+}
+
+/// 运算符和标点，较长的写在前面
+const OPERATORS: [&str; 26] = [
+    "==", "!=", "<=", ">=", "&&", "||", "//", "<<", ">>",
+    "<", ">", "+", "-", "*", "/", "%", "&", "|", "^", "!", "(", ")", "[", "]", ",", ".",
+];
+
+/// 解析中缀表达式
+///
+/// 支持算术（`+ - * / // %`）、位运算（`& | ^ << >>`）、比较（`== != < <= > >=`）、
+/// 逻辑（`&& || !`，或 `and or not`）、字符串和数组字面量、变量和常量、字段访问、下标访问以及函数调用。
+/// 超出64位范围的整数字面量是大整数。
+pub fn parse(source: &str) -> Result<Expr, NjilError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { source, tokens, position: 0, depth: 0 };
+    let expr = parser.expression(0)?;
+    match parser.peek() {
+        Token::End => Ok(expr),
+        token => Err(parser.error(&tip::unexpected_token(&describe(token)))),
+    }
+}
+
+// 辅助函数：把表达式字符串切分为记号，每个记号附带起始字符位置
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, NjilError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        
+        if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = serde_json::from_str::<serde_json::Number>(&text)
+                .map_err(|_| syntax_error(source, start, &tip::invalid_number(&text)))?;
+            // 超出64位范围的整数保持精确，作为大整数
+            let number = match text.bytes().all(|b| b.is_ascii_digit()) && !number.is_i64() && !number.is_u64() {
+                true => type_convert::to_bigint(&Value::String(text)).map(|n| type_convert::bigint_value(&n)).unwrap_or(Value::Number(number)),
+                false => Value::Number(number),
+            };
+            tokens.push((Token::Number(number), start));
+            continue;
+        }
+        
+        if c == '"' || c == '\'' {
+            i += 1;
+            let mut text = String::new();
+            loop {
+                match chars.get(i) {
+                    None => return Err(syntax_error(source, start, tip::unterminated_string())),
+                    Some(&quote) if quote == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some('r') => text.push('\r'),
+                            Some(&escaped) => text.push(escaped),
+                            None => return Err(syntax_error(source, start, tip::unterminated_string())),
+                        }
+                    },
+                    Some(&other) => text.push(other),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(text), start));
+            continue;
+        }
+        
+        if c.is_alphabetic() || c == '_' || c == '$' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+            continue;
+        }
+        
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        match OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            Some(op) => {
+                i += op.chars().count();
+                tokens.push((Token::Op(op), start));
+            },
+            None => return Err(syntax_error(source, start, &tip::unexpected_char(c))),
+        }
+    }
+    
+    tokens.push((Token::End, chars.len()));
+    Ok(tokens)
+}
+
+// 辅助函数：带位置信息的语法错误
+fn syntax_error(source: &str, position: usize, message: &str) -> NjilError {
+    NjilError::ParseStringError(tip::syntax_error(source, position + 1, message))
+}
+
+// 辅助函数：记号在错误信息中的写法
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(number) => number.to_string(),
+        Token::Str(text) => format!("\"{}\"", text),
+        Token::Ident(name) => name.clone(),
+        Token::Op(op) => op.to_string(),
+        Token::End => tip::end_of_expression().to_string(),
+    }
+}
+
+/// 表达式的最大嵌套深度，解析和求值嵌套过深的表达式会耗尽调用栈
+const MAX_DEPTH: usize = 256;
+
+/// 递归下降解析器，二元运算使用优先级爬升
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    /// 当前的嵌套深度，括号、一元运算、列表和后缀运算各算一层
+    ///
+    /// 连续的二元运算在循环中解析，求值时也沿左侧逐个计算，所以不增加深度。
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+    
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        if token != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+    
+    fn error(&self, message: &str) -> NjilError {
+        syntax_error(self.source, self.tokens[self.position].1, message)
+    }
+    
+    // 辅助函数：进入一层嵌套，超过最大深度时返回错误
+    fn nest(&mut self) -> Result<(), NjilError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(&tip::too_deep(MAX_DEPTH)));
+        }
+        Ok(())
+    }
+    
+    fn expect(&mut self, op: &'static str) -> Result<(), NjilError> {
+        if self.peek() == &Token::Op(op) {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error(&tip::expected(op, &describe(self.peek()))))
+        }
+    }
+    
+    fn binary_op(&self) -> Option<BinaryOp> {
+        let op = match self.peek() {
+            Token::Op("+") => BinaryOp::Add,
+            Token::Op("-") => BinaryOp::Sub,
+            Token::Op("*") => BinaryOp::Mul,
+            Token::Op("/") => BinaryOp::Div,
+            Token::Op("//") => BinaryOp::IntDiv,
+            Token::Op("%") => BinaryOp::Mod,
+            Token::Op("&") => BinaryOp::BitAnd,
+            Token::Op("|") => BinaryOp::BitOr,
+            Token::Op("^") => BinaryOp::BitXor,
+            Token::Op("<<") => BinaryOp::Shl,
+            Token::Op(">>") => BinaryOp::Shr,
+            Token::Op("==") => BinaryOp::Eq,
+            Token::Op("!=") => BinaryOp::Ne,
+            Token::Op("<") => BinaryOp::Lt,
+            Token::Op("<=") => BinaryOp::Le,
+            Token::Op(">") => BinaryOp::Gt,
+            Token::Op(">=") => BinaryOp::Ge,
+            Token::Op("&&") => BinaryOp::And,
+            Token::Op("||") => BinaryOp::Or,
+            Token::Ident(word) if word == "and" => BinaryOp::And,
+            Token::Ident(word) if word == "or" => BinaryOp::Or,
+            _ => return None,
+        };
+        Some(op)
+    }
+    
+    /// 解析优先级不低于min_precedence的二元运算，运算符都是左结合的
+    fn expression(&mut self, min_precedence: u8) -> Result<Expr, NjilError> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.binary_op() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.next();
+            // 右侧只解析优先级更高的运算，递归层数不超过优先级的级数
+            let right = self.expression(op.precedence() + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+    
+    fn unary(&mut self) -> Result<Expr, NjilError> {
+        let op = match self.peek() {
+            Token::Op("-") => UnaryOp::Neg,
+            Token::Op("!") => UnaryOp::Not,
+            Token::Ident(word) if word == "not" => UnaryOp::Not,
+            _ => return self.postfix(),
+        };
+        self.next();
+        let depth = self.depth;
+        self.nest()?;
+        let operand = self.unary()?;
+        self.depth = depth;
+        Ok(Expr::Unary(op, Box::new(operand)))
+    }
+    
+    fn postfix(&mut self) -> Result<Expr, NjilError> {
+        let depth = self.depth;
+        let mut expr = self.primary()?;
+        loop {
+            if matches!(self.peek(), Token::Op(".") | Token::Op("[") | Token::Op("(")) {
+                self.nest()?;
+            }
+            match self.peek() {
+                Token::Op(".") => {
+                    self.next();
+                    match self.next() {
+                        Token::Ident(name) => expr = Expr::Member(Box::new(expr), name),
+                        // 允许 a.0 形式的数组下标
+                        Token::Number(Value::Number(index)) if index.is_u64() => {
+                            expr = Expr::Index(Box::new(expr), Box::new(Expr::Literal(Value::Number(index))));
+                        },
+                        token => {
+                            self.position -= 1;
+                            return Err(self.error(&tip::expected("字段名", &describe(&token))));
+                        },
+                    }
+                },
+                Token::Op("[") => {
+                    self.next();
+                    let index = self.expression(0)?;
+                    self.expect("]")?;
+                    expr = Expr::Index(Box::new(expr), Box::new(index));
+                },
+                Token::Op("(") => {
+                    self.next();
+                    let args = self.list(")")?;
+                    expr = Expr::Call(Box::new(expr), args);
+                },
+                _ => {
+                    self.depth = depth;
+                    return Ok(expr);
+                },
+            }
+        }
+    }
+    
+    fn primary(&mut self) -> Result<Expr, NjilError> {
+        match self.next() {
+            Token::Number(number) => Ok(Expr::Literal(number)),
+            Token::Str(text) => Ok(Expr::Literal(Value::String(text))),
+            Token::Ident(name) => Ok(match name.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "null" => Expr::Literal(Value::Null),
+                _ => Expr::Variable(name),
+            }),
+            Token::Op("(") => {
+                let depth = self.depth;
+                self.nest()?;
+                let expr = self.expression(0)?;
+                self.expect(")")?;
+                self.depth = depth;
+                Ok(expr)
+            },
+            Token::Op("[") => Ok(Expr::Array(self.list("]")?)),
+            token => {
+                if token != Token::End {
+                    self.position -= 1;
+                }
+                Err(self.error(&tip::unexpected_token(&describe(&token))))
+            },
+        }
+    }
+    
+    // 辅助函数：解析逗号分隔的表达式列表，直到结束符号
+    fn list(&mut self, close: &'static str) -> Result<Vec<Expr>, NjilError> {
+        let mut items = Vec::new();
+        if self.peek() == &Token::Op(close) {
+            self.next();
+            return Ok(items);
+        }
+        let depth = self.depth;
+        self.nest()?;
+        loop {
+            items.push(self.expression(0)?);
+            if self.peek() == &Token::Op(",") {
+                self.next();
+            } else {
+                self.expect(close)?;
+                self.depth = depth;
+                return Ok(items);
+            }
+        }
+    }
+}
+
+/// 对表达式求值，运算与 `utils::type_convert` 中的算术和比较运算一致
+pub(crate) fn evaluate(interpreter: &mut Interpreter, expr: &Expr) -> Result<Value, NjilError> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Variable(name) => interpreter.get_variable(name)
+            .or_else(|| interpreter.get_constant(name).cloned())
+            .ok_or_else(|| NjilError::UndefinedVariable(name.clone())),
+        Expr::Member(object, key) => {
+            // 模块导出的常量，例如 math.PI
+            if let Some(value) = callee_name(expr).and_then(|name| interpreter.get_constant(&name).cloned()) {
+                return Ok(value);
+            }
+            let object = evaluate(interpreter, object)?;
+            Ok(object.get(key).cloned().unwrap_or(Value::Null))
+        },
+        Expr::Index(object, index) => {
+            let object = evaluate(interpreter, object)?;
+            let index = evaluate(interpreter, index)?;
+            Ok(index_value(&object, &index))
+        },
+        Expr::Call(callee, args) => {
+            let mut arg_values = Vec::with_capacity(args.len());
+            for arg in args {
+                arg_values.push(evaluate(interpreter, arg)?);
+            }
+            // 变量名或 模块.函数 形式的名称按函数名调用，其他表达式需要求值为函数值
+            if let Some(name) = callee_name(callee) {
+                if matches!(**callee, Expr::Variable(_)) || interpreter.get_function(&name).is_some() {
+                    return interpreter.call_function(&name, &arg_values);
+                }
+            }
+            let callee = evaluate(interpreter, callee)?;
+            interpreter.call_value(&callee, &arg_values, &Map::new())
+        },
+        Expr::Array(items) => {
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                values.push(evaluate(interpreter, item)?);
+            }
+            let value = Value::Array(values);
+            interpreter.check_value_size(&value)?;
+            Ok(value)
+        },
+        Expr::Unary(UnaryOp::Neg, operand) => {
+            let operand = evaluate(interpreter, operand)?;
+            type_convert::subtract(&Value::from(0), &operand)
+        },
+        Expr::Unary(UnaryOp::Not, operand) => {
+            let operand = evaluate(interpreter, operand)?;
+            Ok(Value::Bool(!type_convert::to_bool(&operand)))
+        },
+        Expr::Binary(..) => evaluate_binary(interpreter, expr),
+    }
+}
+
+// 辅助函数：计算左结合的二元运算链，沿左侧逐个计算而不是递归，很长的 a + b + c + ... 不会耗尽调用栈
+fn evaluate_binary(interpreter: &mut Interpreter, expr: &Expr) -> Result<Value, NjilError> {
+    let mut links = Vec::new();
+    let mut first = expr;
+    while let Expr::Binary(op, left, right) = first {
+        links.push((*op, right));
+        first = left;
+    }
+    let mut value = evaluate(interpreter, first)?;
+    for (op, right) in links.into_iter().rev() {
+        value = match op {
+            // 逻辑运算短路求值
+            BinaryOp::And => Value::Bool(type_convert::to_bool(&value) && type_convert::to_bool(&evaluate(interpreter, right)?)),
+            BinaryOp::Or => Value::Bool(type_convert::to_bool(&value) || type_convert::to_bool(&evaluate(interpreter, right)?)),
+            _ => {
+                let right = evaluate(interpreter, right)?;
+                // 字符串拼接在表达式内部产生新值，在这里检查长度
+                let value = binary(op, &value, &right)?;
+                interpreter.check_value_size(&value)?;
+                value
+            },
+        };
+    }
+    Ok(value)
+}
+
+// 辅助函数：计算二元运算
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, NjilError> {
+    let ordering = || type_convert::compare(left, right);
+    let result = match op {
+        BinaryOp::Add => type_convert::add(left, right)?,
+        BinaryOp::Sub => type_convert::subtract(left, right)?,
+        BinaryOp::Mul => type_convert::multiply(left, right)?,
+        BinaryOp::Div => type_convert::divide(left, right)?,
+        BinaryOp::IntDiv => type_convert::integer_divide(left, right)?,
+        BinaryOp::Mod => type_convert::modulo(left, right)?,
+        BinaryOp::BitAnd => type_convert::bitwise(IntegerOp::BitAnd, left, right)?,
+        BinaryOp::BitOr => type_convert::bitwise(IntegerOp::BitOr, left, right)?,
+        BinaryOp::BitXor => type_convert::bitwise(IntegerOp::BitXor, left, right)?,
+        BinaryOp::Shl => type_convert::bitwise(IntegerOp::Shl, left, right)?,
+        BinaryOp::Shr => type_convert::bitwise(IntegerOp::Shr, left, right)?,
+        BinaryOp::Eq => Value::Bool(type_convert::is_equal(left, right)),
+        BinaryOp::Ne => Value::Bool(!type_convert::is_equal(left, right)),
+        BinaryOp::Lt => Value::Bool(ordering() == Some(Ordering::Less)),
+        BinaryOp::Le => Value::Bool(matches!(ordering(), Some(Ordering::Less | Ordering::Equal))),
+        BinaryOp::Gt => Value::Bool(ordering() == Some(Ordering::Greater)),
+        BinaryOp::Ge => Value::Bool(matches!(ordering(), Some(Ordering::Greater | Ordering::Equal))),
+        BinaryOp::And | BinaryOp::Or => unreachable!("逻辑运算在evaluate中短路求值"),
+    };
+    Ok(result)
+}
+
+// 辅助函数：下标访问，数组和字符串支持负数下标（从末尾开始），越界或类型不符时为null
+fn index_value(object: &Value, index: &Value) -> Value {
+    let position = |len: usize| {
+        let index = index.as_f64()?;
+        if index.fract() != 0.0 {
+            return None;
+        }
+        let index = if index < 0.0 { len as f64 + index } else { index };
+        (index >= 0.0 && index < len as f64).then_some(index as usize)
+    };
+    match (object, index) {
+        (Value::Object(map), Value::String(key)) => map.get(key).cloned().unwrap_or(Value::Null),
+        (Value::Array(items), _) => position(items.len()).map(|i| items[i].clone()).unwrap_or(Value::Null),
+        (Value::String(text), _) => {
+            let chars: Vec<char> = text.chars().collect();
+            position(chars.len()).map(|i| Value::String(chars[i].to_string())).unwrap_or(Value::Null)
+        },
+        _ => Value::Null,
+    }
+}
+
+// 辅助函数：把 a.b.c 形式的被调用表达式还原为点分名称
+fn callee_name(callee: &Expr) -> Option<String> {
+    match callee {
+        Expr::Variable(name) => Some(name.clone()),
+        Expr::Member(object, key) => Some(format!("{}.{}", callee_name(object)?, key)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_parse_precedence() {
+        let expr = parse("a * (b + 2) > limit && !done").unwrap();
+        let expected = Expr::Binary(
+            BinaryOp::And,
+            Box::new(Expr::Binary(
+                BinaryOp::Gt,
+                Box::new(Expr::Binary(
+                    BinaryOp::Mul,
+                    Box::new(Expr::Variable("a".to_string())),
+                    Box::new(Expr::Binary(BinaryOp::Add, Box::new(Expr::Variable("b".to_string())), Box::new(Expr::Literal(json!(2))))),
+                )),
+                Box::new(Expr::Variable("limit".to_string())),
+            )),
+            Box::new(Expr::Unary(UnaryOp::Not, Box::new(Expr::Variable("done".to_string())))),
+        );
+        assert_eq!(expr, expected);
+        
+        // 减法是左结合的
+        assert_eq!(parse("10 - 3 - 2").unwrap(), parse("(10 - 3) - 2").unwrap());
+    }
+    
+    #[test]
+    fn test_parse_errors_report_position() {
+        let error = parse("a + * b").unwrap_err();
+        assert!(error.to_string().contains("第5个字符"), "{}", error);
+        assert!(parse("f(1, 2").is_err());
+        assert!(parse("'abc").is_err());
+        assert!(parse("a # b").is_err());
+    }
+    
+    #[test]
+    fn test_parse_nesting_limit() {
+        let nested = |open: &str, inner: &str, close: &str, depth: usize| format!("{}{}{}", open.repeat(depth), inner, close.repeat(depth));
+        assert_eq!(parse(&nested("(", "1", ")", 250)).unwrap(), Expr::Literal(json!(1)));
+        assert!(parse(&format!("1{}", " + 1".repeat(200))).is_ok());
+        
+        // 连续的二元运算不增加嵌套深度，很长的平铺运算链可以解析和求值
+        let mut interpreter = Interpreter::new();
+        let sum = parse(&format!("1{}", " + 1".repeat(20000))).unwrap();
+        assert_eq!(evaluate(&mut interpreter, &sum).unwrap(), json!(20001));
+        let all = parse(&format!("true{}", " && 1 < 2".repeat(20000))).unwrap();
+        assert_eq!(evaluate(&mut interpreter, &all).unwrap(), json!(true));
+        assert!(parse(&format!("(1 + 2){}", " * (3 - 1) + f(1, 2)".repeat(5000))).is_ok());
+        
+        // 嵌套过深的表达式返回解析错误，而不是耗尽调用栈
+        for source in [
+            nested("(", "1", ")", 20000),
+            nested("[", "1", "]", 20000),
+            nested("-", "1", "", 20000),
+            format!("1{}", " + (1".repeat(20000)),
+            format!("a{}", ".b".repeat(20000)),
+            format!("f{}", "(1)".repeat(20000)),
+        ] {
+            let error = parse(&source).unwrap_err();
+            assert!(matches!(&error, NjilError::ParseStringError(msg) if msg.contains("嵌套超过")), "{}", error);
+        }
+    }
+    
+    #[test]
+    fn test_evaluate() {
+        let mut interpreter = Interpreter::new();
+        interpreter.declare_variable("user".to_string(), json!({"name": "小明", "scores": [90, 80, 70]}));
+        interpreter.declare_variable("x".to_string(), json!(4));
+        
+        let mut eval = |source: &str| evaluate(&mut interpreter, &parse(source).unwrap()).unwrap();
+        assert_eq!(eval("x * (x + 2) % 5"), json!(4));
+        assert_eq!(eval("'你好, ' + user.name"), json!("你好, 小明"));
+        assert_eq!(eval("user.scores[0] >= 90 and user['scores'][-1] < 75"), json!(true));
+        assert_eq!(eval("user.missing == null || not (x > 3)"), json!(true));
+        assert_eq!(eval("[x, -x, 'a' * 2]"), json!([4, -4, "aa"]));
+        assert_eq!(eval("[7 // 2, 7 / 2, 1 << 4 | 3, 6 & 3 ^ 1, -16 >> 2]"), json!([3, 3.5, 19, 3, -4]));
+        assert_eq!(eval("18446744073709551616 - 1"), json!({"$bigint": "18446744073709551615"}));
+    }
+}
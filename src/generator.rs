@@ -0,0 +1,212 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use serde_json::{Map, Value};
+use crate::closure::{self, Pins};
+use crate::error::NjilError;
+use crate::errortip::generator as tip;
+use crate::interpreter::Interpreter;
+use crate::policy::ExecutionUsage;
+use crate::stack::ActiveFrame;
+use crate::types::Function;
+
+/// 生成器值在JSON中的标记键
+///
+/// 生成器以 `{"$generator": id}` 的形式作为普通JSON值存储和传递，
+/// 实际的生成器和闭包一样保存在解释器及其子解释器共享的闭包表中，不再被引用时被回收。
+pub const GENERATOR_KEY: &str = "$generator";
+
+/// 生成器线程的栈大小，与主线程相同，避免函数体中的递归过早溢出
+const GENERATOR_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// 调用者发给生成器线程的指令
+pub(crate) enum Resume {
+    /// 继续执行到下一个yield
+    Next,
+    /// 关闭生成器：暂停处的yield像return一样结束函数体，执行其中的finally和defer
+    Close,
+}
+
+// 生成器线程暂停或结束时发回的事件，带有对其中的闭包的固定，由调用者接管
+enum Event {
+    Yielded(Value, Pins),
+    Finished(Result<Value, NjilError>, Pins),
+}
+
+/// 恢复一次生成器的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratorStep {
+    /// 生成器产生了一个值，在下一个yield处暂停
+    Yielded(Value),
+    /// 函数体执行完毕，带有return的值（没有return时为null）
+    Returned(Value),
+}
+
+impl GeneratorStep {
+    /// 转换为gen.next返回的对象：`{"value": 值, "done": 是否结束}`
+    pub fn to_value(&self) -> Value {
+        let (value, done) = match self {
+            GeneratorStep::Yielded(value) => (value.clone(), false),
+            GeneratorStep::Returned(value) => (value.clone(), true),
+        };
+        let mut result = Map::new();
+        result.insert("value".to_string(), value);
+        result.insert("done".to_string(), Value::Bool(done));
+        Value::Object(result)
+    }
+}
+
+/// 生成器线程一侧的通道，yield通过它把值交给调用者并等待下一个指令
+///
+/// 指令带有调用者本次运行的资源使用情况，生成器在之后的运行中被恢复时按新的运行计算语句数和截止时间。
+pub(crate) struct Yielder {
+    events: Sender<Event>,
+    resume: Receiver<(Resume, ExecutionUsage)>,
+}
+
+impl Yielder {
+    /// 交出一个值并等待调用者的指令，调用者丢弃生成器（通道断开）时视为关闭
+    pub(crate) fn yield_value(&self, value: Value, pins: Pins, usage: &mut ExecutionUsage) -> Resume {
+        if self.events.send(Event::Yielded(value, pins)).is_err() {
+            return Resume::Close;
+        }
+        self.receive(usage)
+    }
+    
+    // 辅助函数：等待调用者的指令，沿用调用者的运行
+    fn receive(&self, usage: &mut ExecutionUsage) -> Resume {
+        match self.resume.recv() {
+            Ok((resume, caller)) => {
+                usage.continue_run(caller);
+                resume
+            },
+            Err(_) => Resume::Close,
+        }
+    }
+}
+
+/// 调用者一侧的生成器
+///
+/// 函数体在单独的线程中由子解释器执行，和调用者轮流运行：调用者恢复生成器后一直等到
+/// 它产生下一个值或结束，所以同一时刻只有一方在执行语句。丢弃暂停中的生成器时同样等待它关闭。
+/// live是未结束的生成器数，生成器结束或被丢弃时减一；usage是最近一次恢复时调用者的资源使用情况。
+pub(crate) struct Generator {
+    resume: Sender<(Resume, ExecutionUsage)>,
+    events: Receiver<Event>,
+    live: Arc<AtomicUsize>,
+    usage: ExecutionUsage,
+    started: bool,
+    finished: bool,
+}
+
+impl Generator {
+    /// 在新线程中准备执行生成器函数，函数体在第一次恢复时才开始执行
+    pub(crate) fn spawn(mut child: Interpreter, frame: ActiveFrame, function: Arc<Function>, live: Arc<AtomicUsize>) -> Result<Generator, NjilError> {
+        let (resume_sender, resume_receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
+        let yielder = Yielder { events: event_sender.clone(), resume: resume_receiver };
+        
+        thread::Builder::new()
+            .name(format!("generator {}", frame.function))
+            .stack_size(GENERATOR_STACK_SIZE)
+            .spawn(move || {
+                // 开始之前就被关闭的生成器不执行函数体
+                if !matches!(yielder.receive(&mut child.usage), Resume::Next) {
+                    return;
+                }
+                child.yielder = Some(yielder);
+                let result = child.run_generator(frame, &function);
+                let pins = child.transfer_pins(closure::kept_value(result.as_ref().map(Some)));
+                let _ = event_sender.send(Event::Finished(result, pins));
+            })
+            .map_err(|e| NjilError::ExecutionError(tip::spawn_failed(&e.to_string())))?;
+        
+        live.fetch_add(1, Ordering::Relaxed);
+        Ok(Generator { resume: resume_sender, events: event_receiver, live, usage: ExecutionUsage::default(), started: false, finished: false })
+    }
+    
+    /// 执行到下一个yield或函数体结束，已经结束的生成器总是返回 `Returned(null)`
+    ///
+    /// usage是调用者本次运行的资源使用情况，产生的值中的闭包固定到pins中。
+    pub(crate) fn next(&mut self, pins: &mut Pins, usage: ExecutionUsage) -> Result<GeneratorStep, NjilError> {
+        if self.finished {
+            return Ok(GeneratorStep::Returned(Value::Null));
+        }
+        self.started = true;
+        self.usage = usage.inherit();
+        if self.resume.send((Resume::Next, usage)).is_err() {
+            self.finish();
+            return Err(NjilError::ExecutionError(tip::thread_lost().to_string()));
+        }
+        match self.events.recv() {
+            Ok(Event::Yielded(value, received)) => {
+                pins.adopt(received);
+                Ok(GeneratorStep::Yielded(value))
+            },
+            Ok(Event::Finished(result, received)) => {
+                pins.adopt(received);
+                self.finish();
+                result.map(GeneratorStep::Returned)
+            },
+            Err(_) => {
+                self.finish();
+                Err(NjilError::ExecutionError(tip::thread_lost().to_string()))
+            },
+        }
+    }
+    
+    /// 关闭生成器并等待函数体中的finally和defer执行完毕，清理时的错误返回给调用者
+    pub(crate) fn close(&mut self, pins: &mut Pins, usage: ExecutionUsage) -> Result<(), NjilError> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finish();
+        // 还没有开始执行的生成器直接结束，线程不会执行函数体
+        if self.resume.send((Resume::Close, usage)).is_err() || !self.started {
+            return Ok(());
+        }
+        match self.events.recv() {
+            Ok(Event::Finished(result, received)) => {
+                pins.adopt(received);
+                result.map(|_| ())
+            },
+            Ok(Event::Yielded(..)) => Err(NjilError::ExecutionError(tip::yield_while_closing().to_string())),
+            Err(_) => Err(NjilError::ExecutionError(tip::thread_lost().to_string())),
+        }
+    }
+    
+    // 辅助函数：标记生成器已经结束
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.live.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for Generator {
+    fn drop(&mut self) {
+        // 关闭暂停中的生成器并等待finally和defer执行完毕，清理时的错误被忽略，
+        // 还没有开始执行的生成器在通道断开后直接结束
+        if !self.finished && self.started && self.resume.send((Resume::Close, self.usage.inherit())).is_ok() {
+            let _ = self.events.recv();
+        }
+        self.finish();
+    }
+}
+
+/// 创建指向闭包表中指定生成器的JSON值
+pub fn generator_value(id: u64) -> Value {
+    let mut marker = Map::new();
+    marker.insert(GENERATOR_KEY.to_string(), Value::from(id));
+    Value::Object(marker)
+}
+
+/// 如果值是生成器，返回其在闭包表中的编号
+pub fn generator_id(value: &Value) -> Option<u64> {
+    match value {
+        Value::Object(obj) if obj.len() == 1 => obj.get(GENERATOR_KEY)?.as_u64(),
+        _ => None,
+    }
+}
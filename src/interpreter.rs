@@ -1,64 +1,116 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
 use crate::types::{Function, NjilProgram};
 use crate::statements;
-use crate::statements::StatementHandler;
-use crate::builtin::BuiltinModuleRegistry;
+use crate::statements::{StatementHandler, StatementRegistry};
+use crate::builtin::{BuiltinModule, BuiltinModuleRegistry};
+use crate::native;
 use crate::debug_println;
-use crate::preprocessor::Preprocessor;
+use crate::errortip;
+use crate::scope::{self, Scope, ScopeKind, ScopeRef};
+use crate::closure::{self, Closure, ClosureTable, Pins};
+use crate::generator::{self, Generator, GeneratorStep, Resume, Yielder};
+use crate::task::{self, CancelToken, ErrorPolicy, TaskTable};
+use crate::policy::{self, ExecutionPolicy, ExecutionUsage};
+use crate::source::{self, Location, SourceInfo, Spans};
+use crate::stack::{ActiveFrame, Code, Deferred, StackFrame};
+use crate::expr::{self, Expr};
 use crate::utils::path;
-use crate::utils::path::PathPart;
+use crate::utils::type_convert;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Value;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use std::fs;
 use std::path::{Path, PathBuf};
-use regex;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+/// 正则表达式缓存的最大条目数
+const REGEX_CACHE_LIMIT: usize = 256;
+
+/// 表达式解析缓存的最大条目数
+const EXPR_CACHE_LIMIT: usize = 1024;
 
 /// 解释器，负责执行NeoJiLang代码
 pub struct Interpreter {
-    pub(crate) variables: HashMap<String, Value>,
+    pub(crate) global_scope: ScopeRef,
+    pub(crate) current_scope: ScopeRef,
     pub(crate) constants: HashMap<String, Value>,
-    pub(crate) statement_handlers: HashMap<String, Box<dyn StatementHandler>>,
     pub(crate) builtin_modules: BuiltinModuleRegistry,
-    pub(crate) returning: Option<Value>,
+    pub(crate) statement_handlers: StatementRegistry,
     loaded_modules: HashSet<String>,
     current_dir: Option<PathBuf>,
-    current_program: Option<NjilProgram>,
+    functions: HashMap<String, Arc<Function>>,
+    /// 闭包表，保存闭包和生成器，与子解释器共享，生成器中创建的闭包和生成器也可以在外面使用
+    pub(crate) closures: Arc<ClosureTable>,
+    /// 当前线程中正在执行的语句持有的闭包
+    pins: RefCell<Pins>,
+    /// pins中最早的这些固定属于上一次运行交给宿主程序的结果
+    host_pins: usize,
+    /// 常量中的闭包
+    constant_pins: Pins,
+    /// 任务表，与子解释器共享，任务中创建的任务也可以在外面等待
+    pub(crate) tasks: Arc<TaskTable>,
+    /// 是否是宿主程序创建的解释器，它被丢弃时结束所有暂停中的生成器线程并取消所有任务
+    root: bool,
+    /// 在生成器线程中执行时，yield通过它交出值
+    pub(crate) yielder: Option<Yielder>,
+    /// 在任务中执行时，任务被取消后在下一条语句处停止
+    pub(crate) cancel: CancelToken,
+    regex_cache: HashMap<String, regex::Regex>,
+    expr_cache: HashMap<String, Arc<Expr>>,
+    policy: ExecutionPolicy,
+    /// 本次运行已经使用的资源，生成器线程在每次恢复时沿用调用者的运行
+    pub(crate) usage: ExecutionUsage,
+    call_stack: Vec<ActiveFrame>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        // 丢弃生成器会断开通道，暂停中的生成器线程像被关闭一样结束
+        if self.root {
+            self.closures.drop_generators();
+            self.tasks.cancel_all();
+        }
+    }
 }
 
 impl Interpreter {
     /// 创建一个新的解释器实例
     pub fn new() -> Self {
+        let global_scope = Scope::new_ref(ScopeKind::Global, None);
+        let closures = Arc::new(ClosureTable::default());
         Self {
-            variables: HashMap::new(),
+            current_scope: global_scope.clone(),
+            global_scope,
             constants: HashMap::new(),
-            statement_handlers: HashMap::new(),
             builtin_modules: BuiltinModuleRegistry::new(),
-            returning: None,
+            statement_handlers: StatementRegistry::new(),
             loaded_modules: HashSet::new(),
             current_dir: None,
-            current_program: None,
-        }
-    }
-
-    /// 创建一个新的干净的解释器实例，但保留变量
-    pub fn create_clean_instance(&self) -> Self {
-        let mut new_instance = Self {
-            variables: HashMap::new(),
-            constants: self.constants.clone(),
-            statement_handlers: HashMap::new(),
-            builtin_modules: self.builtin_modules.clone_empty(),
-            returning: None,
-            loaded_modules: self.loaded_modules.clone(),
-            current_dir: self.current_dir.clone(),
-            current_program: self.current_program.clone(),
-        };
-        
-        // 复制变量
-        for (key, value) in &self.variables {
-            new_instance.variables.insert(key.clone(), value.clone());
+            functions: HashMap::new(),
+            pins: RefCell::new(Pins::new(closures.clone())),
+            host_pins: 0,
+            constant_pins: Pins::new(closures.clone()),
+            closures,
+            tasks: Arc::default(),
+            root: true,
+            yielder: None,
+            cancel: CancelToken::default(),
+            regex_cache: HashMap::new(),
+            expr_cache: HashMap::new(),
+            policy: ExecutionPolicy::default(),
+            usage: ExecutionUsage::default(),
+            call_stack: Vec::new(),
         }
-        
-        new_instance
     }
 
     pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<NjilProgram, NjilError> {
@@ -69,50 +121,329 @@ impl Interpreter {
             self.current_dir = Some(parent.to_path_buf());
         }
         
-        // 使用预处理器处理文件内容，移除注释
-        let processed_content = Preprocessor::preprocess_file(path)?;
+        // 使用预处理器处理文件内容，移除注释，并记录语句在源码中的位置
+        let content = std::fs::read_to_string(path)?;
+        let source = SourceInfo::new(path.display().to_string(), &content)?;
         debug_println!("文件预处理完成，准备解析JSON");
         
-        // 解析处理后的内容
-        let program: NjilProgram = match serde_json::from_str(&processed_content) {
-            Ok(p) => p,
-            Err(e) => {
-                return Err(NjilError::ExecutionError(
-                    format!("解析JSON失败: {}，请检查文件格式是否正确", e)
-                ));
-            }
-        };
-        
-        // 保存当前程序
-        self.current_program = Some(program.clone());
-        
-        Ok(program)
+        parse_program(source)
     }
 
     pub fn execute(&mut self, program: &NjilProgram) -> Result<Value, NjilError> {
-        // 保存当前程序
-        self.current_program = Some(program.clone());
+        self.load_program(program)?;
+        
+        // 执行主函数，main函数直接运行在全局作用域中
+        if let Some(main_fn) = self.functions.get("main").cloned() {
+            self.run_limited(|interpreter| {
+                interpreter.with_frame(ActiveFrame::new("main", None).with_code(Code::function(&main_fn)), |interpreter| interpreter.execute_function(&main_fn))
+            })
+        } else {
+            Err(NjilError::ExecutionError("找不到main函数".to_string()))
+        }
+    }
+    
+    /// 设置执行策略，之后的每次运行都受其限制
+    pub fn set_policy(&mut self, policy: ExecutionPolicy) {
+        self.policy = policy;
+    }
+    
+    /// 获取当前的执行策略
+    pub fn policy(&self) -> &ExecutionPolicy {
+        &self.policy
+    }
+    
+    /// 作为一次运行执行闭包，最外层的运行开始时重置语句计数和截止时间
+    fn run_limited<T, F>(&mut self, f: F) -> Result<T, NjilError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, NjilError>,
+    {
+        if self.usage.active_runs == 0 {
+            self.usage.start(&self.policy);
+        }
+        self.usage.active_runs += 1;
+        let result = f(self);
+        self.usage.active_runs -= 1;
+        if self.usage.active_runs == 0 {
+            // 交给宿主程序的结果中的闭包保留到下一次运行结束
+            self.host_pins = self.pins.get_mut().retire(self.host_pins);
+        }
+        result
+    }
+    
+    /// 记录执行了一条语句，超出语句数或时间限制，或者所在的任务已被取消时返回错误
+    pub fn check_step(&mut self) -> Result<(), NjilError> {
+        if self.cancel.is_cancelled() {
+            return Err(NjilError::ExecutionError(errortip::task::cancelled().to_string()));
+        }
+        if self.policy.limits_statements() {
+            policy::check_step(&self.policy, &mut self.usage)?;
+        }
+        Ok(())
+    }
+    
+    /// 检查指令是否被执行策略禁用
+    ///
+    /// 除了语句中使用的名称，还检查处理器的主名称和所属的模块，通过别名调用的指令同样受限制。
+    pub fn check_instruction(&self, name: &str, handler: &dyn StatementHandler) -> Result<(), NjilError> {
+        if !self.policy.restricts_instructions() {
+            return Ok(());
+        }
+        let canonical = handler.name();
+        let allowed = self.policy.is_instruction_allowed(name)
+            && self.policy.is_instruction_allowed(canonical)
+            && self.statement_handlers.module_of(canonical).is_none_or(|module| self.policy.is_module_allowed(module));
+        if allowed {
+            Ok(())
+        } else {
+            Err(NjilError::LimitExceeded(errortip::limit::instruction_denied(name)))
+        }
+    }
+    
+    /// 检查语句产生的值是否超出大小限制
+    pub fn check_value_size(&self, value: &Value) -> Result<(), NjilError> {
+        policy::check_value_size(&self.policy, value)
+    }
+    
+    /// 本次运行剩余的时间，没有时间限制时返回None
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.usage.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+    
+    /// 注册程序中的函数并处理导入，但不执行main函数
+    ///
+    /// 同名函数会覆盖之前加载的版本，已导入的模块不会重复导入。
+    pub fn load_program(&mut self, program: &NjilProgram) -> Result<(), NjilError> {
+        for (name, function) in &program.program.functions {
+            let path = ["program", name.as_str()];
+            self.define_function(name.clone(), function, program.source.as_deref(), &path);
+        }
         
-        // 处理导入
         if let Some(imports) = &program.import {
             self.process_imports(imports)?;
         }
         
-        // 执行主函数
-        if let Some(main_fn) = program.program.functions.get("main") {
-            self.execute_function(main_fn)
-        } else {
-            Err(NjilError::ExecutionError("找不到main函数".to_string()))
+        Ok(())
+    }
+    
+    /// 加载NJIL源码中的函数和导入，不执行main函数
+    pub fn load_njil_str(&mut self, source: &str) -> Result<(), NjilError> {
+        let program = parse_program(SourceInfo::new(STRING_SOURCE_NAME, source)?)?;
+        self.load_program(&program)
+    }
+    
+    /// 在当前解释器中运行NJIL源码，返回main函数的返回值
+    pub fn run_njil_str(&mut self, source: &str) -> Result<Value, NjilError> {
+        let program = parse_program(SourceInfo::new(STRING_SOURCE_NAME, source)?)?;
+        self.execute(&program)
+    }
+    
+    /// 在当前解释器的全局作用域中运行NJIS源码
+    pub fn run_njis_str(&mut self, source: &str) -> Result<Value, NjilError> {
+        let source = SourceInfo::new(STRING_SOURCE_NAME, source)?;
+        let statements = crate::njis::parse_njis(&source, "NJIS字符串")?;
+        self.run_njis_source(&source, statements)
+    }
+    
+    /// 运行从源码解析出的NJIS语句，出错时报告语句在源码中的位置
+    pub(crate) fn run_njis_source(&mut self, source: &SourceInfo, statements: Vec<Value>) -> Result<Value, NjilError> {
+        let statements = Arc::new(statements);
+        let code = source.spans(&[]).map(|spans| Code::Script(statements.clone(), spans));
+        self.run_script(&statements, code)
+    }
+    
+    /// 注册函数，有源码信息时函数带上定义在源码中的位置
+    fn define_function(&mut self, name: String, function: &Function, source: Option<&SourceInfo>, path: &[&str]) {
+        let mut function = function.clone();
+        if let Some(source) = source {
+            function.spans = source.spans(path);
+        }
+        self.functions.insert(name, Arc::new(function));
+    }
+    
+    /// 给错误加上出错语句的源码位置和当前的调用栈，已经带有这些信息的错误保持不变
+    pub(crate) fn trace_error(&self, statement: &Value, error: NjilError) -> NjilError {
+        match error {
+            NjilError::Traced { .. } => error,
+            error => {
+                let location = self.location_of(statement);
+                let stack = self.capture_stack();
+                if location.is_none() && stack.is_empty() {
+                    return error;
+                }
+                NjilError::Traced { error: Box::new(error), location, stack }
+            },
         }
     }
     
+    /// 在新的调用栈帧中执行闭包
+    fn with_frame<T, F>(&mut self, frame: ActiveFrame, f: F) -> Result<T, NjilError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, NjilError>,
+    {
+        self.call_stack.push(frame);
+        let result = f(self);
+        self.call_stack.pop();
+        result
+    }
+    
+    /// 记录当前帧开始处理一条语句，返回之前正在处理的语句
+    pub(crate) fn enter_statement(&mut self, statement: &Value) -> Option<usize> {
+        let frame = self.call_stack.last_mut()?;
+        frame.statement.replace(source::address(statement))
+    }
+    
+    /// 语句处理完毕，恢复当前帧之前正在处理的语句
+    pub(crate) fn leave_statement(&mut self, previous: Option<usize>) {
+        if let Some(frame) = self.call_stack.last_mut() {
+            frame.statement = previous;
+        }
+    }
+    
+    // 辅助函数：记录当前帧正在执行的函数体语句序号
+    fn set_statement_index(&mut self, index: usize) {
+        if let Some(frame) = self.call_stack.last_mut() {
+            frame.index = Some(index);
+        }
+    }
+    
+    /// 当前的调用栈，最内层的调用在前
+    pub fn capture_stack(&self) -> Vec<StackFrame> {
+        self.call_stack.iter().rev().map(|frame| StackFrame {
+            function: frame.function.clone(),
+            namespace: frame.namespace.clone(),
+            statement: frame.index,
+            location: frame.statement.and_then(|address| frame.locate(address)).map(|spans| spans.location().clone()),
+        }).collect()
+    }
+    
+    /// 获取当前帧中正在执行的语句（或其中的值）在源码中的位置
+    pub fn location_of(&self, statement: &Value) -> Option<Location> {
+        self.spans_of(statement).map(|spans| spans.location().clone())
+    }
+    
+    /// 当前帧中正在执行的代码里一个值及其子值在源码中的位置
+    pub(crate) fn spans_of(&self, value: &Value) -> Option<Arc<Spans>> {
+        self.call_stack.last()?.locate(source::address(value)).cloned()
+    }
+    
+    /// 依次执行NJIS语句，返回最后一条语句的值或return的值
+    ///
+    /// NJIS不支持import，所以会自动导入所有内置模块。
+    pub fn execute_njis(&mut self, statements: &[Value]) -> Result<Value, NjilError> {
+        self.run_script(statements, None)
+    }
+    
+    // 辅助函数：在脚本帧中执行NJIS语句，code是这些语句带有源码位置时对应的代码
+    fn run_script(&mut self, statements: &[Value], code: Option<Code>) -> Result<Value, NjilError> {
+        self.import_all_builtin_modules()?;
+        
+        self.run_limited(|interpreter| interpreter.with_frame(ActiveFrame::new("<script>", None).with_code(code), |interpreter| {
+            let result = interpreter.execute_script(statements);
+            interpreter.run_deferred(result)
+        }))
+    }
+    
+    // 辅助函数：依次执行NJIS脚本的顶层语句
+    fn execute_script(&mut self, statements: &[Value]) -> Result<Value, NjilError> {
+        let mut result = Value::Null;
+        let pins = self.pin_mark();
+        for (i, statement) in statements.iter().enumerate() {
+            self.set_statement_index(i);
+            match statements::execute_statement(self, statement)? {
+                Completion::Normal(value) => {
+                    self.release_pins(pins, Some(&value));
+                    result = value;
+                },
+                // 遇到返回语句，提前结束执行
+                Completion::Return(value) => return Ok(value),
+                signal => return signal.into_value(),
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    /// 登记一个在当前函数退出时执行的语句块，语句块在登记时的作用域中执行
+    pub(crate) fn defer(&mut self, block: &Value) -> Result<(), NjilError> {
+        let spans = self.spans_of(block);
+        let Some(frame) = self.call_stack.last_mut() else {
+            return Err(NjilError::ExecutionError(crate::errortip::exception::defer_outside_function().to_string()));
+        };
+        frame.deferred.push(Deferred { block: Arc::new(block.clone()), spans, scope: self.current_scope.clone() });
+        Ok(())
+    }
+    
+    /// 按后进先出的顺序执行当前帧登记的defer语句块
+    ///
+    /// 所有语句块都会执行；语句块出错时替换原来的结果，return的值替换原来的返回值。
+    fn run_deferred(&mut self, mut result: Result<Value, NjilError>) -> Result<Value, NjilError> {
+        while let Some(deferred) = self.call_stack.last_mut().and_then(|frame| frame.deferred.pop()) {
+            // 执行期间语句块是帧中最近开始执行的代码
+            let code = deferred.spans.clone().map(|spans| Code::Block(deferred.block.clone(), spans));
+            let entered = code.is_some();
+            if let Some(frame) = self.call_stack.last_mut() {
+                frame.code.extend(code);
+            }
+            let outcome = self.run_in_scope(deferred.scope.clone(), |interpreter| {
+                statements::execute_block(interpreter, &deferred.block)
+            });
+            if let Some(frame) = self.call_stack.last_mut().filter(|_| entered) {
+                frame.code.pop();
+            }
+            match outcome.and_then(Completion::into_returned) {
+                Ok(None) => {},
+                Ok(Some(value)) => result = Ok(value),
+                Err(e) => result = Err(e),
+            }
+        }
+        result
+    }
+    
+    /// 把Rust值设置为全局变量
+    pub fn set_global<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), NjilError> {
+        let value = native::to_value(value)?;
+        self.global_scope.lock().unwrap().variables.insert(name.to_string(), value);
+        Ok(())
+    }
+    
+    /// 读取全局变量并转换为Rust类型
+    pub fn get_global<T: DeserializeOwned>(&self, name: &str) -> Result<T, NjilError> {
+        let value = self.global_scope.lock().unwrap().variables.get(name).cloned()
+            .ok_or_else(|| NjilError::UndefinedVariable(name.to_string()))?;
+        native::from_value(value)
+    }
+    
+    /// 用Rust值作为位置参数调用函数，并把返回值转换为Rust类型
+    ///
+    /// 参数是元组（或数组、Vec），每个元素是一个位置参数，`()` 表示不传参数；
+    /// 单个数组参数要写成一元组 `(vec,)`。命名参数使用 [`Interpreter::call_named`]。
+    pub fn call<A: Serialize + ?Sized, R: DeserializeOwned>(&mut self, function_name: &str, args: &A) -> Result<R, NjilError> {
+        let args = match native::to_value(args)? {
+            Value::Null => Vec::new(),
+            Value::Array(args) => args,
+            _ => return Err(NjilError::TypeError(errortip::function::call_requires_tuple().to_string())),
+        };
+        let result = self.run_limited(|interpreter| interpreter.call_function(function_name, &args))?;
+        native::from_value(result)
+    }
+    
+    /// 用Rust值作为命名参数调用函数，并把返回值转换为Rust类型
+    ///
+    /// 参数是结构体或映射，每个字段是一个命名参数。
+    pub fn call_named<A: Serialize + ?Sized, R: DeserializeOwned>(&mut self, function_name: &str, kwargs: &A) -> Result<R, NjilError> {
+        let Value::Object(kwargs) = native::to_value(kwargs)? else {
+            return Err(NjilError::TypeError(errortip::function::kwargs_requires_object().to_string()));
+        };
+        let result = self.run_limited(|interpreter| interpreter.call_function_with_kwargs(function_name, &[], &kwargs))?;
+        native::from_value(result)
+    }
+    
     /// 处理导入语句
-    fn process_imports(&mut self, imports: &Vec<Value>) -> Result<(), NjilError> {
+    fn process_imports(&mut self, imports: &[Value]) -> Result<(), NjilError> {
         for import in imports {
             if let Value::String(import_path) = import {
                 // 检查是否为内置模块（以!开头）
-                if import_path.starts_with('!') {
-                    let module_name = &import_path[1..]; // 去掉!前缀
+                if let Some(module_name) = import_path.strip_prefix('!') {
                     self.import_builtin_module(module_name)?;
                 } else {
                     // 检查是否为NJIM模块
@@ -200,9 +531,8 @@ impl Interpreter {
                         debug_println!("导入函数: {}", full_func_name);
                         
                         // 将函数添加到当前解释器的函数表中
-                        if let Some(current_program) = &mut self.current_program {
-                            current_program.program.functions.insert(full_func_name, func_def.clone());
-                        }
+                        let path = ["exports", "functions", func_name.as_str()];
+                        self.define_function(full_func_name, func_def, module.source.as_deref(), &path);
                     }
                 }
                 
@@ -211,12 +541,54 @@ impl Interpreter {
                 
                 Ok(())
             },
+            // 保留模块源码中的出错位置
+            Err(NjilError::Traced { error, location, stack }) => {
+                Err(NjilError::Traced {
+                    error: Box::new(NjilError::ExecutionError(format!("导入模块失败: {}", error))),
+                    location,
+                    stack,
+                })
+            },
             Err(e) => {
                 Err(NjilError::ExecutionError(format!("导入模块失败: {}", e)))
             }
         }
     }
     
+    /// 向当前解释器注册一个语句处理器
+    ///
+    /// 注册只对当前解释器实例生效，不会影响同一进程中的其他解释器。
+    pub fn register_handler(&mut self, handler: &'static dyn StatementHandler) {
+        self.statement_handlers.register_handler(handler);
+    }
+    
+    /// 注册一个由Rust闭包实现的指令，闭包收到解释器和已求值的参数
+    ///
+    /// 与内置模块一样，注册只对当前解释器生效。
+    pub fn register_instruction<F>(&mut self, name: impl Into<String>, func: F)
+    where
+        F: Fn(&mut Interpreter, Value) -> Result<Value, NjilError> + Send + Sync + 'static,
+    {
+        let handler = native::NativeHandler::new(name, Arc::new(func));
+        self.statement_handlers.register_shared_handler(Arc::new(handler));
+    }
+    
+    /// 注册一个参数和返回值通过serde自动转换的指令
+    pub fn register_typed_instruction<A, R, F>(&mut self, name: impl Into<String>, func: F)
+    where
+        A: serde::de::DeserializeOwned,
+        R: serde::Serialize,
+        F: Fn(A) -> Result<R, NjilError> + Send + Sync + 'static,
+    {
+        let handler = native::NativeHandler::new(name, native::typed(func));
+        self.statement_handlers.register_shared_handler(Arc::new(handler));
+    }
+    
+    /// 注册一个模块，注册后脚本可以通过 `"!模块名"` 导入
+    pub fn register_module(&mut self, module: Box<dyn BuiltinModule + Send + Sync>) {
+        self.builtin_modules.register_module(module);
+    }
+    
     /// 导入内置模块
     fn import_builtin_module(&mut self, module_name: &str) -> Result<(), NjilError> {
         // 检查模块是否已加载
@@ -224,21 +596,30 @@ impl Interpreter {
             return Ok(());
         }
         
+        if !self.policy.is_module_allowed(module_name) {
+            return Err(NjilError::LimitExceeded(errortip::limit::module_denied(module_name)));
+        }
+        
         // 克隆模块名称以避免借用冲突
         let module_name_owned = module_name.to_string();
         
         // 获取并存储模块中的所有处理器
-        let handlers = {
+        let (handlers, shared_handlers) = {
             let module = self.builtin_modules.get_module(&module_name_owned)
                 .ok_or_else(|| NjilError::ExecutionError(format!("找不到内置模块: {}", module_name_owned)))?;
             
             // 获取模块中的所有处理器
-            module.get_handlers()
+            (module.get_handlers(), module.get_shared_handlers())
         };
         
-        // 将处理器注册到语句处理系统
+        // 将处理器注册到当前解释器的语句注册表
         for handler in handlers {
-            statements::register_handler(handler);
+            self.statement_handlers.set_module(handler.name(), &module_name_owned);
+            self.register_handler(handler);
+        }
+        for handler in shared_handlers {
+            self.statement_handlers.set_module(handler.name(), &module_name_owned);
+            self.statement_handlers.register_shared_handler(handler);
         }
         
         // 标记模块为已加载
@@ -261,11 +642,14 @@ impl Interpreter {
         Ok(())
     }
     
-    /// 导入所有内置模块（用于NJIS）
+    /// 导入所有内置模块（用于NJIS），跳过被执行策略禁用的模块
     pub fn import_all_builtin_modules(&mut self) -> Result<(), NjilError> {
         // 获取所有模块名称的副本，避免借用冲突
         let module_names: Vec<String> = self.builtin_modules.get_module_names()
-            .iter().map(|&s| s.clone()).collect();
+            .into_iter()
+            .filter(|name| self.policy.is_module_allowed(name))
+            .cloned()
+            .collect();
         
         // 逐个导入模块
         for module_name in module_names {
@@ -276,20 +660,26 @@ impl Interpreter {
     }
 
     fn execute_function(&mut self, function: &Function) -> Result<Value, NjilError> {
+        let result = self.execute_function_body(function);
+        self.run_deferred(result)
+    }
+    
+    fn execute_function_body(&mut self, function: &Function) -> Result<Value, NjilError> {
         debug_println!("[Interpreter::execute_function] 开始执行函数, 语句数量: {}", function.body.len());
         
+        let pins = self.pin_mark();
         for (i, statement) in function.body.iter().enumerate() {
             debug_println!("[Interpreter::execute_function] 执行语句 #{}: {}", i, serde_json::to_string_pretty(statement).unwrap());
+            self.set_statement_index(i);
             
-            match self.execute_statement(statement) {
-                Ok(result) => {
-                    debug_println!("[Interpreter::execute_function] 语句 #{} 执行成功, 结果: {}", i, serde_json::to_string_pretty(&result).unwrap());
+            match statements::execute_statement(self, statement).and_then(Completion::into_returned) {
+                Ok(None) => {
+                    debug_println!("[Interpreter::execute_function] 语句 #{} 执行成功", i);
+                    self.release_pins(pins, None);
                 },
-                Err(NjilError::ReturnValue(value)) => {
+                Ok(Some(value)) => {
                     debug_println!("[Interpreter::execute_function] 遇到return语句, 返回值: {}", serde_json::to_string_pretty(&value).unwrap());
-                    let value_clone = value.clone();
-                    self.returning = Some(value);
-                    return Ok(value_clone);
+                    return Ok(value);
                 }
                 Err(e) => {
                     debug_println!("[Interpreter::execute_function] 语句 #{} 执行失败: {:?}", i, e);
@@ -303,7 +693,7 @@ impl Interpreter {
     }
 
     fn execute_statement(&mut self, statement: &Value) -> Result<Value, NjilError> {
-        // 使用语句处理函数处理语句
+        // 使用语句处理函数处理语句，语句出现在需要值的位置
         statements::handle_statement(self, statement)
     }
 
@@ -311,34 +701,14 @@ impl Interpreter {
         match value {
             Value::String(_) => Ok(value.clone()),
             Value::Object(obj) => {
-                // 如果是对象，尝试执行它
-                if obj.len() == 1 {
-                    let (key, value) = obj.iter().next().unwrap();
-                    
-                    // 特殊处理嵌套变量路径的情况
-                    if key == "var" && value.is_string() {
-                        if let Value::String(var_path) = value {
-                            if var_path.contains('.') || var_path.contains('[') {
-                                return statements::var::VAR_HANDLER.handle(self, value);
-                            }
-                        }
-                    }
-                    
-                    // 特殊处理嵌套常量路径的情况
-                    if key == "const" && value.is_string() {
-                        if let Value::String(const_path) = value {
-                            if const_path.contains('.') || const_path.contains('[') {
-                                return statements::constant::CONST_HANDLER.handle(self, value);
-                            }
-                        }
-                    }
-                    
-                    // 处理函数调用
-                    if key == "function.call" || key == "call" || key == "func.call" {
-                        return statements::function_call::FUNCTION_CALL_HANDLER.handle(self, value);
-                    }
+                // 闭包值直接返回
+                if obj.len() == 1 && obj.contains_key(closure::CLOSURE_KEY) {
+                    return Ok(value.clone());
                 }
                 
+                // 如果是对象，尝试执行它
+                
+                // 嵌套变量路径、常量路径和函数调用都由语句处理函数统一处理
                 self.execute_statement(value)
             }
             Value::Array(_) => Ok(value.clone()),
@@ -348,6 +718,27 @@ impl Interpreter {
         }
     }
 
+    /// 递归求值：语句会被执行，数组和普通对象中的每个元素也会被求值
+    pub fn evaluate_deep(&mut self, value: &Value) -> Result<Value, NjilError> {
+        match value {
+            Value::Array(items) => {
+                let mut result = Vec::with_capacity(items.len());
+                for item in items {
+                    result.push(self.evaluate_deep(item)?);
+                }
+                Ok(Value::Array(result))
+            },
+            Value::Object(obj) if !statements::is_statement(self, obj) && closure::closure_id(value).is_none() => {
+                let mut result = serde_json::Map::new();
+                for (key, item) in obj {
+                    result.insert(key.clone(), self.evaluate_deep(item)?);
+                }
+                Ok(Value::Object(result))
+            },
+            _ => self.evaluate_value(value),
+        }
+    }
+
     /// 解析内容值，支持变量引用和表达式执行
     /// 这个方法可以被各个模块使用，用于统一处理内容值
     pub fn parse_content(&mut self, content: &Value) -> Result<String, NjilError> {
@@ -395,6 +786,8 @@ impl Interpreter {
                 }
                 parts.join(", ")
             },
+            // 大整数和定点小数显示为十进制数字
+            Value::Object(_) if type_convert::is_tagged_number(value) => type_convert::to_string(value),
             Value::Object(obj) => {
                 let mut parts = Vec::new();
                 for (key, val) in obj {
@@ -405,25 +798,146 @@ impl Interpreter {
         }
     }
     
-    #[allow(dead_code)]
-    pub fn get_variable(&self, name: &str) -> Option<&Value> {
-        self.variables.get(name)
+    /// 沿作用域链获取变量值
+    pub fn get_variable(&self, name: &str) -> Option<Value> {
+        self.read_variable(name, Value::clone)
     }
     
-    #[allow(dead_code)]
+    /// 在变量所在的作用域中借用变量值，只复制需要的部分
+    ///
+    /// 复制出的值中的闭包在当前语句结束之前不会被回收。
+    pub fn read_variable(&self, name: &str, f: impl FnOnce(&Value) -> Value) -> Option<Value> {
+        self.pins.borrow_mut().read(|| scope::read_variable(&self.current_scope, &self.global_scope, name, f))
+    }
+    
+    /// 当前线程固定的闭包数，语句开始时记录，结束时传给 `release_pins`
+    pub(crate) fn pin_mark(&self) -> usize {
+        self.pins.borrow().mark()
+    }
+    
+    /// 解除mark之后固定的闭包，keep中引用的闭包保持固定
+    pub(crate) fn release_pins(&self, mark: usize, keep: Option<&Value>) {
+        self.pins.borrow_mut().release(mark, keep);
+    }
+    
+    /// 常量不能修改，其中的闭包在解释器存在期间一直保留
+    pub(crate) fn pin_constant(&mut self, value: &Value) {
+        self.constant_pins.adopt(Pins::of(&self.closures, Some(value)));
+    }
+    
+    /// 检查变量在当前作用域链中是否可见
+    pub fn has_variable(&self, name: &str) -> bool {
+        scope::find_owner(&self.current_scope, &self.global_scope, name).is_some()
+    }
+    
+    /// 给变量赋值
+    ///
+    /// 已存在的变量在其所在作用域中更新，新变量创建在当前函数帧中。
     pub fn set_variable(&mut self, name: String, value: Value) {
-        self.variables.insert(name, value);
+        scope::assign_variable(&self.current_scope, &self.global_scope, name, value);
     }
     
-    /// 检查是否正在返回
-    pub fn is_returning(&self) -> bool {
-        self.returning.is_some()
+    /// 按路径给变量赋值，支持 `a.b[0]` 形式的嵌套路径
+    pub fn set_variable_path(&mut self, var_path: &str, value: Value) -> Result<(), NjilError> {
+        if var_path.contains('.') || var_path.contains('[') {
+            let base_name = match path::parse_path(var_path)?.first() {
+                Some(path::PathPart::ObjectProperty(name)) => name.clone(),
+                _ => return Err(NjilError::ExecutionError("变量名不能是数组索引".to_string())),
+            };
+            let target = scope::find_assign_target(&self.current_scope, &self.global_scope, &base_name);
+            let mut target = target.lock().unwrap();
+            path::set_nested_value(&mut target.variables, var_path, value)
+        } else {
+            self.set_variable(var_path.to_string(), value);
+            Ok(())
+        }
+    }
+    
+    /// 在当前作用域中声明变量（用于循环变量、catch变量和函数参数）
+    pub fn declare_variable(&mut self, name: String, value: Value) {
+        self.current_scope.lock().unwrap().variables.insert(name, value);
+    }
+    
+    /// 将变量名声明为全局变量，之后在当前函数中的读写都作用于全局作用域
+    pub fn declare_global(&mut self, name: String) {
+        let frame = scope::frame_of(&self.current_scope);
+        let mut frame = frame.lock().unwrap();
+        if frame.kind == ScopeKind::Function {
+            frame.global_names.insert(name);
+        }
     }
     
-    /// 设置返回状态
-    pub fn set_returning(&mut self, returning: bool) {
-        self.returning = if returning { Some(Value::Null) } else { None };
+    /// 给当前函数帧之外的变量赋值
+    ///
+    /// 从外层作用域开始查找已存在的变量，找不到时写入全局作用域。
+    pub fn set_outer_variable(&mut self, var_path: &str, value: Value) -> Result<(), NjilError> {
+        let base_name = match path::parse_path(var_path)?.first() {
+            Some(path::PathPart::ObjectProperty(name)) => name.clone(),
+            _ => return Err(NjilError::ExecutionError("变量名不能是数组索引".to_string())),
+        };
+        
+        let frame = scope::frame_of(&self.current_scope);
+        let outer = frame.lock().unwrap().parent.clone();
+        let target = outer
+            .and_then(|outer| scope::find_owner(&outer, &self.global_scope, &base_name))
+            .unwrap_or_else(|| self.global_scope.clone());
+        
+        let mut target = target.lock().unwrap();
+        if var_path.contains('.') || var_path.contains('[') {
+            path::set_nested_value(&mut target.variables, var_path, value)
+        } else {
+            target.variables.insert(base_name, value);
+            Ok(())
+        }
+    }
+    
+    /// 在新的作用域中执行操作，无论操作成功与否都会恢复原来的作用域
+    pub fn with_scope<T, F>(&mut self, kind: ScopeKind, f: F) -> Result<T, NjilError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, NjilError>,
+    {
+        let scope = self.new_scope(kind, self.current_scope.clone());
+        self.run_in_scope(scope, f)
+    }
+    
+    /// 在循环体的块作用域中执行一次迭代
+    ///
+    /// `slot` 由循环在迭代之间持有：迭代结束后作用域没有被闭包、defer或生成器捕获时清空其中的变量，
+    /// 留给下一次迭代复用；被捕获时丢弃，下一次迭代使用新的作用域，每个闭包仍然看到自己那次迭代的变量。
+    pub(crate) fn with_loop_scope<T, F>(&mut self, slot: &mut Option<ScopeRef>, f: F) -> Result<T, NjilError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, NjilError>,
+    {
+        let scope = match slot.take() {
+            Some(scope) => scope,
+            None => self.new_scope(ScopeKind::Block, self.current_scope.clone()),
+        };
+        let result = self.run_in_scope(scope.clone(), f);
+        if Arc::strong_count(&scope) == 1 {
+            scope.lock().unwrap().variables.clear();
+            *slot = Some(scope);
+        }
+        result
+    }
+    
+    // 辅助函数：创建作用域并登记到闭包表，回收闭包时据此找出正在使用的作用域
+    fn new_scope(&self, kind: ScopeKind, parent: ScopeRef) -> ScopeRef {
+        let scope = Scope::new_ref(kind, Some(parent));
+        self.closures.register_scope(&scope);
+        scope
+    }
+    
+    /// 在指定的作用域中执行操作，完成后恢复原来的作用域
+    pub(crate) fn run_in_scope<T, F>(&mut self, scope: ScopeRef, f: F) -> Result<T, NjilError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, NjilError>,
+    {
+        let previous = std::mem::replace(&mut self.current_scope, scope);
+        let result = f(self);
+        self.current_scope = previous;
+        result
     }
+    
 
     /// 执行算术运算或比较运算，直接获取结果
     pub fn execute_operation(&mut self, value: &Value) -> Result<Value, NjilError> {
@@ -450,60 +964,400 @@ impl Interpreter {
 
     /// 调用函数
     pub fn call_function(&mut self, function_name: &str, args: &[Value]) -> Result<Value, NjilError> {
+        self.call_function_with_kwargs(function_name, args, &serde_json::Map::new())
+    }
+    
+    /// 调用函数，同时传入位置参数和命名参数
+    ///
+    /// 找不到同名函数时，会尝试调用同名变量中保存的闭包。
+    pub fn call_function_with_kwargs(&mut self, function_name: &str, args: &[Value], kwargs: &serde_json::Map<String, Value>) -> Result<Value, NjilError> {
         debug_println!("[Interpreter::call_function] 开始调用函数: {}", function_name);
         
         // 检查函数是否存在
-        let function = match self.get_function(function_name) {
+        let function = match self.functions.get(function_name) {
             Some(func) => {
                 debug_println!("[Interpreter::call_function] 找到函数: {}", function_name);
                 func.clone()
             },
             None => {
+                if let Some(id) = self.get_variable(function_name).as_ref().and_then(closure::closure_id) {
+                    debug_println!("[Interpreter::call_function] 变量 {} 是闭包，调用闭包", function_name);
+                    return self.call_closure(id, args, kwargs);
+                }
                 debug_println!("[Interpreter::call_function] 找不到函数: {}", function_name);
-                return Err(NjilError::ExecutionError(format!("找不到函数: {}", function_name)));
+                return Err(NjilError::ExecutionError(errortip::function::undefined_function(function_name)));
+            },
+        };
+        
+        // 函数帧的外层是全局作用域
+        let global = self.global_scope.clone();
+        self.invoke(function_name, &function, global, args, kwargs)
+    }
+    
+    /// 调用一个可调用的值：闭包值，或者函数名字符串
+    pub fn call_value(&mut self, callee: &Value, args: &[Value], kwargs: &serde_json::Map<String, Value>) -> Result<Value, NjilError> {
+        if let Some(id) = closure::closure_id(callee) {
+            return self.call_closure(id, args, kwargs);
+        }
+        match callee {
+            Value::String(function_name) => self.call_function_with_kwargs(function_name, args, kwargs),
+            _ => Err(NjilError::ExecutionError(errortip::function::not_callable(&callee.to_string()))),
+        }
+    }
+    
+    /// 判断值是否可以被调用
+    pub fn is_callable(&self, value: &Value) -> bool {
+        match value {
+            Value::String(name) => self.functions.contains_key(name),
+            _ => closure::closure_id(value).is_some_and(|id| self.closures.contains(id)),
+        }
+    }
+    
+    /// 获取可调用值最多接受的位置参数个数
+    ///
+    /// 未声明参数（通过 $args 访问）或声明了剩余参数时返回None，表示不限。
+    pub fn callable_arity(&self, callee: &Value) -> Option<usize> {
+        let function = match callee {
+            Value::String(name) => match self.functions.get(name) {
+                Some(function) => function.clone(),
+                None => {
+                    let id = self.get_variable(name).as_ref().and_then(closure::closure_id)?;
+                    self.closures.get(id)?.function
+                },
             },
+            _ => self.closures.get(closure::closure_id(callee)?)?.function,
         };
         
-        // 创建一个新的变量作用域（新的解释器实例，但共享模块加载状态）
-        let mut function_interpreter = self.create_clean_instance();
-        debug_println!("[Interpreter::call_function] 创建了新的解释器实例");
+        if function.params.is_empty() || function.params.iter().any(|param| param.rest) {
+            None
+        } else {
+            Some(function.params.len())
+        }
+    }
+    
+    /// 用函数定义创建闭包，捕获当前作用域
+    pub fn create_closure(&mut self, function: impl Into<Arc<Function>>) -> Value {
+        let scope = self.current_scope.clone();
+        self.push_closure(Closure { function: function.into(), scope, name: None })
+    }
+    
+    /// 把已定义的函数转换为函数值，以便作为参数传递
+    pub fn function_value(&mut self, function_name: &str) -> Result<Value, NjilError> {
+        let function = self.functions.get(function_name)
+            .cloned()
+            .ok_or_else(|| NjilError::ExecutionError(errortip::function::undefined_function(function_name)))?;
+        let scope = self.global_scope.clone();
+        Ok(self.push_closure(Closure { function, scope, name: Some(function_name.to_string()) }))
+    }
+    
+    fn push_closure(&mut self, closure: Closure) -> Value {
+        self.closures.insert(closure, self.pins.get_mut(), &self.global_scope)
+    }
+    
+    /// 调用闭包表中的闭包，函数帧的外层是闭包捕获的作用域
+    fn call_closure(&mut self, id: u64, args: &[Value], kwargs: &serde_json::Map<String, Value>) -> Result<Value, NjilError> {
+        let closure = self.closures.get(id)
+            .ok_or_else(|| NjilError::ExecutionError(errortip::function::invalid_closure(id)))?;
+        self.invoke(closure.display_name(), &closure.function, closure.scope.clone(), args, kwargs)
+    }
+    
+    /// 在新的函数帧中执行函数
+    fn invoke(&mut self, function_name: &str, function: &Arc<Function>, parent: ScopeRef, args: &[Value], kwargs: &serde_json::Map<String, Value>) -> Result<Value, NjilError> {
+        // 创建新的函数帧
+        let frame = self.new_scope(ScopeKind::Function, parent);
         
         // 如果有参数，则设置参数变量
         if !args.is_empty() {
             debug_println!("[Interpreter::call_function] 设置参数变量, 参数数量: {}", args.len());
+            let mut frame = frame.lock().unwrap();
             
             // 参数通过 $args 数组传递
-            function_interpreter.set_variable("$args".to_string(), Value::Array(args.to_vec()));
+            frame.variables.insert("$args".to_string(), Value::Array(args.to_vec()));
             
-            // 如果需要，可以添加命名参数支持
-            // 例如，第一个参数可以通过 $1 访问，第二个通过 $2，以此类推
+            // 第一个参数可以通过 $1 访问，第二个通过 $2，以此类推
             for (i, arg) in args.iter().enumerate() {
                 let param_name = format!("${}", i + 1);
                 debug_println!("[Interpreter::call_function] 设置参数 {}: {}", param_name, serde_json::to_string_pretty(arg).unwrap());
-                function_interpreter.set_variable(param_name, arg.clone());
+                frame.variables.insert(param_name, arg.clone());
             }
         }
         
-        // 执行函数
+        // 在函数帧中绑定命名参数并执行函数，结束后恢复调用者的作用域
         debug_println!("[Interpreter::call_function] 开始执行函数: {}", function_name);
-        let result = function_interpreter.execute_function(&function);
+        policy::check_call_depth(&self.policy, self.usage.call_depth + 1)?;
+        self.usage.call_depth += 1;
+        let namespace = function_name.rsplit_once('.')
+            .map(|(namespace, _)| namespace)
+            .filter(|namespace| self.loaded_modules.contains(*namespace))
+            .map(str::to_string);
+        let result = self.with_frame(ActiveFrame::new(function_name, namespace.clone()).with_code(Code::function(function)), |interpreter| {
+            interpreter.run_in_scope(frame, |interpreter| {
+                interpreter.bind_parameters(function_name, function, args, kwargs)?;
+                if function.is_generator() {
+                    // 生成器函数先不执行函数体，返回在函数帧中继续执行的生成器
+                    interpreter.spawn_generator(ActiveFrame::new(function_name, namespace).with_code(Code::function(function)), function)
+                } else {
+                    interpreter.execute_function(function)
+                }
+            })
+        });
+        self.usage.call_depth -= 1;
         debug_println!("[Interpreter::call_function] 函数执行结果: {:?}", result);
         result
     }
     
-    /// 获取函数定义
-    pub fn get_function(&self, function_name: &str) -> Option<&Function> {
-        // 首先在当前加载的程序中查找
-        if let Some(program) = &self.current_program {
-            if let Some(func) = program.program.functions.get(function_name) {
-                return Some(func);
+    /// 按参数声明把位置参数和命名参数绑定到当前函数帧
+    ///
+    /// 默认值在函数帧中求值，因此可以引用前面已绑定的参数。
+    fn bind_parameters(&mut self, function_name: &str, function: &Function, args: &[Value], kwargs: &serde_json::Map<String, Value>) -> Result<(), NjilError> {
+        use crate::errortip::function as tip;
+        
+        // 检查命名参数是否都有对应的声明
+        for key in kwargs.keys() {
+            if !function.params.iter().any(|param| !param.rest && &param.name == key) {
+                return Err(NjilError::ExecutionError(tip::unknown_argument(function_name, key)));
+            }
+        }
+        
+        let positional_count = function.params.iter().filter(|param| !param.rest).count();
+        let has_rest = function.params.iter().any(|param| param.rest);
+        if !function.params.is_empty() && !has_rest && args.len() > positional_count {
+            return Err(NjilError::ExecutionError(tip::too_many_arguments(function_name, positional_count, args.len())));
+        }
+        
+        let mut position = 0;
+        for param in &function.params {
+            // 剩余参数收集其余所有位置参数，类型声明作用于每个元素
+            if param.rest {
+                let rest = args.get(position..).unwrap_or_default().to_vec();
+                position = args.len();
+                for item in &rest {
+                    check_param_type(function_name, &param.name, param.param_type.as_deref(), item)?;
+                }
+                self.declare_variable(param.name.clone(), Value::Array(rest));
+                continue;
             }
+            
+            let value = if let Some(arg) = args.get(position) {
+                if kwargs.contains_key(&param.name) {
+                    return Err(NjilError::ExecutionError(tip::duplicate_argument(function_name, &param.name)));
+                }
+                arg.clone()
+            } else if let Some(arg) = kwargs.get(&param.name) {
+                arg.clone()
+            } else if let Some(default) = &param.default {
+                self.evaluate_value(default)?
+            } else {
+                return Err(NjilError::ExecutionError(tip::missing_argument(function_name, &param.name)));
+            };
+            position += 1;
+            
+            check_param_type(function_name, &param.name, param.param_type.as_deref(), &value)?;
+            self.declare_variable(param.name.clone(), value);
         }
         
-        // 如果没有找到，可以在未来实现在导入的模块中查找
-        None
+        Ok(())
+    }
+    
+    /// 创建在其他线程中执行的子解释器，在指定的作用域中运行
+    ///
+    /// 子解释器共享全局作用域、闭包表、任务表和内置模块，复制函数、常量、语句处理器、执行策略和源码位置，
+    /// 继承本次运行的截止时间、语句数、调用深度和取消标记。
+    pub(crate) fn fork(&self, scope: ScopeRef) -> Interpreter {
+        Interpreter {
+            global_scope: self.global_scope.clone(),
+            current_scope: scope,
+            constants: self.constants.clone(),
+            builtin_modules: self.builtin_modules.clone(),
+            statement_handlers: self.statement_handlers.clone(),
+            loaded_modules: self.loaded_modules.clone(),
+            current_dir: self.current_dir.clone(),
+            functions: self.functions.clone(),
+            closures: self.closures.clone(),
+            pins: RefCell::new(Pins::new(self.closures.clone())),
+            host_pins: 0,
+            constant_pins: Pins::new(self.closures.clone()),
+            tasks: self.tasks.clone(),
+            root: false,
+            yielder: None,
+            cancel: self.cancel.clone(),
+            regex_cache: HashMap::new(),
+            expr_cache: self.expr_cache.clone(),
+            policy: self.policy.clone(),
+            usage: self.usage.inherit(),
+            call_stack: Vec::new(),
+        }
+    }
+    
+    // 辅助函数：为当前函数帧中的生成器函数创建生成器
+    //
+    // 未结束的生成器数达到执行策略的上限时，先回收不再被引用的生成器。
+    fn spawn_generator(&mut self, frame: ActiveFrame, function: &Arc<Function>) -> Result<Value, NjilError> {
+        if policy::check_generators(&self.policy, self.closures.live_generators()).is_err() {
+            self.closures.collect(&self.global_scope);
+            policy::check_generators(&self.policy, self.closures.live_generators())?;
+        }
+        let child = self.fork(self.current_scope.clone());
+        let generator = Generator::spawn(child, frame, function.clone(), self.closures.generator_counter())?;
+        Ok(self.closures.insert_generator(generator, &self.current_scope, self.pins.get_mut(), &self.global_scope))
+    }
+    
+    /// 在生成器线程中执行生成器函数的函数体，没有return时返回null
+    pub(crate) fn run_generator(&mut self, frame: ActiveFrame, function: &Function) -> Result<Value, NjilError> {
+        self.with_frame(frame, |interpreter| {
+            let mut result = Ok(Value::Null);
+            let pins = interpreter.pin_mark();
+            for (i, statement) in function.body.iter().enumerate() {
+                interpreter.set_statement_index(i);
+                match statements::execute_statement(interpreter, statement).and_then(Completion::into_returned) {
+                    Ok(None) => interpreter.release_pins(pins, None),
+                    returned => {
+                        result = returned.map(Option::unwrap_or_default);
+                        break;
+                    },
+                }
+            }
+            interpreter.run_deferred(result)
+        })
+    }
+    
+    /// 恢复生成器，执行到下一个yield或函数体结束
+    pub fn resume_generator(&mut self, generator: &Value) -> Result<GeneratorStep, NjilError> {
+        self.with_generator(generator, Generator::next)
+    }
+    
+    /// 关闭生成器，执行函数体中尚未执行的finally和defer
+    pub fn close_generator(&mut self, generator: &Value) -> Result<(), NjilError> {
+        self.with_generator(generator, Generator::close)
+    }
+    
+    // 辅助函数：执行期间把生成器从闭包表中取出，其他线程（包括生成器自身）不能同时恢复它
+    fn with_generator<T, F>(&mut self, generator: &Value, f: F) -> Result<T, NjilError>
+    where
+        F: FnOnce(&mut Generator, &mut Pins, ExecutionUsage) -> Result<T, NjilError>,
+    {
+        use crate::errortip::generator as tip;
+        
+        let id = generator::generator_id(generator)
+            .ok_or_else(|| NjilError::TypeError(tip::not_a_generator(generator)))?;
+        let mut entry = match self.closures.take_generator(id) {
+            Some(entry) => entry.ok_or_else(|| NjilError::ExecutionError(tip::generator_running().to_string()))?,
+            None => return Err(NjilError::ExecutionError(tip::unknown_generator(id))),
+        };
+        let result = f(&mut entry, self.pins.get_mut(), self.usage.inherit());
+        self.closures.restore_generator(id, entry);
+        result
+    }
+    
+    /// 在生成器中交出一个值，等待调用者恢复或关闭生成器
+    pub(crate) fn yield_value(&mut self, value: Value) -> Result<Resume, NjilError> {
+        match &self.yielder {
+            Some(yielder) => {
+                let pins = self.transfer_pins(Some(&value));
+                Ok(yielder.yield_value(value, pins, &mut self.usage))
+            },
+            None => Err(NjilError::ExecutionError(errortip::generator::yield_outside_generator().to_string())),
+        }
+    }
+    
+    /// 固定值中的闭包，用于把值交给其他线程
+    pub(crate) fn transfer_pins(&self, value: Option<&Value>) -> Pins {
+        Pins::of(&self.closures, value)
+    }
+    
+    /// 在新线程中执行语句块，返回任务值
+    ///
+    /// 任务在当前作用域之下的函数作用域中执行：可以读取外层的变量，赋值只影响任务自己的作用域。
+    pub(crate) fn spawn_task(&mut self, block: &Value) -> Result<Value, NjilError> {
+        let scope = self.new_scope(ScopeKind::Function, self.current_scope.clone());
+        let child = self.fork(scope);
+        let task_block = Arc::new(block.clone());
+        let code = self.spans_of(block).map(|spans| Code::Block(task_block.clone(), spans));
+        let done = Arc::new(AtomicBool::new(false));
+        let id = self.closures.insert_task(done.clone(), self.pins.get_mut(), &self.global_scope);
+        self.tasks.spawn(child, task_block, code, id, done)?;
+        Ok(task::task_value(id))
+    }
+    
+    /// 在任务线程中执行语句块，返回return的值或最后一条语句的值
+    pub(crate) fn run_task(&mut self, block: &Value, code: Option<Code>) -> Result<Value, NjilError> {
+        self.with_frame(ActiveFrame::new("<task>", None).with_code(code), |interpreter| {
+            let result = statements::execute_statements(interpreter, statements::block_statements(block))
+                .and_then(|completion| match completion {
+                    Completion::Return(value) => Ok(value),
+                    other => other.into_value(),
+                });
+            interpreter.run_deferred(result)
+        })
+    }
+    
+    /// 等待一组任务结束，按顺序返回每个任务的结果，每个任务只能被等待一次
+    ///
+    /// 快速失败时返回第一个出错的任务的错误，并取消其余的任务。
+    pub(crate) fn await_tasks(&mut self, tasks: &[Value], errors: ErrorPolicy) -> Result<Vec<Result<Value, NjilError>>, NjilError> {
+        let ids = tasks.iter()
+            .map(|value| task::task_id(value).ok_or_else(|| NjilError::TypeError(errortip::task::not_a_task(value))))
+            .collect::<Result<Vec<u64>, NjilError>>()?;
+        self.tasks.wait(&ids, errors, self.pins.get_mut())
+    }
+    
+    /// 获取函数定义
+    pub fn get_function(&self, function_name: &str) -> Option<&Function> {
+        self.functions.get(function_name).map(|func| func.as_ref())
     }
 
+    /// 编译正则表达式，编译结果按模式和标志缓存在解释器中
+    ///
+    /// 支持的标志：i（忽略大小写）、m（多行）、s（.匹配换行）、x（忽略空白和注释）。
+    pub fn compile_regex(&mut self, pattern: &str, flags: &str) -> Result<regex::Regex, NjilError> {
+        let cache_key = format!("{}/{}", flags, pattern);
+        if let Some(regex) = self.regex_cache.get(&cache_key) {
+            return Ok(regex.clone());
+        }
+        
+        let mut builder = regex::RegexBuilder::new(pattern);
+        for flag in flags.chars() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                'm' => builder.multi_line(true),
+                's' => builder.dot_matches_new_line(true),
+                'x' => builder.ignore_whitespace(true),
+                _ => return Err(NjilError::ExecutionError(format!("不支持的正则表达式标志: {}", flag))),
+            };
+        }
+        let regex = builder.build()
+            .map_err(|e| NjilError::ExecutionError(format!("无效的正则表达式 {}: {}", pattern, e)))?;
+        
+        // 避免动态生成的模式让缓存无限增长
+        if self.regex_cache.len() >= REGEX_CACHE_LIMIT {
+            self.regex_cache.clear();
+        }
+        self.regex_cache.insert(cache_key, regex.clone());
+        Ok(regex)
+    }
+    
+    /// 解析中缀表达式，解析结果按表达式字符串缓存在解释器中
+    pub fn parse_expr(&mut self, source: &str) -> Result<Arc<Expr>, NjilError> {
+        if let Some(expr) = self.expr_cache.get(source) {
+            return Ok(expr.clone());
+        }
+        
+        let expr = Arc::new(expr::parse(source)?);
+        
+        // 避免动态生成的表达式让缓存无限增长
+        if self.expr_cache.len() >= EXPR_CACHE_LIMIT {
+            self.expr_cache.clear();
+        }
+        self.expr_cache.insert(source.to_string(), expr.clone());
+        Ok(expr)
+    }
+    
+    /// 解析并求值中缀表达式
+    pub fn evaluate_expr(&mut self, source: &str) -> Result<Value, NjilError> {
+        let expr = self.parse_expr(source)?;
+        expr::evaluate(self, &expr)
+    }
+    
     /// 检查常量是否存在
     pub fn has_constant(&self, name: &str) -> bool {
         self.constants.contains_key(name)
@@ -528,9 +1382,9 @@ impl Interpreter {
             debug_println!("[replace_var_in_string] 尝试替换变量: {}", var_name);
             
             // 获取变量值
-            let var_value = if let Some(val) = self.variables.get(&var_name) {
-                debug_println!("[replace_var_in_string] 找到变量 {} 的值: {}", var_name, serde_json::to_string(val).unwrap());
-                val.clone()
+            let var_value = if let Some(val) = self.get_variable(&var_name) {
+                debug_println!("[replace_var_in_string] 找到变量 {} 的值: {}", var_name, serde_json::to_string(&val).unwrap());
+                val
             } else if var_name.contains('.') || var_name.contains('[') {
                 // 尝试处理嵌套变量路径
                 debug_println!("[replace_var_in_string] 尝试解析嵌套变量路径: {}", var_name);
@@ -625,4 +1479,472 @@ pub fn run_file<P: AsRef<Path>>(file_path: P) -> Result<Value, NjilError> {
     let mut interpreter = Interpreter::new();
     let program = interpreter.load_file(file_path)?;
     interpreter.execute(&program)
-} 
\ No newline at end of file
+}
+
+/// 从字符串加载的源码在错误信息中显示的名称
+const STRING_SOURCE_NAME: &str = "<string>";
+
+// 辅助函数：解析NJIL源码，并保留源码位置信息
+fn parse_program(source: SourceInfo) -> Result<NjilProgram, NjilError> {
+    let mut program: NjilProgram = source.parse(|e| NjilError::ExecutionError(
+        format!("解析JSON失败: {}，请检查文件格式是否正确", e)
+    ))?;
+    program.source = Some(Arc::new(source));
+    Ok(program)
+} 
+/// 检查参数值是否符合声明的类型
+fn check_param_type(function_name: &str, param_name: &str, param_type: Option<&str>, value: &Value) -> Result<(), NjilError> {
+    use crate::errortip::function as tip;
+    
+    let Some(expected) = param_type else {
+        return Ok(());
+    };
+    let Some(matches) = type_convert::has_type(value, expected) else {
+        return Err(NjilError::ExecutionError(tip::unknown_param_type(function_name, param_name, expected)));
+    };
+    
+    if matches {
+        Ok(())
+    } else {
+        Err(NjilError::TypeError(tip::argument_type_mismatch(function_name, param_name, expected, type_convert::type_name(value))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    fn run_program(program: Value) -> (Interpreter, Result<Value, NjilError>) {
+        let program: NjilProgram = serde_json::from_value(program).unwrap();
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.execute(&program);
+        (interpreter, result)
+    }
+    
+    #[test]
+    fn test_function_writes_stay_local() {
+        let (interpreter, result) = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"var.set": {"name": "count", "value": 1}},
+                    {"function.call": "touch"},
+                    {"return": {"var": "count"}}
+                ]},
+                "touch": {"body": [
+                    {"var.set": {"name": "seen", "value": {"var": "count"}}},
+                    {"var.set": {"name": "count", "value": 100}},
+                    {"return": {"var": "seen"}}
+                ]}
+            }
+        }));
+        
+        // 函数可以读取全局变量，但未声明global的赋值只作用于函数帧
+        assert_eq!(result.unwrap(), json!(1));
+        assert!(interpreter.get_variable("seen").is_none());
+    }
+    
+    #[test]
+    fn test_global_declaration_updates_global() {
+        let (_, result) = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"var.set": {"name": "count", "value": 1}},
+                    {"function.call": "increment"},
+                    {"function.call": "increment"},
+                    {"return": {"var": "count"}}
+                ]},
+                "increment": {"body": [
+                    {"global": "count"},
+                    {"var.set": {"name": "count", "value": {"add": [{"var": "count"}, 1]}}},
+                    {"return": null}
+                ]}
+            }
+        }));
+        
+        assert_eq!(result.unwrap(), json!(3));
+    }
+    
+    #[test]
+    fn test_var_outer_creates_global() {
+        let (interpreter, result) = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"function.call": "init"},
+                    {"return": {"var": "config.debug"}}
+                ]},
+                "init": {"body": [
+                    {"var.outer": {"name": "config.debug", "value": true}},
+                    {"return": null}
+                ]}
+            }
+        }));
+        
+        assert_eq!(result.unwrap(), json!(true));
+        assert_eq!(interpreter.get_variable("config").unwrap(), json!({"debug": true}));
+    }
+    
+    #[test]
+    fn test_loop_variables_do_not_leak() {
+        let (interpreter, result) = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"var.set": {"name": "total", "value": 0}},
+                    {"loop.foreach": {
+                        "collection": [1, 2, 3],
+                        "var": "item",
+                        "index": "i",
+                        "body": [
+                            {"var.set": {"name": "total", "value": {"add": [{"var": "total"}, {"var": "item"}]}}}
+                        ]
+                    }},
+                    {"return": {"var": "total"}}
+                ]}
+            }
+        }));
+        
+        // 循环体中对外层变量的赋值保留，循环变量不泄漏
+        assert_eq!(result.unwrap(), json!(6));
+        assert!(!interpreter.has_variable("item"));
+        assert!(!interpreter.has_variable("i"));
+    }
+    
+    #[test]
+    fn test_closures_capture_their_own_iteration() {
+        // 没有被捕获的循环作用域在迭代之间复用，被闭包捕获的迭代保留自己的循环变量
+        let result = Interpreter::new().run_njis_str(r#"[
+            {"var.set": {"name": "kept", "value": []}},
+            {"loop.for": {"var": "i", "count": 6, "body": [
+                {"if": {"condition": {"expr": "i % 2 == 0"}, "then": [
+                    {"var.set": {"name": "f", "value": {"fn": {"body": [{"return": {"var": "i"}}]}}}},
+                    {"var.set": {"name": "kept", "value": {"expr": "kept + [f]"}}}
+                ]}}
+            ]}},
+            {"return": {"expr": "[kept[0](), kept[1](), kept[2]()]"}}
+        ]"#);
+        assert_eq!(result.unwrap(), json!([0, 2, 4]));
+    }
+    
+    #[test]
+    fn test_named_default_and_rest_params() {
+        let (interpreter, result) = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"var.set": {"name": "a", "value": {"function.call": {"name": "greet", "args": ["a"]}}}},
+                    {"var.set": {"name": "b", "value": {"function.call": {"name": "greet", "kwargs": {"name": "b", "greeting": "hi"}}}}},
+                    {"var.set": {"name": "c", "value": {"function.call": {"name": "greet", "args": ["c", "yo", 1, 2]}}}},
+                    {"return": null}
+                ]},
+                "greet": {
+                    "params": ["name", {"name": "greeting", "default": "hello"}, "...extra"],
+                    "body": [
+                        {"return": {"string.concat": [{"var": "greeting"}, " ", {"var": "name"}, " ", {"var": "extra"}]}}
+                    ]
+                }
+            }
+        }));
+        
+        result.unwrap();
+        assert_eq!(interpreter.get_variable("a").unwrap(), json!("hello a "));
+        assert_eq!(interpreter.get_variable("b").unwrap(), json!("hi b "));
+        assert_eq!(interpreter.get_variable("c").unwrap(), json!("yo c 1, 2"));
+    }
+    
+    #[test]
+    fn test_param_errors() {
+        let call = |call: Value| {
+            run_program(json!({
+                "program": {
+                    "main": {"body": [{"return": call}]},
+                    "square": {
+                        "params": [{"name": "x", "type": "number"}],
+                        "body": [{"return": {"var": "x"}}]
+                    }
+                }
+            })).1.unwrap_err().to_string()
+        };
+        
+        assert!(call(json!({"function.call": "square"})).contains("缺少参数: x"));
+        assert!(call(json!({"function.call": {"name": "square", "kwargs": {"y": 1}}})).contains("没有名为 y 的参数"));
+        assert!(call(json!({"function.call": {"name": "square", "args": [1], "kwargs": {"x": 1}}})).contains("重复提供"));
+        assert!(call(json!({"function.call": {"name": "square", "args": [1, 2]}})).contains("最多接受 1 个参数"));
+        assert!(call(json!({"function.call": {"name": "square", "args": ["1"]}})).contains("需要 number 类型"));
+    }
+    
+    #[test]
+    fn test_builtin_imports_are_per_interpreter() {
+        let (with_import, result) = run_program(json!({
+            "import": ["!array"],
+            "program": {"main": {"body": [{"return": {"array.chunk": {"array": [1, 2], "size": 1}}}]}}
+        }));
+        assert_eq!(result.unwrap(), json!([[1], [2]]));
+        assert!(with_import.statement_handlers.get("array.chunk").is_some());
+        
+        // 另一个解释器没有导入!array，不能使用其中的指令
+        let (without_import, result) = run_program(json!({
+            "program": {"main": {"body": [{"return": {"array.chunk": {"array": [1, 2], "size": 1}}}]}}
+        }));
+        assert!(result.unwrap_err().to_string().contains("array.chunk"));
+        assert!(without_import.statement_handlers.get("array.chunk").is_none());
+    }
+    
+    #[test]
+    fn test_host_api_reuses_interpreter() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Summary {
+            total: f64,
+            label: String,
+        }
+        
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("prefix", "合计").unwrap();
+        interpreter.load_njil_str(r#"{
+            "program": {
+                "summarize": {
+                    "params": ["items", {"name": "scale", "default": 1}],
+                    "body": [
+                        {"var.set": {"name": "total", "value": 0}},
+                        {"loop.foreach": {"collection": {"var": "items"}, "var": "n", "body": [
+                            {"var.set": {"name": "total", "value": {"math.add": [{"var": "total"}, {"var": "n"}]}}}
+                        ]}},
+                        {"var.set": {"name": "total", "value": {"math.multiply": [{"var": "total"}, {"var": "scale"}]}}},
+                        {"return": {"json.new": {
+                            "total": {"var": "total"},
+                            "label": {"string.concat": [{"var": "prefix"}, ": ", {"var": "total"}]}
+                        }}}
+                    ]
+                }
+            }
+        }"#).unwrap();
+        
+        // 位置参数与命名参数
+        let summary: Summary = interpreter.call("summarize", &(vec![1, 2, 3],)).unwrap();
+        assert_eq!(summary, Summary { total: 6.0, label: "合计: 6".to_string() });
+        let summary: Summary = interpreter.call_named("summarize", &json!({"items": [4], "scale": 10})).unwrap();
+        assert_eq!(summary.total, 40.0);
+        // 调用方式由方法决定，不再根据参数的形状猜测
+        assert!(matches!(interpreter.call::<_, Summary>("summarize", &json!({"items": [4]})), Err(NjilError::TypeError(_))));
+        assert!(matches!(interpreter.call_named::<_, Summary>("summarize", &(vec![4],)), Err(NjilError::TypeError(_))));
+        
+        // NJIS脚本与之前加载的函数和全局变量共享同一个解释器
+        let total: f64 = native::from_value(interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "count", "value": 2}},
+            {"return": {"json.get": {"object": {"function.call": {"name": "summarize", "args": [[5, 5]]}}, "key": "total"}}}
+        ]"#).unwrap()).unwrap();
+        assert_eq!(total, 10.0);
+        assert_eq!(interpreter.get_global::<i64>("count").unwrap(), 2);
+        
+        let result = interpreter.run_njil_str(r#"{"program": {"main": {"body": [{"return": {"var": "count"}}]}}}"#);
+        assert_eq!(result.unwrap(), json!(2));
+        assert!(interpreter.call::<_, Summary>("missing", &()).is_err());
+        assert!(matches!(interpreter.get_global::<i64>("missing"), Err(NjilError::UndefinedVariable(name)) if name == "missing"));
+    }
+    
+    #[test]
+    fn test_execution_policy_limits() {
+        use std::time::Duration;
+        
+        let run_limited = |policy: ExecutionPolicy, source: &str| {
+            let mut interpreter = Interpreter::new();
+            interpreter.set_policy(policy);
+            interpreter.run_njis_str(source)
+        };
+        let endless = r#"[{"loop.while": {"condition": true, "body": []}}]"#;
+        
+        let result = run_limited(ExecutionPolicy::new().max_statements(1000), endless);
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("语句数")));
+        
+        let result = run_limited(ExecutionPolicy::new().timeout(Duration::from_millis(50)), endless);
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("执行时间")));
+        
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(ExecutionPolicy::new().max_call_depth(20));
+        interpreter.load_njil_str(r#"{"program": {"down": {"params": ["n"], "body": [
+            {"return": {"function.call": {"name": "down", "args": [{"var": "n"}]}}}
+        ]}}}"#).unwrap();
+        let result = interpreter.call::<_, Value>("down", &(1,));
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("调用深度")));
+        
+        // 调用深度在出错后恢复，同一个解释器可以继续使用
+        let result = interpreter.run_njis_str(r#"[{"return": "ok"}]"#);
+        assert_eq!(result.unwrap(), json!("ok"));
+        
+        // 超出限制的错误可以被脚本捕获
+        let result = run_limited(ExecutionPolicy::new().max_string_length(8), r#"[
+            {"var.set": {"name": "s", "value": "ab"}},
+            {"try": {
+                "try": [{"loop.while": {"condition": true, "body": [
+                    {"var.set": {"name": "s", "value": {"string.concat": [{"var": "s"}, {"var": "s"}]}}}
+                ]}}],
+                "catch": {"var": "e", "body": [{"return": {"var": "s"}}]}
+            }}
+        ]"#);
+        assert_eq!(result.unwrap(), json!("abababab"));
+        
+        // 表达式内部产生的值在产生时检查，即使最终结果只是嵌套在数组中
+        let result = run_limited(ExecutionPolicy::new().max_string_length(4), r#"[
+            {"var.set": {"name": "s", "value": "abc"}},
+            {"return": {"expr": "[1, s + s]"}}
+        ]"#);
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("字符串长度")));
+    }
+    
+    #[test]
+    fn test_execution_policy_capabilities() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(ExecutionPolicy::new().deny_module("system").deny_instruction("io.*"));
+        
+        // NJIS自动导入时跳过被禁用的模块，被禁用的指令返回独立的错误
+        let result = interpreter.run_njis_str(r#"[{"system.process.pid": null}]"#);
+        assert!(result.unwrap_err().to_string().contains("system.process.pid"));
+        assert!(interpreter.statement_handlers.get("system.process.pid").is_none());
+        let result = interpreter.run_njis_str(r#"[{"io.readFile": "/etc/hostname"}]"#);
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("io.readFile")));
+        assert_eq!(interpreter.run_njis_str(r#"[{"array.unique": [1, 1]}]"#).unwrap(), json!([1]));
+        
+        let result = interpreter.run_njil_str(r#"{"import": ["!system"], "program": {"main": {"body": [{"return": null}]}}}"#);
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("system")));
+    }
+    
+    #[test]
+    fn test_execution_policy_checks_aliases() {
+        let denied = |result: Result<Value, NjilError>, name: &str| {
+            matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains(name))
+        };
+        
+        // 别名按处理器的主名称检查
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(ExecutionPolicy::new()
+            .deny_instruction("system.process.*")
+            .deny_instruction("system.fs.*")
+            .deny_instruction("system.env.*"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"process.exec": "echo hi"}]"#), "process.exec"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"fs.exists": "."}]"#), "fs.exists"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"env.get": "HOME"}]"#), "env.get"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"system.env.get": "HOME"}]"#), "system.env.get"));
+        assert_eq!(interpreter.run_njis_str(r#"[{"string.concat": ["a", "b"]}]"#).unwrap(), json!("ab"));
+        
+        // 模块导入之后才设置的策略同样禁止其中的指令，包括别名
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.run_njis_str(r#"[{"fs.exists": "."}]"#).unwrap(), json!(true));
+        interpreter.set_policy(ExecutionPolicy::sandboxed());
+        assert!(denied(interpreter.run_njis_str(r#"[{"fs.exists": "."}]"#), "fs.exists"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"env.get": "HOME"}]"#), "env.get"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"process.exec": "echo hi"}]"#), "process.exec"));
+        assert!(denied(interpreter.run_njis_str(r#"[{"shell.write": "hi"}]"#), "shell.write"));
+        
+        // 预设策略限制了调用深度，无限递归报告超出限制而不是耗尽栈
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "down", "value": {"fn": {"body": [{"return": {"function.call": "down"}}]}}}},
+            {"function.call": "down"}
+        ]"#);
+        assert!(matches!(result.map_err(NjilError::into_root), Err(NjilError::LimitExceeded(msg)) if msg.contains("调用深度")));
+        interpreter.set_policy(ExecutionPolicy::new());
+        assert_eq!(interpreter.run_njis_str(r#"[{"fs.exists": "."}]"#).unwrap(), json!(true));
+    }
+    
+    #[test]
+    fn test_errors_report_source_location() {
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.run_njil_str(r#"{
+    // 注释不影响行号
+    "program": {
+        "main": {"body": [
+            {"var.set": {"name": "f", "value": {"fn": {"body": [
+                /* 闭包中的语句 */ {"prnt": 1}
+            ]}}}},
+            {"return": {"function.call": "f"}}
+        ]}
+    }
+}"#).unwrap_err();
+        
+        let location = error.location().unwrap();
+        assert_eq!(location.to_string(), "<string>:6:30");
+        assert!(matches!(error.root(), NjilError::ExecutionError(msg) if msg.contains("prnt")));
+        assert!(error.to_string().contains("6 |                 /* 闭包中的语句 */ {\"prnt\": 1}"));
+        
+        // defer和任务的语句块是语句的副本，其中的语句同样报告源码位置
+        let error = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "f", "value": {"fn": {"body": [{"defer": [{"prnt": 1}]}]}}}},
+            {"function.call": "f"}
+        ]"#).unwrap_err();
+        assert_eq!(error.location().unwrap().to_string(), "<string>:2:76");
+        let error = interpreter.run_njis_str(r#"[
+            {"task.await": {"task.spawn": [{"print": ""},
+                {"prnt": 1}]}}
+        ]"#).unwrap_err();
+        assert_eq!(error.location().unwrap().to_string(), "<string>:3:17");
+        
+        // 被try捕获的错误只保留信息和类型，未定义变量在条件中仍然视为假
+        let result = interpreter.run_njis_str(r#"[
+            {"if": {"condition": {"var": "missing"}, "then": [{"return": 1}], "else": [{"return": 2}]}}
+        ]"#);
+        assert_eq!(result.unwrap(), json!(2));
+        let result = interpreter.run_njis_str(r#"[
+            {"try": {"try": [{"prnt": 1}], "catch": {"var": "e", "body": [{"return": {"var": "e"}}]}}}
+        ]"#).unwrap();
+        assert_eq!(result["type"], json!("execution"));
+        assert_eq!(result["message"], json!("未知的指令: prnt"));
+    }
+    
+    #[test]
+    fn test_errors_carry_call_stack() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load_njil_str(r#"{"program": {
+    "outer": {"body": [
+        {"print": ""},
+        {"return": {"function.call": "inner"}}
+    ]},
+    "inner": {"body": [{"return": {"throw": "失败"}}]}
+}}"#).unwrap();
+        
+        let error = interpreter.call::<_, Value>("outer", &()).unwrap_err();
+        let stack: Vec<String> = error.stack().iter().map(|frame| frame.to_string()).collect();
+        assert_eq!(stack, vec!["inner (<string>:6:35)", "outer (<string>:4:20)"]);
+        assert_eq!(error.stack()[1].statement, Some(1));
+        assert!(matches!(error.root(), NjilError::ThrowException(value) if value == "失败"));
+        
+        // catch块可以通过stack字段取得调用栈
+        let result = interpreter.run_njis_str(r#"[
+            {"try": {
+                "try": [{"function.call": "outer"}],
+                "catch": {"var": "e", "stack": "trace", "body": [{"return": {"var": "trace"}}]}
+            }}
+        ]"#).unwrap();
+        let functions: Vec<&Value> = result.as_array().unwrap().iter().map(|frame| &frame["function"]).collect();
+        assert_eq!(functions, vec!["inner", "outer", "<script>"]);
+        assert_eq!(result[2]["location"], json!("<string>:3:25"));
+    }
+    
+    #[test]
+    fn test_control_flow_is_uniform() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load_njil_str(r#"{"program": {
+    "find": {"params": ["items", "target"], "body": [
+        {"loop.foreach": {"collection": {"var": "items"}, "var": "item", "index": "i", "body": [
+            {"try": {
+                "try": [
+                    {"if": {"condition": {"math.compare": {"left": {"var": "item"}, "op": "==", "right": {"var": "target"}}}, "then": [{"return": {"var": "i"}}]}},
+                    {"match": {"value": {"var": "item"}, "cases": [{"pattern": 0, "body": [{"loop.continue": null}]}]}},
+                    {"throw": "不匹配"}
+                ],
+                "catch": {"body": []}
+            }}
+        ]}},
+        {"return": -1}
+    ]},
+    "escape": {"body": [{"loop.break": null}]}
+}}"#).unwrap();
+        
+        // try、match和if中的return与loop.continue都交给函数和循环处理，不会被catch捕获
+        assert_eq!(interpreter.call::<_, i64>("find", &(vec![0, 3, 5], 5)).unwrap(), 2);
+        assert_eq!(interpreter.call::<_, i64>("find", &(vec![0, 3], 7)).unwrap(), -1);
+        
+        // loop.break不能离开函数，return不能用在表达式中
+        let error = interpreter.call::<_, Value>("escape", &()).unwrap_err();
+        assert!(error.message().contains("loop.break"));
+        let error = interpreter.run_njis_str(r#"[{"var.set": {"name": "x", "value": {"return": 1}}}]"#).unwrap_err();
+        assert!(error.message().contains("return"));
+        assert_eq!(interpreter.run_njis_str(r#"[{"return": 1}, {"throw": "不会执行"}]"#).unwrap(), json!(1));
+    }
+}
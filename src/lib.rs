@@ -8,6 +8,16 @@ pub mod builtin;
 pub mod utils;
 pub mod preprocessor;
 pub mod module;
+pub mod scope;
+pub mod closure;
+pub mod generator;
+pub mod task;
+pub mod native;
+pub mod policy;
+pub mod source;
+pub mod stack;
+pub mod expr;
+pub mod completion;
 
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -61,5 +71,9 @@ pub fn run_file<P: AsRef<Path>>(file_path: P) -> Result<serde_json::Value, error
 
 // 重新导出常用类型
 pub use crate::error::NjilError;
+pub use crate::completion::Completion;
+pub use crate::generator::GeneratorStep;
 pub use crate::types::{NjilProgram, Program, Function, NjimModule, ModuleExports};
-pub use crate::interpreter::Interpreter; 
\ No newline at end of file
+pub use crate::interpreter::Interpreter;
+pub use crate::native::NativeModule;
+pub use crate::policy::ExecutionPolicy;
\ No newline at end of file
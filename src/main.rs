@@ -1,4 +1,5 @@
-use neo_jilang::{run_file, NjilError, set_debug_mode};
+use neo_jilang::{run_file, set_debug_mode};
+use neo_jilang::stack::format_stack;
 use std::env;
 use std::process;
 
@@ -38,15 +39,11 @@ fn main() {
             println!("程序执行成功，返回值: {}", result);
         }
         Err(err) => {
-            match err {
-                NjilError::ReturnValue(value) => {
-                    println!("程序执行成功，返回值: {}", value);
-                }
-                _ => {
-                    eprintln!("错误: {}", err);
-                    process::exit(1);
-                }
+            eprintln!("错误: {}", err);
+            if !err.stack().is_empty() {
+                eprintln!("{}", format_stack(err.stack()));
             }
+            process::exit(1);
         }
     }
 }
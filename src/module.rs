@@ -5,7 +5,7 @@ use serde_json::Value;
 use crate::error::NjilError;
 use crate::types::{NjimModule, Function};
 use crate::debug_println;
-use crate::preprocessor::Preprocessor;
+use crate::source::SourceInfo;
 
 /// 模块加载器，负责加载和管理NJIM模块
 pub struct ModuleLoader {
@@ -16,6 +16,12 @@ pub struct ModuleLoader {
     search_paths: Vec<PathBuf>,
 }
 
+impl Default for ModuleLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ModuleLoader {
     /// 创建一个新的模块加载器
     pub fn new() -> Self {
@@ -44,18 +50,15 @@ impl ModuleLoader {
         
         debug_println!("加载模块: {}", file_path_key);
         
-        // 使用预处理器处理文件内容，移除注释
-        let processed_content = Preprocessor::preprocess_file(&module_path)?;
+        // 使用预处理器处理文件内容，移除注释，并记录语句在源码中的位置
+        let content = std::fs::read_to_string(&module_path)?;
+        let source = SourceInfo::new(module_path.display().to_string(), &content)?;
         
         // 解析模块内容
-        let module: NjimModule = match serde_json::from_str(&processed_content) {
-            Ok(m) => m,
-            Err(e) => {
-                return Err(NjilError::ExecutionError(
-                    format!("解析NJIM文件失败: {}，请检查文件格式是否正确", e)
-                ));
-            }
-        };
+        let mut module: NjimModule = source.parse(|e| NjilError::ExecutionError(
+            format!("解析NJIM文件失败: {}，请检查文件格式是否正确", e)
+        ))?;
+        module.source = Some(Arc::new(source));
         
         // 获取模块名称和命名空间
         let module_name = module.module.clone();
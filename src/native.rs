@@ -0,0 +1,173 @@
+use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use crate::builtin::BuiltinModule;
+use crate::error::NjilError;
+use crate::errortip;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+
+/// 宿主程序注册的原生指令函数，参数为已求值的指令参数
+pub type NativeFn = dyn Fn(&mut Interpreter, Value) -> Result<Value, NjilError> + Send + Sync;
+
+/// 把JSON值转换为Rust类型
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, NjilError> {
+    serde_json::from_value(value)
+        .map_err(|e| NjilError::TypeError(errortip::function::from_value_failed(&e.to_string())))
+}
+
+/// 把Rust类型转换为JSON值
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, NjilError> {
+    serde_json::to_value(value)
+        .map_err(|e| NjilError::TypeError(errortip::function::to_value_failed(&e.to_string())))
+}
+
+/// 把接收Rust类型参数的函数包装为原生指令函数
+///
+/// 指令参数通过serde转换为`A`，返回值`R`再转换回JSON值。
+pub fn typed<A, R, F>(func: F) -> Arc<NativeFn>
+where
+    A: DeserializeOwned,
+    R: Serialize,
+    F: Fn(A) -> Result<R, NjilError> + Send + Sync + 'static,
+{
+    Arc::new(move |_: &mut Interpreter, args: Value| to_value(&func(from_value(args)?)?))
+}
+
+/// 由宿主程序的闭包实现的语句处理器
+pub struct NativeHandler {
+    name: String,
+    func: Arc<NativeFn>,
+}
+
+impl NativeHandler {
+    pub fn new(name: impl Into<String>, func: Arc<NativeFn>) -> Self {
+        Self { name: name.into(), func }
+    }
+}
+
+impl StatementHandler for NativeHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let args = interpreter.evaluate_deep(value)?;
+        (self.func)(interpreter, args)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 由宿主程序的闭包组成的模块，注册后可以在脚本中通过 `"!模块名"` 导入
+///
+/// ```
+/// use neo_jilang::native::{self, NativeModule};
+///
+/// let module = NativeModule::new("greeter")
+///     .instruction("greeter.hello", |_, args| Ok(format!("你好, {}", args).into()))
+///     .typed_instruction("greeter.twice", |n: i64| Ok(n * 2));
+/// ```
+pub struct NativeModule {
+    name: String,
+    handlers: Vec<Arc<dyn StatementHandler>>,
+}
+
+impl NativeModule {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), handlers: Vec::new() }
+    }
+
+    /// 添加一个指令，闭包收到解释器和已求值的参数
+    pub fn instruction<F>(mut self, name: impl Into<String>, func: F) -> Self
+    where
+        F: Fn(&mut Interpreter, Value) -> Result<Value, NjilError> + Send + Sync + 'static,
+    {
+        self.handlers.push(Arc::new(NativeHandler::new(name, Arc::new(func))));
+        self
+    }
+
+    /// 添加一个参数和返回值通过serde自动转换的指令
+    pub fn typed_instruction<A, R, F>(mut self, name: impl Into<String>, func: F) -> Self
+    where
+        A: DeserializeOwned,
+        R: Serialize,
+        F: Fn(A) -> Result<R, NjilError> + Send + Sync + 'static,
+    {
+        self.handlers.push(Arc::new(NativeHandler::new(name, typed(func))));
+        self
+    }
+}
+
+impl BuiltinModule for NativeModule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_handlers(&self) -> Vec<&'static dyn StatementHandler> {
+        Vec::new()
+    }
+
+    fn get_shared_handlers(&self) -> Vec<Arc<dyn StatementHandler>> {
+        self.handlers.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NjilProgram;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn run(interpreter: &mut Interpreter, program: Value) -> Result<Value, NjilError> {
+        let program: NjilProgram = serde_json::from_value(program).unwrap();
+        interpreter.execute(&program)
+    }
+
+    #[test]
+    fn test_register_instruction_with_host_state() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut interpreter = Interpreter::new();
+
+        let sink = log.clone();
+        interpreter.register_instruction("host.log", move |_, args| {
+            sink.lock().unwrap().push(args);
+            Ok(Value::Null)
+        });
+
+        run(&mut interpreter, json!({"program": {"main": {"body": [
+            {"var.set": {"name": "user", "value": "alice"}},
+            {"host.log": {"user": {"var": "user"}, "tags": [{"var": "user"}, 1]}},
+            {"return": null}
+        ]}}})).unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec![json!({"user": "alice", "tags": ["alice", 1]})]);
+    }
+
+    #[test]
+    fn test_typed_instruction_and_module() {
+        #[derive(Deserialize)]
+        struct Range {
+            start: i64,
+            end: i64,
+        }
+
+        let mut interpreter = Interpreter::new();
+        interpreter.register_module(Box::new(
+            NativeModule::new("ranges").typed_instruction("ranges.sum", |range: Range| Ok((range.start..range.end).sum::<i64>()))
+        ));
+
+        let program = json!({
+            "import": ["!ranges"],
+            "program": {"main": {"body": [{"return": {"ranges.sum": {"start": 1, "end": 5}}}]}}
+        });
+        assert_eq!(run(&mut interpreter, program).unwrap(), json!(10));
+
+        let bad = json!({
+            "import": ["!ranges"],
+            "program": {"main": {"body": [{"return": {"ranges.sum": {"start": "x"}}}]}}
+        });
+        assert!(run(&mut interpreter, bad).unwrap_err().to_string().contains("参数类型转换失败"));
+    }
+}
@@ -1,95 +1,36 @@
-use std::fs;
 use std::path::Path;
 use serde_json::Value;
 use crate::error::NjilError;
+use crate::errortip;
 use crate::interpreter::Interpreter;
-use crate::preprocessor::Preprocessor;
+use crate::source::SourceInfo;
 
-/// NJIS (NeoJi Script) 文件处理模块
-/// NJIS 是NJIL的简化版本，使用JSON数组直接表示语句序列
+// NJIS (NeoJi Script) 文件处理模块
+// NJIS 是NJIL的简化版本，使用JSON数组直接表示语句序列
 
 /// 从.njis文件加载并执行脚本
 pub fn run_njis_file<P: AsRef<Path>>(file_path: P) -> Result<Value, NjilError> {
-    // 使用预处理器处理文件内容，移除注释
-    let processed_content = Preprocessor::preprocess_file(file_path.as_ref())?;
+    // 使用预处理器处理文件内容，移除注释，并记录语句在源码中的位置
+    let path = file_path.as_ref();
+    let content = std::fs::read_to_string(path)?;
+    let source = SourceInfo::new(path.display().to_string(), &content)?;
+    let statements = parse_njis(&source, "NJIS文件")?;
     
-    // 解析NJIS文件内容
-    let statements: Value = serde_json::from_str(&processed_content)
-        .map_err(|e| NjilError::ParseError(e))?;
-    
-    // 验证NJIS结构（必须是数组）
-    if !statements.is_array() {
-        return Err(NjilError::ParseStringError("NJIS文件必须是JSON数组".to_string()));
-    }
-    
-    // 创建解释器并执行语句
-    let mut interpreter = Interpreter::new();
-    
-    // NJIS不支持import，所以自动导入所有内置模块
-    interpreter.import_all_builtin_modules()?;
-    
-    let mut result = Value::Null;
-    
-    if let Value::Array(statements) = statements {
-        for statement in statements.iter() {
-            match interpreter.evaluate_value(statement) {
-                Ok(value) => {
-                    result = value;
-                },
-                Err(e) => {
-                    return Err(e);
-                }
-            }
-            
-            // 如果遇到返回语句，提前结束执行
-            if interpreter.is_returning() {
-                break;
-            }
-        }
-    }
-    
-    Ok(result)
+    Interpreter::new().run_njis_source(&source, statements)
 }
 
 /// 从字符串执行NJIS脚本
 pub fn run_njis_str(content: &str) -> Result<Value, NjilError> {
-    // 使用预处理器处理内容，移除注释
-    let processed_content = Preprocessor::preprocess_content(content)?;
-    
-    // 解析NJIS内容
-    let statements: Value = serde_json::from_str(&processed_content)
-        .map_err(|e| NjilError::ParseError(e))?;
+    Interpreter::new().run_njis_str(content)
+}
+
+/// 解析NJIS源码，kind用于错误信息中说明内容来源
+pub(crate) fn parse_njis(source: &SourceInfo, kind: &str) -> Result<Vec<Value>, NjilError> {
+    let statements: Value = source.parse(NjilError::ParseError)?;
     
     // 验证NJIS结构（必须是数组）
-    if !statements.is_array() {
-        return Err(NjilError::ParseStringError("NJIS字符串必须是JSON数组".to_string()));
+    match statements {
+        Value::Array(statements) => Ok(statements),
+        _ => Err(NjilError::ParseStringError(errortip::script_requires_array(kind))),
     }
-    
-    // 创建解释器并执行语句
-    let mut interpreter = Interpreter::new();
-    
-    // NJIS不支持import，所以自动导入所有内置模块
-    interpreter.import_all_builtin_modules()?;
-    
-    let mut result = Value::Null;
-    
-    if let Value::Array(statements) = statements {
-        for statement in statements.iter() {
-            match interpreter.evaluate_value(statement) {
-                Ok(value) => {
-                    result = value;
-                },
-                Err(e) => {
-                    return Err(e);
-                }
-            }
-            
-            // 如果遇到返回语句，提前结束执行
-            if interpreter.is_returning() {
-                break;
-            }
-        }
-    }
-    
-    Ok(result)
-} 
\ No newline at end of file
+}
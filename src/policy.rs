@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use crate::error::NjilError;
+use crate::errortip::limit as tip;
+
+/// 执行策略，限制脚本可以使用的资源和模块，用于运行不可信的脚本
+///
+/// 默认不做任何限制。超出限制时返回 `NjilError::LimitExceeded`。
+///
+/// ```
+/// use std::time::Duration;
+/// use neo_jilang::{ExecutionPolicy, Interpreter};
+///
+/// let mut interpreter = Interpreter::new();
+/// interpreter.set_policy(
+///     ExecutionPolicy::new()
+///         .max_statements(100_000)
+///         .timeout(Duration::from_secs(1))
+///         .deny_module("system")
+///         .deny_instruction("io.*"),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionPolicy {
+    max_statements: Option<u64>,
+    timeout: Option<Duration>,
+    max_call_depth: Option<usize>,
+    max_string_length: Option<usize>,
+    max_array_length: Option<usize>,
+    max_generators: Option<usize>,
+    max_tasks: Option<usize>,
+    denied_modules: HashSet<String>,
+    denied_instructions: HashSet<String>,
+}
+
+impl ExecutionPolicy {
+    /// 创建一个不做任何限制的策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 适合运行不可信脚本的预设策略
+    ///
+    /// 禁用 `!system`、`!io` 和 `!shell` 模块，并限制执行时间、语句数、调用深度（64层）、值的大小、生成器数和任务数。
+    pub fn sandboxed() -> Self {
+        Self::new()
+            .max_statements(1_000_000)
+            .timeout(Duration::from_secs(5))
+            .max_call_depth(64)
+            .max_string_length(1 << 20)
+            .max_array_length(100_000)
+            .max_generators(64)
+            .max_tasks(16)
+            .deny_module("system")
+            .deny_module("io")
+            .deny_module("shell")
+    }
+
+    /// 一次运行中最多执行的语句数
+    pub fn max_statements(mut self, limit: u64) -> Self {
+        self.max_statements = Some(limit);
+        self
+    }
+
+    /// 一次运行的最长时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 函数调用的最大嵌套深度
+    pub fn max_call_depth(mut self, limit: usize) -> Self {
+        self.max_call_depth = Some(limit);
+        self
+    }
+
+    /// 语句产生的字符串的最大长度（按字节计算）
+    pub fn max_string_length(mut self, limit: usize) -> Self {
+        self.max_string_length = Some(limit);
+        self
+    }
+
+    /// 语句产生的数组的最大长度
+    pub fn max_array_length(mut self, limit: usize) -> Self {
+        self.max_array_length = Some(limit);
+        self
+    }
+
+    /// 同时存在的未结束的生成器的最大数量，每个未结束的生成器占用一个线程
+    ///
+    /// 不再被引用的生成器会被回收，不计入这个数量。
+    pub fn max_generators(mut self, limit: usize) -> Self {
+        self.max_generators = Some(limit);
+        self
+    }
+
+    /// 同时执行的任务的最大数量，每个任务（包括parallel中的每个语句块）占用一个线程
+    pub fn max_tasks(mut self, limit: usize) -> Self {
+        self.max_tasks = Some(limit);
+        self
+    }
+
+    /// 禁用一个内置模块，禁止导入并禁止调用其中的指令
+    pub fn deny_module(mut self, name: impl Into<String>) -> Self {
+        self.denied_modules.insert(name.into());
+        self
+    }
+
+    /// 禁用一个指令，以 `.*` 结尾时禁用所有以该前缀开头的指令，如 `system.process.*`
+    pub fn deny_instruction(mut self, name: impl Into<String>) -> Self {
+        self.denied_instructions.insert(name.into());
+        self
+    }
+
+    /// 检查模块是否允许导入
+    pub fn is_module_allowed(&self, name: &str) -> bool {
+        !self.denied_modules.contains(name)
+    }
+
+    /// 检查指令是否允许执行
+    pub fn is_instruction_allowed(&self, name: &str) -> bool {
+        if self.denied_modules.is_empty() && self.denied_instructions.is_empty() {
+            return true;
+        }
+        if self.denied_instructions.contains(name) {
+            return false;
+        }
+        let mut prefix = name;
+        while let Some(index) = prefix.rfind('.') {
+            prefix = &prefix[..index];
+            if self.denied_instructions.contains(&format!("{}.*", prefix)) {
+                return false;
+            }
+            if !prefix.contains('.') && self.denied_modules.contains(prefix) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub(crate) fn restricts_instructions(&self) -> bool {
+        !self.denied_modules.is_empty() || !self.denied_instructions.is_empty()
+    }
+
+    pub(crate) fn limits_statements(&self) -> bool {
+        self.max_statements.is_some() || self.timeout.is_some()
+    }
+}
+
+/// 一次运行中已经使用的资源
+///
+/// 语句数由本次运行中的生成器和并行任务共享，限制的是整次运行执行的语句总数。
+#[derive(Debug, Default)]
+pub(crate) struct ExecutionUsage {
+    pub(crate) statements: Arc<AtomicU64>,
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) call_depth: usize,
+    pub(crate) active_runs: usize,
+}
+
+impl ExecutionUsage {
+    /// 开始一次新的运行，重置计数器
+    pub(crate) fn start(&mut self, policy: &ExecutionPolicy) {
+        self.statements = Arc::new(AtomicU64::new(0));
+        self.deadline = policy.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    /// 在之后的运行中继续执行（恢复生成器）：沿用调用者本次运行的语句数和截止时间
+    ///
+    /// 调用深度属于子解释器自己的线程，保持不变。
+    pub(crate) fn continue_run(&mut self, caller: ExecutionUsage) {
+        self.statements = caller.statements;
+        self.deadline = caller.deadline;
+    }
+
+    /// 子解释器的资源使用情况：沿用本次运行的截止时间和调用深度，共享语句数
+    pub(crate) fn inherit(&self) -> Self {
+        Self {
+            statements: self.statements.clone(),
+            deadline: self.deadline,
+            call_depth: self.call_depth,
+            active_runs: 1,
+        }
+    }
+}
+
+/// 检查一条语句是否超出语句数和时间限制
+pub(crate) fn check_step(policy: &ExecutionPolicy, usage: &mut ExecutionUsage) -> Result<(), NjilError> {
+    let statements = usage.statements.fetch_add(1, Ordering::Relaxed) + 1;
+    if let Some(limit) = policy.max_statements {
+        if statements > limit {
+            return Err(NjilError::LimitExceeded(tip::too_many_statements(limit)));
+        }
+    }
+    if let (Some(deadline), Some(timeout)) = (usage.deadline, policy.timeout) {
+        if Instant::now() >= deadline {
+            return Err(NjilError::LimitExceeded(tip::timeout(timeout)));
+        }
+    }
+    Ok(())
+}
+
+/// 检查语句产生的值是否超出大小限制：字符串的长度和数组的元素数
+///
+/// 每个字符串和数组都是某条语句的结果，创建时已经检查过，所以不遍历数组和对象中嵌套的值，
+/// 在循环中逐步构造数组时每次检查的开销与数组大小无关。
+pub(crate) fn check_value_size(policy: &ExecutionPolicy, value: &Value) -> Result<(), NjilError> {
+    match value {
+        Value::String(s) => check_string_length(policy, s),
+        Value::Array(items) => match policy.max_array_length {
+            Some(limit) if items.len() > limit => Err(NjilError::LimitExceeded(tip::array_too_long(items.len(), limit))),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+// 辅助函数：检查字符串长度
+fn check_string_length(policy: &ExecutionPolicy, s: &str) -> Result<(), NjilError> {
+    match policy.max_string_length {
+        Some(limit) if s.len() > limit => Err(NjilError::LimitExceeded(tip::string_too_long(s.len(), limit))),
+        _ => Ok(()),
+    }
+}
+
+/// 检查调用深度
+pub(crate) fn check_call_depth(policy: &ExecutionPolicy, depth: usize) -> Result<(), NjilError> {
+    match policy.max_call_depth {
+        Some(limit) if depth > limit => Err(NjilError::LimitExceeded(tip::call_too_deep(limit))),
+        _ => Ok(()),
+    }
+}
+
+/// 检查是否还能创建生成器，live是未结束的生成器数
+pub(crate) fn check_generators(policy: &ExecutionPolicy, live: usize) -> Result<(), NjilError> {
+    match policy.max_generators {
+        Some(limit) if live >= limit => Err(NjilError::LimitExceeded(tip::too_many_generators(limit))),
+        _ => Ok(()),
+    }
+}
+
+/// 检查是否还能开始执行任务，running是执行中的任务数
+pub(crate) fn check_tasks(policy: &ExecutionPolicy, running: usize) -> Result<(), NjilError> {
+    match policy.max_tasks {
+        Some(limit) if running >= limit => Err(NjilError::LimitExceeded(tip::too_many_tasks(limit))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_instruction_patterns() {
+        let policy = ExecutionPolicy::new()
+            .deny_module("io")
+            .deny_instruction("system.process.*")
+            .deny_instruction("system.fs.remove");
+
+        assert!(!policy.is_module_allowed("io"));
+        assert!(policy.is_module_allowed("system"));
+        assert!(!policy.is_instruction_allowed("io.readFile"));
+        assert!(!policy.is_instruction_allowed("system.process.exec"));
+        assert!(!policy.is_instruction_allowed("system.fs.remove"));
+        assert!(policy.is_instruction_allowed("system.fs.exists"));
+        assert!(policy.is_instruction_allowed("print"));
+        assert!(policy.is_instruction_allowed("iox.read"));
+    }
+
+    #[test]
+    fn test_value_size_checks_the_value_itself() {
+        let policy = ExecutionPolicy::new().max_string_length(3).max_array_length(2);
+
+        assert!(check_value_size(&policy, &json!([1, 2])).is_ok());
+        assert!(check_value_size(&policy, &json!([1, 2, 3])).is_err());
+        assert!(check_value_size(&policy, &json!("abc")).is_ok());
+        assert!(check_value_size(&policy, &json!("abcd")).is_err());
+        // 嵌套的值由产生它们的语句检查
+        assert!(check_value_size(&policy, &json!({"a": [1, 2, 3]})).is_ok());
+    }
+}
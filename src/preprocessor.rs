@@ -15,9 +15,15 @@ impl Preprocessor {
     
     /// 处理内容，移除注释
     pub fn preprocess_content(content: &str) -> Result<String, NjilError> {
+        Self::preprocess_with_offsets(content).map(|(result, _)| result)
+    }
+    
+    /// 处理内容，移除注释，同时返回结果中每个字符在原始内容中的字符索引
+    pub fn preprocess_with_offsets(content: &str) -> Result<(String, Vec<usize>), NjilError> {
         debug_println!("开始预处理内容，移除注释");
         
         let mut result = String::new();
+        let mut offsets = Vec::new();
         let mut in_string = false;
         let mut in_single_line_comment = false;
         let mut in_multi_line_comment = false;
@@ -32,6 +38,7 @@ impl Preprocessor {
             if escape_next {
                 if !in_single_line_comment && !in_multi_line_comment {
                     result.push(c);
+                    offsets.push(i);
                 }
                 escape_next = false;
                 i += 1;
@@ -43,6 +50,7 @@ impl Preprocessor {
                 escape_next = true;
                 if !in_single_line_comment && !in_multi_line_comment {
                     result.push(c);
+                    offsets.push(i);
                 }
                 i += 1;
                 continue;
@@ -52,6 +60,7 @@ impl Preprocessor {
             if c == '"' && !in_single_line_comment && !in_multi_line_comment {
                 in_string = !in_string;
                 result.push(c);
+                offsets.push(i);
                 i += 1;
                 continue;
             }
@@ -59,6 +68,7 @@ impl Preprocessor {
             // 在字符串内，直接添加字符
             if in_string {
                 result.push(c);
+                offsets.push(i);
                 i += 1;
                 continue;
             }
@@ -82,6 +92,7 @@ impl Preprocessor {
                 in_single_line_comment = false;
                 // 保留换行符
                 result.push(c);
+                offsets.push(i);
                 i += 1;
                 continue;
             }
@@ -96,13 +107,14 @@ impl Preprocessor {
             // 非注释内容，添加到结果
             if !in_single_line_comment && !in_multi_line_comment {
                 result.push(c);
+                offsets.push(i);
             }
             
             i += 1;
         }
         
         debug_println!("预处理完成，移除了注释");
-        Ok(result)
+        Ok((result, offsets))
     }
 }
 
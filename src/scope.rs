@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use serde_json::Value;
+
+/// 作用域类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// 全局作用域（main函数和NJIS脚本的顶层）
+    Global,
+    /// 函数调用帧
+    Function,
+    /// 块作用域（循环体、catch块、match分支）
+    Block,
+}
+
+/// 作用域引用，作用域之间通过parent指针组成作用域链
+pub type ScopeRef = Arc<Mutex<Scope>>;
+
+/// 单个作用域
+#[derive(Debug)]
+pub struct Scope {
+    /// 作用域类型
+    pub kind: ScopeKind,
+
+    /// 在该作用域中声明的变量
+    pub variables: HashMap<String, Value>,
+
+    /// 在该函数帧中通过global声明的变量名
+    pub global_names: HashSet<String>,
+
+    /// 外层作用域
+    pub parent: Option<ScopeRef>,
+}
+
+impl Scope {
+    /// 创建一个新的作用域引用
+    pub fn new_ref(kind: ScopeKind, parent: Option<ScopeRef>) -> ScopeRef {
+        Arc::new(Mutex::new(Self {
+            kind,
+            variables: HashMap::new(),
+            global_names: HashSet::new(),
+            parent,
+        }))
+    }
+}
+
+/// 从指定作用域开始沿作用域链查找变量所在的作用域
+///
+/// 函数帧中声明为global的变量会直接跳转到全局作用域查找。
+pub fn find_owner(start: &ScopeRef, global: &ScopeRef, name: &str) -> Option<ScopeRef> {
+    let mut scope = start.clone();
+    loop {
+        let next = {
+            let current = scope.lock().unwrap();
+            if current.variables.contains_key(name) {
+                None
+            } else if current.kind == ScopeKind::Function && current.global_names.contains(name) {
+                return if global.lock().unwrap().variables.contains_key(name) {
+                    Some(global.clone())
+                } else {
+                    None
+                };
+            } else {
+                match &current.parent {
+                    Some(parent) => Some(parent.clone()),
+                    None => return None,
+                }
+            }
+        };
+
+        match next {
+            Some(parent) => scope = parent,
+            None => return Some(scope),
+        }
+    }
+}
+
+/// 沿作用域链读取变量，在持有变量所在作用域的锁时用f借用变量值
+///
+/// 查找规则与 `find_owner` 相同，只是找到变量时直接读取，不需要再次加锁和查找。
+pub fn read_variable<R>(start: &ScopeRef, global: &ScopeRef, name: &str, f: impl FnOnce(&Value) -> R) -> Option<R> {
+    let mut parent;
+    let mut scope = start;
+    loop {
+        parent = {
+            let current = scope.lock().unwrap();
+            if let Some(value) = current.variables.get(name) {
+                return Some(f(value));
+            }
+            if current.kind == ScopeKind::Function && current.global_names.contains(name) {
+                drop(current);
+                return global.lock().unwrap().variables.get(name).map(f);
+            }
+            current.parent.clone()?
+        };
+        scope = &parent;
+    }
+}
+
+/// 给变量赋值，目标作用域与 `find_assign_target` 相同
+pub fn assign_variable(start: &ScopeRef, global: &ScopeRef, name: String, value: Value) {
+    let mut parent;
+    let mut scope = start;
+    loop {
+        parent = {
+            let mut current = scope.lock().unwrap();
+            if let Some(slot) = current.variables.get_mut(&name) {
+                *slot = value;
+                return;
+            }
+            match current.kind {
+                ScopeKind::Function if current.global_names.contains(&name) => {
+                    drop(current);
+                    global.lock().unwrap().variables.insert(name, value);
+                    return;
+                },
+                ScopeKind::Function | ScopeKind::Global => {
+                    current.variables.insert(name, value);
+                    return;
+                },
+                ScopeKind::Block => match current.parent.clone() {
+                    Some(parent) => parent,
+                    None => {
+                        drop(current);
+                        global.lock().unwrap().variables.insert(name, value);
+                        return;
+                    },
+                },
+            }
+        };
+        scope = &parent;
+    }
+}
+
+/// 查找变量赋值的目标作用域
+///
+/// 在当前函数帧内（包括其中的块作用域）查找已存在的变量；
+/// 找不到时新变量写入函数帧本身，块作用域只保存显式声明的变量（如循环变量）。
+/// 声明为global的变量写入全局作用域。
+pub fn find_assign_target(start: &ScopeRef, global: &ScopeRef, name: &str) -> ScopeRef {
+    let mut scope = start.clone();
+    loop {
+        let next = {
+            let current = scope.lock().unwrap();
+            if current.variables.contains_key(name) {
+                return scope.clone();
+            }
+            match current.kind {
+                ScopeKind::Function if current.global_names.contains(name) => return global.clone(),
+                ScopeKind::Function | ScopeKind::Global => return scope.clone(),
+                ScopeKind::Block => current.parent.clone(),
+            }
+        };
+
+        match next {
+            Some(parent) => scope = parent,
+            None => return global.clone(),
+        }
+    }
+}
+
+/// 查找当前作用域所在的函数帧（在顶层时返回全局作用域）
+pub fn frame_of(start: &ScopeRef) -> ScopeRef {
+    let mut scope = start.clone();
+    loop {
+        let next = {
+            let current = scope.lock().unwrap();
+            match current.kind {
+                ScopeKind::Function | ScopeKind::Global => return scope.clone(),
+                ScopeKind::Block => current.parent.clone(),
+            }
+        };
+
+        match next {
+            Some(parent) => scope = parent,
+            None => return scope,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_block_scope_does_not_capture_assignments() {
+        let global = Scope::new_ref(ScopeKind::Global, None);
+        let block = Scope::new_ref(ScopeKind::Block, Some(global.clone()));
+        block.lock().unwrap().variables.insert("item".to_string(), json!(1));
+
+        // 已声明在块中的变量写回块作用域
+        assert!(Arc::ptr_eq(&find_assign_target(&block, &global, "item"), &block));
+        // 未声明的变量写入外层帧
+        assert!(Arc::ptr_eq(&find_assign_target(&block, &global, "total"), &global));
+    }
+
+    #[test]
+    fn test_function_frame_reads_global_but_writes_local() {
+        let global = Scope::new_ref(ScopeKind::Global, None);
+        global.lock().unwrap().variables.insert("count".to_string(), json!(0));
+        let frame = Scope::new_ref(ScopeKind::Function, Some(global.clone()));
+
+        assert!(Arc::ptr_eq(&find_owner(&frame, &global, "count").unwrap(), &global));
+        assert!(Arc::ptr_eq(&find_assign_target(&frame, &global, "count"), &frame));
+
+        // 声明global后写入全局作用域
+        frame.lock().unwrap().global_names.insert("count".to_string());
+        assert!(Arc::ptr_eq(&find_assign_target(&frame, &global, "count"), &global));
+    }
+}
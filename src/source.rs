@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::error::NjilError;
+use crate::preprocessor::Preprocessor;
+
+// 源码位置模块
+// 预处理时记录每个输出字符在原始源码中的位置，再扫描JSON结构得到每个值的起始位置，
+// 位置与解析出的语句一起保存，执行出错时据此报告文件、行号、列号和代码片段。
+
+/// 原始源文件
+pub struct SourceFile {
+    name: String,
+    text: String,
+    /// 每一行第一个字符的字符索引
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let mut line_starts = vec![0];
+        for (i, c) in text.chars().enumerate() {
+            if c == '\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        Self { name: name.into(), text, line_starts }
+    }
+
+    /// 文件名
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 获取指定行的内容（行号从1开始）
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.text.lines().nth(line.checked_sub(1)?)
+    }
+
+    // 辅助函数：把字符索引转换为从1开始的行号和列号
+    fn line_column(&self, index: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= index);
+        (line, index - self.line_starts[line - 1] + 1)
+    }
+}
+
+/// 源码中的一个位置
+#[derive(Clone)]
+pub struct Location {
+    file: Arc<SourceFile>,
+    /// 行号，从1开始
+    pub line: usize,
+    /// 列号（按字符计算），从1开始
+    pub column: usize,
+}
+
+impl Location {
+    /// 所在文件名
+    pub fn file_name(&self) -> &str {
+        self.file.name()
+    }
+
+    /// 所在行的代码片段，下一行用 `^` 指出列位置
+    pub fn snippet(&self) -> String {
+        let Some(line) = self.file.line(self.line) else {
+            return String::new();
+        };
+        let line = line.trim_end_matches('\r');
+        let gutter = self.line.to_string();
+        let padding = " ".repeat(gutter.len());
+        // 保留制表符，使箭头与代码对齐
+        let indent: String = line.chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!("{} |\n{} | {}\n{} | {}^", padding, gutter, line, padding, indent)
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.name, self.line, self.column)
+    }
+}
+
+impl fmt::Debug for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Location({})", self)
+    }
+}
+
+/// 一个JSON值在源码中的起始位置及其子值的位置
+///
+/// 与解析出的语句一起保存（函数、NJIS脚本、defer和任务的语句块），
+/// 执行出错时在正在执行的代码中找到出错的语句，再取出对应的位置。
+#[derive(Debug)]
+pub struct Spans {
+    location: Location,
+    children: SpanChildren,
+}
+
+#[derive(Debug)]
+enum SpanChildren {
+    None,
+    Object(HashMap<String, Arc<Spans>>),
+    Array(Vec<Arc<Spans>>),
+}
+
+impl Spans {
+    /// 值的起始位置
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// 对象中指定键的值的位置
+    pub fn get(&self, key: &str) -> Option<&Arc<Spans>> {
+        match &self.children {
+            SpanChildren::Object(children) => children.get(key),
+            _ => None,
+        }
+    }
+
+    /// 数组中指定元素的位置
+    pub fn index(&self, index: usize) -> Option<&Arc<Spans>> {
+        match &self.children {
+            SpanChildren::Array(children) => children.get(index),
+            _ => None,
+        }
+    }
+}
+
+/// 在值及其位置中查找地址为address的子值，返回它的位置
+///
+/// value必须是这些位置所描述的值本身（而不是副本），地址只在其中比较，不会被解引用。
+pub(crate) fn locate<'a>(spans: &'a Arc<Spans>, value: &Value, address: usize) -> Option<&'a Arc<Spans>> {
+    if self::address(value) == address {
+        return Some(spans);
+    }
+    match (value, &spans.children) {
+        (Value::Object(obj), SpanChildren::Object(children)) => obj.iter()
+            .find_map(|(key, child)| locate(children.get(key)?, child, address)),
+        (Value::Array(items), SpanChildren::Array(_)) => locate_all(spans, items, address),
+        _ => None,
+    }
+}
+
+/// 在语句序列及其位置（数组的位置）中查找地址为address的语句
+pub(crate) fn locate_all<'a>(spans: &'a Arc<Spans>, values: &[Value], address: usize) -> Option<&'a Arc<Spans>> {
+    match &spans.children {
+        SpanChildren::Array(children) => children.iter()
+            .zip(values)
+            .find_map(|(child, value)| locate(child, value, address)),
+        _ => None,
+    }
+}
+
+/// 值的地址，用于在正在执行的代码中找到正在处理的语句
+pub(crate) fn address(value: &Value) -> usize {
+    value as *const Value as usize
+}
+
+/// 预处理后的源码及其位置信息
+pub struct SourceInfo {
+    file: Arc<SourceFile>,
+    /// 预处理后每个字符在原始源码中的字符索引
+    offsets: Vec<usize>,
+    /// 预处理后的内容
+    processed: String,
+    root: Option<Arc<Spans>>,
+}
+
+impl fmt::Debug for SourceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SourceInfo({})", self.file.name)
+    }
+}
+
+impl SourceInfo {
+    /// 预处理源码（移除注释）并记录JSON值的位置
+    pub fn new(name: impl Into<String>, content: &str) -> Result<Self, NjilError> {
+        let (processed, offsets) = Preprocessor::preprocess_with_offsets(content)?;
+        let mut info = Self {
+            file: Arc::new(SourceFile::new(name, content)),
+            offsets,
+            processed,
+            root: None,
+        };
+        let chars: Vec<char> = info.processed.chars().collect();
+        let mut scanner = Scanner { info: &info, chars: &chars, pos: 0, depth: 0 };
+        info.root = scanner.value();
+        Ok(info)
+    }
+
+    /// 预处理后的内容
+    pub fn processed(&self) -> &str {
+        &self.processed
+    }
+
+    /// 解析预处理后的内容，解析失败时错误中带有原始源码中的位置
+    pub fn parse<T: DeserializeOwned>(&self, describe: impl FnOnce(serde_json::Error) -> NjilError) -> Result<T, NjilError> {
+        serde_json::from_str(&self.processed).map_err(|e| {
+            let location = (e.line() > 0).then(|| self.parse_error_location(e.line(), e.column()));
+            let error = describe(e);
+            match location {
+                Some(location) => NjilError::Traced { error: Box::new(error), location: Some(location), stack: Vec::new() },
+                None => error,
+            }
+        })
+    }
+
+    // 辅助函数：把预处理后内容中的行列号转换为原始源码中的位置
+    fn parse_error_location(&self, line: usize, column: usize) -> Location {
+        let line_start = self.processed.chars()
+            .enumerate()
+            .filter(|(_, c)| *c == '\n')
+            .nth(line.saturating_sub(2))
+            .map(|(i, _)| i + 1)
+            .filter(|_| line > 1)
+            .unwrap_or(0);
+        self.location_at(line_start + column.saturating_sub(1))
+    }
+
+    // 辅助函数：预处理后内容中的字符索引对应的原始位置
+    fn location_at(&self, processed_index: usize) -> Location {
+        let index = self.offsets.get(processed_index)
+            .or(self.offsets.last())
+            .copied()
+            .unwrap_or(0);
+        let (line, column) = self.file.line_column(index);
+        Location { file: self.file.clone(), line, column }
+    }
+
+    /// 按对象键组成的路径查找值的位置，空路径表示整个内容
+    pub fn spans(&self, path: &[&str]) -> Option<Arc<Spans>> {
+        let mut node = self.root.as_ref()?;
+        for key in path {
+            node = node.get(key)?;
+        }
+        Some(node.clone())
+    }
+}
+
+/// 扫描器允许的最大嵌套层数，与serde_json的递归上限相同，更深的内容无法解析
+const MAX_SCAN_DEPTH: usize = 128;
+
+/// 扫描预处理后的JSON内容，记录每个值的起始位置
+///
+/// 这里只跟踪结构，不做完整校验，结构不完整或嵌套过深时不记录位置，由serde_json报告解析错误。
+struct Scanner<'a> {
+    info: &'a SourceInfo,
+    chars: &'a [char],
+    pos: usize,
+    depth: usize,
+}
+
+impl Scanner<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn value(&mut self) -> Option<Arc<Spans>> {
+        if self.depth >= MAX_SCAN_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let spans = self.nested_value();
+        self.depth -= 1;
+        spans
+    }
+
+    // 辅助函数：读取一个值，对象和数组中的值通过value递归读取
+    fn nested_value(&mut self) -> Option<Arc<Spans>> {
+        self.skip_whitespace();
+        let offset = self.pos;
+        let children = match self.peek()? {
+            '{' => {
+                self.pos += 1;
+                let mut children = HashMap::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        '}' => {
+                            self.pos += 1;
+                            break;
+                        },
+                        ',' => self.pos += 1,
+                        '"' => {
+                            let key = self.string()?;
+                            self.skip_whitespace();
+                            if self.peek()? != ':' {
+                                return None;
+                            }
+                            self.pos += 1;
+                            let child = self.value()?;
+                            children.insert(key, child);
+                        },
+                        _ => return None,
+                    }
+                }
+                SpanChildren::Object(children)
+            },
+            '[' => {
+                self.pos += 1;
+                let mut children = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        ']' => {
+                            self.pos += 1;
+                            break;
+                        },
+                        ',' => self.pos += 1,
+                        _ => children.push(self.value()?),
+                    }
+                }
+                SpanChildren::Array(children)
+            },
+            '"' => {
+                self.string()?;
+                SpanChildren::None
+            },
+            _ => {
+                while let Some(c) = self.peek() {
+                    if c == ',' || c == '}' || c == ']' || c.is_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                SpanChildren::None
+            },
+        };
+        Some(Arc::new(Spans { location: self.info.location_at(offset), children }))
+    }
+
+    // 辅助函数：读取字符串，返回解码后的内容
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                '\\' => self.pos += 2,
+                '"' => {
+                    self.pos += 1;
+                    break;
+                },
+                _ => self.pos += 1,
+            }
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        serde_json::from_str(&raw).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_locations_survive_comment_removal() {
+        let source = "[\n  /* 多行\n     注释 */ {\"print\": \"a\"}, // 注释\n  {\"prnt\": {\"x\": 1}}\n]";
+        let info = SourceInfo::new("test.njis", source).unwrap();
+        let statements: Vec<Value> = info.parse(NjilError::ParseError).unwrap();
+        assert_eq!(statements[1], json!({"prnt": {"x": 1}}));
+
+        let spans = info.spans(&[]).unwrap();
+        let location = locate_all(&spans, &statements, address(&statements[0])).unwrap().location();
+        assert_eq!((location.line, location.column), (3, 12));
+        let location = locate_all(&spans, &statements, address(&statements[1])).unwrap().location();
+        assert_eq!(location.to_string(), "test.njis:4:3");
+        assert_eq!(locate_all(&spans, &statements, address(&statements[1]["prnt"])).unwrap().location().column, 12);
+        assert_eq!(location.snippet(), "  |\n4 |   {\"prnt\": {\"x\": 1}}\n  |   ^");
+
+        // 副本中的语句不在这些位置所描述的值中
+        let copy = statements.clone();
+        assert!(locate_all(&spans, &statements, address(&copy[1])).is_none());
+    }
+
+    #[test]
+    fn test_deeply_nested_source_is_a_parse_error() {
+        let depth = 200_000;
+        let source = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let info = SourceInfo::new("deep.njis", &source).unwrap();
+        assert!(info.spans(&[]).is_none());
+        assert!(info.parse::<Value>(NjilError::ParseError).is_err());
+
+        let mut interpreter = crate::interpreter::Interpreter::new();
+        let error = interpreter.run_njis_str(&source).unwrap_err();
+        assert!(error.message().contains("recursion limit"));
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let source = "{\n  // 注释\n  \"a\": 1,,\n}";
+        let info = SourceInfo::new("bad.njil", source).unwrap();
+        let error = info.parse::<Value>(NjilError::ParseError).unwrap_err();
+        assert_eq!(error.location().unwrap().to_string(), "bad.njil:3:10");
+    }
+}
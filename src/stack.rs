@@ -0,0 +1,128 @@
+use std::fmt;
+use std::sync::Arc;
+use serde_json::{Map, Value};
+use crate::scope::ScopeRef;
+use crate::source::{self, Location, Spans};
+use crate::types::Function;
+
+/// 调用栈中正在执行的一帧
+pub(crate) struct ActiveFrame {
+    pub(crate) function: String,
+    pub(crate) namespace: Option<String>,
+    /// 正在执行的函数体语句的序号
+    pub(crate) index: Option<usize>,
+    /// 正在处理的语句的地址，只用于在帧中正在执行的代码里找到这条语句
+    pub(crate) statement: Option<usize>,
+    /// 帧中正在执行的代码，最后一项是最近开始执行的（如正在执行的defer语句块）
+    pub(crate) code: Vec<Code>,
+    /// 函数退出时按后进先出的顺序执行的defer语句块
+    pub(crate) deferred: Vec<Deferred>,
+}
+
+impl ActiveFrame {
+    pub(crate) fn new(function: impl Into<String>, namespace: Option<String>) -> Self {
+        Self { function: function.into(), namespace, index: None, statement: None, code: Vec::new(), deferred: Vec::new() }
+    }
+    
+    /// 指定帧中执行的代码，用于查找出错语句的源码位置
+    pub(crate) fn with_code(mut self, code: Option<Code>) -> Self {
+        self.code.extend(code);
+        self
+    }
+    
+    /// 帧中正在执行的代码里指定地址的语句的位置
+    pub(crate) fn locate(&self, address: usize) -> Option<&Arc<Spans>> {
+        self.code.iter().rev().find_map(|code| code.locate(address))
+    }
+}
+
+/// 带有源码位置的代码，持有代码本身，其中语句的地址在帧执行期间保持不变
+#[derive(Clone)]
+pub(crate) enum Code {
+    /// 函数的函数体和参数默认值
+    Function(Arc<Function>),
+    /// NJIS脚本的顶层语句
+    Script(Arc<Vec<Value>>, Arc<Spans>),
+    /// defer或任务的语句块
+    Block(Arc<Value>, Arc<Spans>),
+}
+
+impl Code {
+    /// 函数的代码，函数没有源码位置时返回None
+    pub(crate) fn function(function: &Arc<Function>) -> Option<Code> {
+        function.spans.is_some().then(|| Code::Function(function.clone()))
+    }
+    
+    fn locate(&self, address: usize) -> Option<&Arc<Spans>> {
+        match self {
+            Code::Function(function) => {
+                let spans = function.spans.as_ref()?;
+                let body = spans.get("body").and_then(|body| source::locate_all(body, &function.body, address));
+                body.or_else(|| function.params.iter().enumerate().find_map(|(i, param)| {
+                    let default = spans.get("params")?.index(i)?.get("default")?;
+                    source::locate(default, param.default.as_ref()?, address)
+                }))
+            },
+            Code::Script(statements, spans) => source::locate_all(spans, statements, address),
+            Code::Block(block, spans) => source::locate(spans, block, address),
+        }
+    }
+}
+
+/// 登记的defer语句块及登记时的作用域
+pub(crate) struct Deferred {
+    pub(crate) block: Arc<Value>,
+    /// 语句块在源码中的位置
+    pub(crate) spans: Option<Arc<Spans>>,
+    pub(crate) scope: ScopeRef,
+}
+
+/// 错误发生时调用栈中的一帧
+#[derive(Debug, Clone)]
+pub struct StackFrame {
+    /// 函数名，NJIS脚本的顶层为 `<script>`
+    pub function: String,
+    /// 函数所在模块的命名空间
+    pub namespace: Option<String>,
+    /// 正在执行的函数体语句的序号（从0开始）
+    pub statement: Option<usize>,
+    /// 正在执行的语句在源码中的位置
+    pub location: Option<Location>,
+}
+
+impl StackFrame {
+    /// 转换为脚本中可以访问的对象
+    pub fn to_value(&self) -> Value {
+        let mut frame = Map::new();
+        frame.insert("function".to_string(), Value::String(self.function.clone()));
+        frame.insert("namespace".to_string(), self.namespace.clone().map(Value::String).unwrap_or(Value::Null));
+        frame.insert("statement".to_string(), self.statement.map(Value::from).unwrap_or(Value::Null));
+        frame.insert("location".to_string(), self.location.as_ref().map(|l| Value::String(l.to_string())).unwrap_or(Value::Null));
+        Value::Object(frame)
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.function)?;
+        match (&self.location, self.statement) {
+            (Some(location), _) => write!(f, " ({})", location),
+            (None, Some(index)) => write!(f, " (第{}条语句)", index + 1),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// 把调用栈转换为数组，最内层的调用在前
+pub fn stack_to_value(stack: &[StackFrame]) -> Value {
+    Value::Array(stack.iter().map(StackFrame::to_value).collect())
+}
+
+/// 把调用栈格式化为多行文本，用于命令行输出
+pub fn format_stack(stack: &[StackFrame]) -> String {
+    let mut text = String::from("调用栈（最近的调用在前）:");
+    for frame in stack {
+        text.push_str(&format!("\n    在 {}", frame));
+    }
+    text
+}
@@ -42,7 +42,7 @@ impl StatementHandler for AddHandler {
                 let operand_value = interpreter.evaluate_value(operand)?;
                 
                 // 执行加法运算
-                result = type_convert::add(&result, &operand_value);
+                result = type_convert::add(&result, &operand_value)?;
             }
             
             Ok(result)
@@ -53,7 +53,7 @@ impl StatementHandler for AddHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "math.add"
     }
     
@@ -0,0 +1,32 @@
+use crate::error::NjilError;
+use serde_json::Value;
+use crate::interpreter::Interpreter;
+use crate::errortip::math as tip;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert;
+
+/// 大整数转换处理器
+///
+/// 把整数或十进制数字字符串转换为大整数，有大整数参与的整数运算不会溢出。
+pub struct BigIntHandler;
+
+// 静态实例
+pub static BIGINT_HANDLER: BigIntHandler = BigIntHandler;
+
+impl StatementHandler for BigIntHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let value = interpreter.evaluate_value(value)?;
+        match type_convert::to_bigint(&value) {
+            Some(number) => Ok(type_convert::bigint_value(&number)),
+            None => Err(NjilError::TypeError(tip::bigint_invalid(&type_convert::to_string(&value)))),
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "math.bigint"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.bigint", "bigint"]
+    }
+}
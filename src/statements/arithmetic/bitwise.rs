@@ -0,0 +1,111 @@
+use crate::error::NjilError;
+use serde_json::Value;
+use crate::interpreter::Interpreter;
+use crate::errortip::math as tip;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert::{self, IntegerOp};
+
+/// 按位与处理器
+pub struct BitAndHandler;
+/// 按位或处理器
+pub struct BitOrHandler;
+/// 按位异或处理器
+pub struct BitXorHandler;
+/// 左移处理器
+pub struct ShiftLeftHandler;
+/// 右移处理器（算术右移）
+pub struct ShiftRightHandler;
+
+// 静态实例
+pub static BIT_AND_HANDLER: BitAndHandler = BitAndHandler;
+pub static BIT_OR_HANDLER: BitOrHandler = BitOrHandler;
+pub static BIT_XOR_HANDLER: BitXorHandler = BitXorHandler;
+pub static SHIFT_LEFT_HANDLER: ShiftLeftHandler = ShiftLeftHandler;
+pub static SHIFT_RIGHT_HANDLER: ShiftRightHandler = ShiftRightHandler;
+
+impl StatementHandler for BitAndHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_bitwise(interpreter, value, IntegerOp::BitAnd)
+    }
+    
+    fn name(&self) -> &str {
+        "math.and"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.and", "bitand"]
+    }
+}
+
+impl StatementHandler for BitOrHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_bitwise(interpreter, value, IntegerOp::BitOr)
+    }
+    
+    fn name(&self) -> &str {
+        "math.or"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.or", "bitor"]
+    }
+}
+
+impl StatementHandler for BitXorHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_bitwise(interpreter, value, IntegerOp::BitXor)
+    }
+    
+    fn name(&self) -> &str {
+        "math.xor"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.xor", "bitxor", "xor"]
+    }
+}
+
+impl StatementHandler for ShiftLeftHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_bitwise(interpreter, value, IntegerOp::Shl)
+    }
+    
+    fn name(&self) -> &str {
+        "math.shl"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.shl", "shl"]
+    }
+}
+
+impl StatementHandler for ShiftRightHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_bitwise(interpreter, value, IntegerOp::Shr)
+    }
+    
+    fn name(&self) -> &str {
+        "math.shr"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.shr", "shr"]
+    }
+}
+
+// 辅助函数：从左到右依次对数组中的操作数执行位运算
+fn fold_bitwise(interpreter: &mut Interpreter, value: &Value, op: IntegerOp) -> Result<Value, NjilError> {
+    let Value::Array(operands) = value else {
+        return Err(NjilError::ExecutionError(tip::operands_required(op.name())));
+    };
+    if operands.len() < 2 {
+        return Err(NjilError::ExecutionError(tip::operands_required(op.name())));
+    }
+    
+    let mut result = interpreter.evaluate_value(&operands[0])?;
+    for operand in &operands[1..] {
+        let operand_value = interpreter.evaluate_value(operand)?;
+        result = type_convert::bitwise(op, &result, &operand_value)?;
+    }
+    Ok(result)
+}
@@ -2,7 +2,9 @@ use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::statements::StatementHandler;
-use crate::utils::type_convert;
+use crate::utils::type_convert::integer::compare_integers;
+use crate::utils::type_convert::decimal::compare_decimals;
+use std::cmp::Ordering;
 
 /// 比较运算处理器
 pub struct CompareHandler;
@@ -57,7 +59,7 @@ impl StatementHandler for CompareHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "math.compare"
     }
     
@@ -69,6 +71,10 @@ impl StatementHandler for CompareHandler {
 impl CompareHandler {
     /// 判断两个值是否相等
     fn equals(&self, left: &Value, right: &Value) -> bool {
+        // 整数（包括大整数）和定点小数精确比较
+        if let Some(ordering) = compare_integers(left, right).or_else(|| compare_decimals(left, right)) {
+            return ordering == Ordering::Equal;
+        }
         match (left, right) {
             (Value::Number(l), Value::Number(r)) => {
                 // 数字比较
@@ -87,6 +93,9 @@ impl CompareHandler {
     
     /// 判断左值是否大于右值
     fn greater_than(&self, left: &Value, right: &Value) -> bool {
+        if let Some(ordering) = compare_integers(left, right).or_else(|| compare_decimals(left, right)) {
+            return ordering == Ordering::Greater;
+        }
         match (left, right) {
             (Value::Number(l), Value::Number(r)) => {
                 if let (Some(l_f64), Some(r_f64)) = (l.as_f64(), r.as_f64()) {
@@ -107,6 +116,9 @@ impl CompareHandler {
     
     /// 判断左值是否小于右值
     fn less_than(&self, left: &Value, right: &Value) -> bool {
+        if let Some(ordering) = compare_integers(left, right).or_else(|| compare_decimals(left, right)) {
+            return ordering == Ordering::Less;
+        }
         match (left, right) {
             (Value::Number(l), Value::Number(r)) => {
                 if let (Some(l_f64), Some(r_f64)) = (l.as_f64(), r.as_f64()) {
@@ -0,0 +1,274 @@
+use crate::error::NjilError;
+use serde_json::{Map, Value};
+use crate::interpreter::Interpreter;
+use crate::errortip::math as tip;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert::{self, Decimal, Rounding};
+use crate::utils::type_convert::decimal::{check_scale, DEFAULT_DIVISION_SCALE};
+
+/// 定点小数转换处理器
+///
+/// 参数可以是要转换的值，也可以是 `{"value", "scale", "rounding"}`，转换后舍入到scale位小数。
+pub struct DecimalHandler;
+/// 定点小数加法处理器
+pub struct DecimalAddHandler;
+/// 定点小数减法处理器
+pub struct DecimalSubtractHandler;
+/// 定点小数乘法处理器
+pub struct DecimalMultiplyHandler;
+/// 定点小数除法处理器
+pub struct DecimalDivideHandler;
+/// 定点小数舍入处理器
+pub struct DecimalRoundHandler;
+/// 定点小数格式化处理器，结果为字符串
+pub struct DecimalFormatHandler;
+
+// 静态实例
+pub static DECIMAL_HANDLER: DecimalHandler = DecimalHandler;
+pub static DECIMAL_ADD_HANDLER: DecimalAddHandler = DecimalAddHandler;
+pub static DECIMAL_SUBTRACT_HANDLER: DecimalSubtractHandler = DecimalSubtractHandler;
+pub static DECIMAL_MULTIPLY_HANDLER: DecimalMultiplyHandler = DecimalMultiplyHandler;
+pub static DECIMAL_DIVIDE_HANDLER: DecimalDivideHandler = DecimalDivideHandler;
+pub static DECIMAL_ROUND_HANDLER: DecimalRoundHandler = DecimalRoundHandler;
+pub static DECIMAL_FORMAT_HANDLER: DecimalFormatHandler = DecimalFormatHandler;
+
+impl StatementHandler for DecimalHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (number, options) = match value {
+            Value::Object(obj) if obj.contains_key("value") => {
+                let number = evaluate_decimal(interpreter, &obj["value"])?;
+                (number, Some(obj))
+            },
+            _ => (evaluate_decimal(interpreter, value)?, None),
+        };
+        let number = match options {
+            Some(options) => apply_scale(interpreter, &number, options)?,
+            None => number,
+        };
+        Ok(type_convert::decimal_value(&number))
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.decimal", "decimal"]
+    }
+}
+
+impl StatementHandler for DecimalAddHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_decimal(interpreter, value, self.name(), |l, r, _| Some(l.add(r)))
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal.add"
+    }
+}
+
+impl StatementHandler for DecimalSubtractHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_decimal(interpreter, value, self.name(), |l, r, _| Some(l.sub(r)))
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal.sub"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.decimal.sub", "math.decimal.subtract"]
+    }
+}
+
+impl StatementHandler for DecimalMultiplyHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        fold_decimal(interpreter, value, self.name(), |l, r, _| Some(l.mul(r)))
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal.mul"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.decimal.mul", "math.decimal.multiply"]
+    }
+}
+
+impl StatementHandler for DecimalDivideHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        // 指定了scale时每一步都舍入到scale位，否则保留DEFAULT_DIVISION_SCALE位后去掉末尾的零
+        fold_decimal(interpreter, value, self.name(), |l, r, precision| match precision {
+            Some((scale, rounding)) => l.div(r, scale, rounding),
+            None => l.div(r, DEFAULT_DIVISION_SCALE, Rounding::HalfUp).map(|n| n.normalize()),
+        })
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal.div"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.decimal.div", "math.decimal.divide"]
+    }
+}
+
+impl StatementHandler for DecimalRoundHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let Value::Object(obj) = value else {
+            return Err(NjilError::ExecutionError(tip::decimal_value_required(self.name())));
+        };
+        let Some(number) = obj.get("value") else {
+            return Err(NjilError::ExecutionError(tip::decimal_value_required(self.name())));
+        };
+        let number = evaluate_decimal(interpreter, number)?;
+        
+        // 默认舍入为整数
+        let scale = match obj.get("scale") {
+            Some(scale) => check_scale(&interpreter.evaluate_value(scale)?)?,
+            None => 0,
+        };
+        let rounding = evaluate_rounding(interpreter, obj)?;
+        Ok(type_convert::decimal_value(&number.round(scale, rounding)))
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal.round"
+    }
+}
+
+impl StatementHandler for DecimalFormatHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let number = match value {
+            Value::Object(obj) if obj.contains_key("value") => {
+                let number = evaluate_decimal(interpreter, &obj["value"])?;
+                apply_scale(interpreter, &number, obj)?
+            },
+            _ => evaluate_decimal(interpreter, value)?,
+        };
+        Ok(Value::String(number.to_string()))
+    }
+    
+    fn name(&self) -> &str {
+        "math.decimal.format"
+    }
+}
+
+// 辅助函数：求值并转换为定点小数
+fn evaluate_decimal(interpreter: &mut Interpreter, value: &Value) -> Result<Decimal, NjilError> {
+    let value = interpreter.evaluate_value(value)?;
+    type_convert::to_decimal(&value)
+        .ok_or_else(|| NjilError::TypeError(tip::decimal_invalid(&type_convert::to_string(&value))))
+}
+
+// 辅助函数：读取rounding字段，默认为half_up
+fn evaluate_rounding(interpreter: &mut Interpreter, options: &Map<String, Value>) -> Result<Rounding, NjilError> {
+    let Some(rounding) = options.get("rounding") else {
+        return Ok(Rounding::default());
+    };
+    let rounding = type_convert::to_string(&interpreter.evaluate_value(rounding)?);
+    Rounding::parse(&rounding).ok_or_else(|| NjilError::ExecutionError(tip::rounding_invalid(&rounding)))
+}
+
+// 辅助函数：读取scale和rounding字段，没有scale时返回None
+fn evaluate_precision(interpreter: &mut Interpreter, options: &Map<String, Value>) -> Result<Option<(u32, Rounding)>, NjilError> {
+    let Some(scale) = options.get("scale") else {
+        return Ok(None);
+    };
+    let scale = check_scale(&interpreter.evaluate_value(scale)?)?;
+    Ok(Some((scale, evaluate_rounding(interpreter, options)?)))
+}
+
+// 辅助函数：有scale字段时舍入到scale位小数
+fn apply_scale(interpreter: &mut Interpreter, number: &Decimal, options: &Map<String, Value>) -> Result<Decimal, NjilError> {
+    match evaluate_precision(interpreter, options)? {
+        Some((scale, rounding)) => Ok(number.round(scale, rounding)),
+        None => Ok(number.clone()),
+    }
+}
+
+// 辅助函数：从左到右依次计算操作数
+//
+// 参数可以是操作数数组，也可以是 `{"operands", "scale", "rounding"}`，结果舍入到scale位小数。
+// 运算返回None（除数为零）时结果为null。
+fn fold_decimal<F>(interpreter: &mut Interpreter, value: &Value, instruction: &str, op: F) -> Result<Value, NjilError>
+where
+    F: Fn(&Decimal, &Decimal, Option<(u32, Rounding)>) -> Option<Decimal>,
+{
+    let (operands, precision) = match value {
+        Value::Array(operands) => (operands, None),
+        Value::Object(obj) => match obj.get("operands") {
+            Some(Value::Array(operands)) => (operands, evaluate_precision(interpreter, obj)?),
+            _ => return Err(NjilError::ExecutionError(tip::decimal_arguments(instruction))),
+        },
+        _ => return Err(NjilError::ExecutionError(tip::decimal_arguments(instruction))),
+    };
+    let Some((first, rest)) = operands.split_first() else {
+        return Err(NjilError::ExecutionError(tip::decimal_arguments(instruction)));
+    };
+    
+    let mut result = evaluate_decimal(interpreter, first)?;
+    for operand in rest {
+        let operand = evaluate_decimal(interpreter, operand)?;
+        match op(&result, &operand, precision) {
+            Some(value) => result = value,
+            None => return Ok(Value::Null),
+        }
+    }
+    
+    if let Some((scale, rounding)) = precision {
+        result = result.round(scale, rounding);
+    }
+    Ok(type_convert::decimal_value(&result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    fn run(statement: Value) -> Result<Value, NjilError> {
+        let mut interpreter = Interpreter::new();
+        crate::statements::handle_statement(&mut interpreter, &statement)
+    }
+    
+    #[test]
+    fn test_decimal_arithmetic() {
+        assert_eq!(run(json!({"math.decimal.add": ["0.1", "0.2"]})).unwrap(), json!({"$decimal": "0.3"}));
+        assert_eq!(run(json!({"math.decimal.mul": ["19.99", 3]})).unwrap(), json!({"$decimal": "59.97"}));
+        assert_eq!(run(json!({"math.decimal.sub": [{"math.decimal": "10"}, 0.25]})).unwrap(), json!({"$decimal": "9.75"}));
+        assert_eq!(run(json!({"math.decimal.div": ["1", "4"]})).unwrap(), json!({"$decimal": "0.25"}));
+        assert_eq!(run(json!({"math.decimal.div": {"operands": [10, 3], "scale": 2}})).unwrap(), json!({"$decimal": "3.33"}));
+        assert_eq!(run(json!({"math.decimal.div": ["1", 0]})).unwrap(), Value::Null);
+        // 普通算术指令遇到定点小数时也按定点小数计算
+        assert_eq!(run(json!({"math.add": [{"math.decimal": "0.1"}, 0.2]})).unwrap(), json!({"$decimal": "0.3"}));
+        assert!(run(json!({"math.decimal.add": ["0.1", "abc"]})).is_err());
+    }
+    
+    #[test]
+    fn test_decimal_rounding_modes() {
+        let round = |value: &str, scale: u32, rounding: &str| {
+            run(json!({"math.decimal.format": {"value": value, "scale": scale, "rounding": rounding}})).unwrap()
+        };
+        assert_eq!(round("2.345", 2, "half_up"), json!("2.35"));
+        assert_eq!(round("2.345", 2, "half_even"), json!("2.34"));
+        assert_eq!(round("2.355", 2, "half_even"), json!("2.36"));
+        assert_eq!(round("-2.345", 2, "half_up"), json!("-2.35"));
+        assert_eq!(round("-2.341", 2, "floor"), json!("-2.35"));
+        assert_eq!(round("2.349", 2, "floor"), json!("2.34"));
+        assert_eq!(round("2.5", 3, "floor"), json!("2.500"));
+        assert_eq!(run(json!({"math.decimal.round": {"value": "-0.5", "rounding": "half-even"}})).unwrap(), json!({"$decimal": "0"}));
+        assert!(run(json!({"math.decimal.round": {"value": "1.5", "rounding": "up"}})).is_err());
+    }
+    
+    #[test]
+    fn test_decimal_compare() {
+        let compare = |left: Value, op: &str, right: Value| {
+            run(json!({"math.compare": {"left": left, "op": op, "right": right}})).unwrap()
+        };
+        assert_eq!(compare(json!({"math.decimal": "0.30"}), "==", json!({"math.decimal.add": ["0.1", "0.2"]})), json!(true));
+        assert_eq!(compare(json!({"math.decimal": "0.3"}), "==", json!(0.3)), json!(true));
+        assert_eq!(compare(json!({"math.decimal": "2.50"}), ">", json!(2)), json!(true));
+        assert_eq!(compare(json!({"math.decimal": "-1.5"}), "<", json!({"math.decimal": "-1.25"})), json!(true));
+    }
+}
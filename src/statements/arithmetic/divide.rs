@@ -25,6 +25,10 @@ impl StatementHandler for DivideHandler {
             
             // 如果只有一个操作数，返回其倒数
             if operands.len() == 1 {
+                // 整数按整数除法的规则计算，1和-1的倒数仍是整数
+                if type_convert::integer::as_integer(&first_operand).is_some() {
+                    return type_convert::divide(&Value::from(1), &first_operand);
+                }
                 if let Some(n) = type_convert::to_number(&first_operand) {
                     if n == 0.0 {
                         return Ok(Value::Null); // 除以零返回null
@@ -44,7 +48,7 @@ impl StatementHandler for DivideHandler {
                 let operand_value = interpreter.evaluate_value(operand)?;
                 
                 // 执行除法运算
-                result = type_convert::divide(&result, &operand_value);
+                result = type_convert::divide(&result, &operand_value)?;
             }
             
             return Ok(result);
@@ -78,7 +82,7 @@ impl StatementHandler for DivideHandler {
                         let divisor_value = interpreter.evaluate_value(divisor)?;
                         
                         // 执行除法运算
-                        result = type_convert::divide(&result, &divisor_value);
+                        result = type_convert::divide(&result, &divisor_value)?;
                     }
                     
                     return Ok(result);
@@ -94,7 +98,7 @@ impl StatementHandler for DivideHandler {
                 let divisor_value = interpreter.evaluate_value(div_obj.get("divisor").unwrap())?;
                 
                 // 执行除法运算
-                return Ok(type_convert::divide(&dividend_value, &divisor_value));
+                return type_convert::divide(&dividend_value, &divisor_value);
             }
             
             return Err(NjilError::ExecutionError(
@@ -107,7 +111,7 @@ impl StatementHandler for DivideHandler {
         ))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "math.divide"
     }
     
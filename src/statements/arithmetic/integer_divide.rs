@@ -0,0 +1,42 @@
+use crate::error::NjilError;
+use serde_json::Value;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert;
+
+/// 整数除法处理器，商向零取整
+pub struct IntegerDivideHandler;
+
+// 静态实例
+pub static INTEGER_DIVIDE_HANDLER: IntegerDivideHandler = IntegerDivideHandler;
+
+impl StatementHandler for IntegerDivideHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        if let Value::Object(div_obj) = value {
+            // 检查必要的字段
+            if !div_obj.contains_key("dividend") || !div_obj.contains_key("divisor") {
+                return Err(NjilError::ExecutionError(
+                    "整数除法需要dividend和divisor字段".to_string()
+                ));
+            }
+            
+            let dividend_value = interpreter.evaluate_value(div_obj.get("dividend").unwrap())?;
+            let divisor_value = interpreter.evaluate_value(div_obj.get("divisor").unwrap())?;
+            
+            // 除数为零或无法转换为数字时返回null
+            type_convert::integer_divide(&dividend_value, &divisor_value)
+        } else {
+            Err(NjilError::ExecutionError(
+                "整数除法需要一个对象参数".to_string()
+            ))
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "math.intdiv"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["math.intdiv", "intdiv", "idiv"]
+    }
+}
@@ -7,6 +7,10 @@ pub mod multiply;
 pub mod divide;
 pub mod modulo;
 pub mod compare;
+pub mod integer_divide;
+pub mod bitwise;
+pub mod bigint;
+pub mod decimal;
 
 // 重新导出所有处理器，方便其他模块使用
 pub use self::add::ADD_HANDLER;
@@ -15,6 +19,10 @@ pub use self::multiply::MULTIPLY_HANDLER;
 pub use self::divide::DIVIDE_HANDLER;
 pub use self::modulo::MODULO_HANDLER;
 pub use self::compare::COMPARE_HANDLER;
+pub use self::integer_divide::INTEGER_DIVIDE_HANDLER;
+pub use self::bitwise::{BIT_AND_HANDLER, BIT_OR_HANDLER, BIT_XOR_HANDLER, SHIFT_LEFT_HANDLER, SHIFT_RIGHT_HANDLER};
+pub use self::bigint::BIGINT_HANDLER;
+pub use self::decimal::{DECIMAL_HANDLER, DECIMAL_ADD_HANDLER, DECIMAL_SUBTRACT_HANDLER, DECIMAL_MULTIPLY_HANDLER, DECIMAL_DIVIDE_HANDLER, DECIMAL_ROUND_HANDLER, DECIMAL_FORMAT_HANDLER};
 
 // 导出所有处理器的集合，便于注册
 pub fn get_all_handlers() -> Vec<&'static dyn crate::statements::StatementHandler> {
@@ -25,5 +33,19 @@ pub fn get_all_handlers() -> Vec<&'static dyn crate::statements::StatementHandle
         &DIVIDE_HANDLER,
         &MODULO_HANDLER,
         &COMPARE_HANDLER,
+        &INTEGER_DIVIDE_HANDLER,
+        &BIT_AND_HANDLER,
+        &BIT_OR_HANDLER,
+        &BIT_XOR_HANDLER,
+        &SHIFT_LEFT_HANDLER,
+        &SHIFT_RIGHT_HANDLER,
+        &BIGINT_HANDLER,
+        &DECIMAL_HANDLER,
+        &DECIMAL_ADD_HANDLER,
+        &DECIMAL_SUBTRACT_HANDLER,
+        &DECIMAL_MULTIPLY_HANDLER,
+        &DECIMAL_DIVIDE_HANDLER,
+        &DECIMAL_ROUND_HANDLER,
+        &DECIMAL_FORMAT_HANDLER,
     ]
 } 
\ No newline at end of file
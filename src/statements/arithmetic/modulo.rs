@@ -22,31 +22,12 @@ impl StatementHandler for ModuloHandler {
             
             // 获取被除数
             let dividend_value = interpreter.evaluate_value(mod_obj.get("dividend").unwrap())?;
-            let dividend = match type_convert::to_number(&dividend_value) {
-                Some(n) => n,
-                None => return Ok(Value::Null),
-            };
             
             // 获取除数
             let divisor_value = interpreter.evaluate_value(mod_obj.get("divisor").unwrap())?;
-            let divisor = match type_convert::to_number(&divisor_value) {
-                Some(n) => n,
-                None => return Ok(Value::Null),
-            };
             
-            // 检查除数是否为零
-            if divisor == 0.0 {
-                return Ok(Value::Null);
-            }
-            
-            // 计算余数
-            let remainder = dividend % divisor;
-            
-            if let Some(num) = serde_json::Number::from_f64(remainder) {
-                Ok(Value::Number(num))
-            } else {
-                Ok(Value::Null)
-            }
+            // 计算余数，除数为零或无法转换为数字时返回null
+            type_convert::modulo(&dividend_value, &divisor_value)
         } else {
             Err(NjilError::ExecutionError(
                 "取模运算需要一个对象参数".to_string()
@@ -54,7 +35,7 @@ impl StatementHandler for ModuloHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "math.modulo"
     }
     
@@ -27,7 +27,7 @@ impl StatementHandler for MultiplyHandler {
                 let operand_value = interpreter.evaluate_value(operand)?;
                 
                 // 执行乘法运算
-                result = type_convert::multiply(&result, &operand_value);
+                result = type_convert::multiply(&result, &operand_value)?;
             }
             
             Ok(result)
@@ -38,7 +38,7 @@ impl StatementHandler for MultiplyHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "math.multiply"
     }
     
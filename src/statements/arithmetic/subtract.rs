@@ -25,6 +25,10 @@ impl StatementHandler for SubtractHandler {
             
             // 如果只有一个操作数，返回其负值
             if operands.len() == 1 {
+                // 整数的负值仍是整数
+                if type_convert::integer::as_integer(&first_operand).is_some() {
+                    return type_convert::subtract(&Value::from(0), &first_operand);
+                }
                 if let Some(n) = type_convert::to_number(&first_operand) {
                     if let Some(num) = serde_json::Number::from_f64(-n) {
                         return Ok(Value::Number(num));
@@ -41,7 +45,7 @@ impl StatementHandler for SubtractHandler {
                 let operand_value = interpreter.evaluate_value(operand)?;
                 
                 // 执行减法运算
-                result = type_convert::subtract(&result, &operand_value);
+                result = type_convert::subtract(&result, &operand_value)?;
             }
             
             return Ok(result);
@@ -75,7 +79,7 @@ impl StatementHandler for SubtractHandler {
                         let subtrahend_value = interpreter.evaluate_value(subtrahend)?;
                         
                         // 执行减法运算
-                        result = type_convert::subtract(&result, &subtrahend_value);
+                        result = type_convert::subtract(&result, &subtrahend_value)?;
                     }
                     
                     return Ok(result);
@@ -91,7 +95,7 @@ impl StatementHandler for SubtractHandler {
                 let subtrahend_value = interpreter.evaluate_value(sub_obj.get("subtrahend").unwrap())?;
                 
                 // 执行减法运算
-                return Ok(type_convert::subtract(&minuend_value, &subtrahend_value));
+                return type_convert::subtract(&minuend_value, &subtrahend_value);
             }
             
             return Err(NjilError::ExecutionError(
@@ -104,7 +108,7 @@ impl StatementHandler for SubtractHandler {
         ))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "math.subtract"
     }
     
@@ -1,10 +1,9 @@
 use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
-use crate::errortip;
 use crate::debug_println;
 use super::StatementHandler;
-use crate::utils::path::{self, PathPart};
+use crate::utils::path;
 
 /// 常量定义语句处理器
 pub struct ConstSetHandler;
@@ -41,6 +40,7 @@ impl StatementHandler for ConstSetHandler {
             // 获取常量值
             let const_value = interpreter.evaluate_value(const_obj.get("value").unwrap())?;
             debug_println!("常量 {} 的值为: {}", const_name, serde_json::to_string_pretty(&const_value).unwrap());
+            interpreter.pin_constant(&const_value);
                 
             // 检查是否为嵌套路径
             if const_name.contains('.') || const_name.contains('[') {
@@ -56,7 +56,7 @@ impl StatementHandler for ConstSetHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "const.set"
     }
 }
@@ -100,7 +100,7 @@ impl StatementHandler for ConstSetMultiHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "const.set.m"
     }
     
@@ -125,6 +125,11 @@ impl StatementHandler for ConstHandler {
         
         debug_println!("获取常量 {}", const_name);
         
+        // 模块导出的常量以 模块.名称 作为完整的常量名
+        if let Some(value) = interpreter.constants.get(const_name.as_str()) {
+            return Ok(value.clone());
+        }
+        
         // 检查是否为嵌套路径
         if const_name.contains('.') || const_name.contains('[') {
             // 处理嵌套路径的常量访问
@@ -163,7 +168,7 @@ impl StatementHandler for ConstHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "const"
     }
 }
@@ -187,6 +192,10 @@ impl StatementHandler for ConstHasHandler {
         
         debug_println!("检查常量是否存在: {}", const_name);
         
+        if interpreter.has_constant(&const_name) {
+            return Ok(Value::Bool(true));
+        }
+        
         // 检查是否为嵌套路径
         if const_name.contains('.') || const_name.contains('[') {
             // 处理嵌套路径的常量访问
@@ -222,7 +231,7 @@ impl StatementHandler for ConstHasHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "has_constant"
     }
     
@@ -1,38 +1,108 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
+use crate::errortip::control_flow as tip;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::statements::StatementHandler;
 
-/// break语句处理器
+/// break语句处理器，参数为null时结束最内层的循环，为标签时结束带有该标签的循环
 pub struct BreakHandler;
 
 // 静态实例
 pub static BREAK_HANDLER: BreakHandler = BreakHandler;
 
 impl StatementHandler for BreakHandler {
-    fn handle(&self, _interpreter: &mut Interpreter, _value: &Value) -> Result<Value, NjilError> {
-        // 直接返回LoopBreak错误，由循环处理器捕获
-        Err(NjilError::LoopBreak)
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
     }
     
-    fn name(&self) -> &'static str {
+    fn execute(&self, _interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
+        // 结束所在的语句序列，由循环处理器处理
+        Ok(Completion::Break(parse_label(self.name(), value)?))
+    }
+    
+    fn name(&self) -> &str {
         "loop.break"
     }
 }
 
-/// continue语句处理器
+/// continue语句处理器，参数为null时继续最内层的循环，为标签时继续带有该标签的循环
 pub struct ContinueHandler;
 
 // 静态实例
 pub static CONTINUE_HANDLER: ContinueHandler = ContinueHandler;
 
 impl StatementHandler for ContinueHandler {
-    fn handle(&self, _interpreter: &mut Interpreter, _value: &Value) -> Result<Value, NjilError> {
-        // 直接返回LoopContinue错误，由循环处理器捕获
-        Err(NjilError::LoopContinue)
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, _interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
+        // 结束本次迭代，由循环处理器处理
+        Ok(Completion::Continue(parse_label(self.name(), value)?))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "loop.continue"
     }
-} 
\ No newline at end of file
+}
+
+// 辅助函数：解析目标循环的标签：null、"标签" 或 {"label": "标签"}
+fn parse_label(name: &str, value: &Value) -> Result<Option<String>, NjilError> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(label) => Ok(Some(label.clone())),
+        Value::Object(obj) => match obj.get("label") {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(label)) => Ok(Some(label.clone())),
+            Some(_) => Err(NjilError::ExecutionError(tip::label_argument_invalid(name))),
+        },
+        _ => Err(NjilError::ExecutionError(tip::label_argument_invalid(name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_labeled_break_and_continue() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "pairs", "value": ""}},
+            {"loop.foreach": {"label": "rows", "collection": [1, 2, 3], "var": "row", "body": [
+                {"loop.for": {"count": 3, "var": "col", "body": [
+                    {"if": {"condition": {"expr": "col > row"}, "then": [{"loop.continue": "rows"}]}},
+                    {"if": {"condition": {"expr": "row == 3"}, "then": [{"loop.break": {"label": "rows"}}]}},
+                    {"var.set": {"name": "pairs", "value": {"string.concat": [{"var": "pairs"}, {"var": "row"}, {"var": "col"}, " "]}}}
+                ]}}
+            ]}},
+            {"return": {"var": "pairs"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("10 11 20 21 22 "));
+        
+        // 没有标签的loop.break只结束最内层的循环
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "n", "value": 0}},
+            {"loop.while": {"label": "outer", "condition": {"expr": "n < 3"}, "body": [
+                {"var.set": {"name": "n", "value": {"expr": "n + 1"}}},
+                {"loop.for": {"count": 5, "body": [{"loop.break": null}]}}
+            ]}},
+            {"return": {"var": "n"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!(3));
+    }
+    
+    #[test]
+    fn test_unknown_label() {
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.run_njis_str(r#"[
+            {"loop.for": {"label": "inner", "count": 2, "body": [{"loop.break": "outer"}]}}
+        ]"#).unwrap_err();
+        assert!(error.message().contains("找不到标签为'outer'的循环"));
+        
+        let error = interpreter.run_njis_str(r#"[{"loop.for": {"label": 1, "count": 2, "body": []}}]"#).unwrap_err();
+        assert!(error.message().contains("label必须是字符串"));
+    }
+}
@@ -1,11 +1,16 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::errortip;
 use crate::statements::StatementHandler;
-use crate::statements::handle_statement;
+use super::{execute_loop_body, loop_label};
+use super::range::build_range;
 
 /// for循环语句处理器
+///
+/// 循环count次（循环变量从0开始），或者按from、to、step从起点计数到终点，
+/// inclusive为true时包含终点，reverse为true时倒序。
 pub struct ForLoopHandler;
 
 // 静态实例
@@ -13,20 +18,20 @@ pub static FOR_LOOP_HANDLER: ForLoopHandler = ForLoopHandler;
 
 impl StatementHandler for ForLoopHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
         if let Value::Object(for_obj) = value {
             // 检查必要的字段
-            if !for_obj.contains_key("count") || !for_obj.contains_key("body") {
+            if !for_obj.contains_key("body") || !["count", "to"].iter().any(|key| for_obj.contains_key(*key)) {
                 return Err(NjilError::ExecutionError(
                     errortip::control_flow::for_missing_fields().to_string()
                 ));
             }
-
-            // 获取循环次数
-            let count_value = interpreter.evaluate_value(for_obj.get("count").unwrap())?;
-            let count = match count_value {
-                Value::Number(n) => n.as_u64().unwrap_or(0) as usize,
-                _ => return Err(NjilError::ExecutionError(errortip::control_flow::count_requires_number().to_string())),
-            };
+            
+            // 获取循环范围：count次，或者从from到to
+            let range = build_range(interpreter, for_obj)?;
             
             // 获取循环变量名（可选）
             let var_name = match for_obj.get("var") {
@@ -36,64 +41,33 @@ impl StatementHandler for ForLoopHandler {
             };
             
             let body = for_obj.get("body").unwrap();
+            let label = loop_label(for_obj)?;
             
-            // 执行循环
+            // 执行循环，循环变量只在循环体的块作用域中可见
             let mut last_result = Value::Null;
-            for i in 0..count {
-                // 如果指定了循环变量，设置它
-                if let Some(ref name) = var_name {
-                    interpreter.variables.insert(name.clone(), Value::Number(i.into()));
-                }
+            let pins = interpreter.pin_mark();
+            let mut scope = None;
+            for item in range.iter() {
+                let finished = execute_loop_body(interpreter, label, &mut scope, body, &mut last_result, |interpreter| {
+                    // 如果指定了循环变量，设置它
+                    if let Some(ref name) = var_name {
+                        interpreter.declare_variable(name.clone(), item);
+                    }
+                })?;
                 
-                // 执行循环体
-                match body {
-                    Value::Array(statements) => {
-                        for stmt in statements {
-                            // 检查是否有返回值
-                            if interpreter.is_returning() {
-                                return Ok(Value::Null);
-                            }
-                            
-                            // 执行语句
-                            match handle_statement(interpreter, stmt) {
-                                Ok(result) => {
-                                    last_result = result;
-                                },
-                                Err(NjilError::LoopBreak) => {
-                                    return Ok(Value::Null);
-                                },
-                                Err(NjilError::LoopContinue) => {
-                                    break;
-                                },
-                                Err(e) => return Err(e),
-                            }
-                        }
-                    },
-                    _ => {
-                        // 执行单个语句
-                        match handle_statement(interpreter, body) {
-                            Ok(result) => {
-                                last_result = result;
-                            },
-                            Err(NjilError::LoopBreak) => {
-                                return Ok(Value::Null);
-                            },
-                            Err(NjilError::LoopContinue) => {
-                                continue;
-                            },
-                            Err(e) => return Err(e),
-                        }
-                    },
+                if let Some(completion) = finished {
+                    return Ok(completion);
                 }
+                interpreter.release_pins(pins, Some(&last_result));
             }
             
-            Ok(last_result)
+            Ok(Completion::Normal(last_result))
         } else {
             Err(NjilError::ExecutionError(errortip::control_flow::for_requires_object().to_string()))
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "loop.for"
     }
 } 
\ No newline at end of file
@@ -1,9 +1,13 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::errortip;
+use crate::generator::{generator_id, GeneratorStep};
 use crate::statements::StatementHandler;
-use crate::statements::handle_statement;
+use crate::statements::var::destructure;
+use crate::utils::type_convert::{as_range, is_range};
+use super::{execute_loop_body, loop_label};
 
 /// foreach循环语句处理器
 pub struct ForeachLoopHandler;
@@ -13,6 +17,10 @@ pub static FOREACH_LOOP_HANDLER: ForeachLoopHandler = ForeachLoopHandler;
 
 impl StatementHandler for ForeachLoopHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
         if let Value::Object(foreach_obj) = value {
             // 检查必要的字段
             if !foreach_obj.contains_key("collection") || !foreach_obj.contains_key("body") || !foreach_obj.contains_key("var") {
@@ -20,13 +28,13 @@ impl StatementHandler for ForeachLoopHandler {
                     errortip::control_flow::foreach_missing_fields().to_string()
                 ));
             }
-
+            
             // 获取集合
             let collection_value = interpreter.evaluate_value(foreach_obj.get("collection").unwrap())?;
             
-            // 获取变量名
-            let var_name = match foreach_obj.get("var") {
-                Some(Value::String(name)) => name.clone(),
+            // 获取循环变量，可以是变量名或解构模式
+            let var_pattern = match foreach_obj.get("var") {
+                Some(pattern @ (Value::String(_) | Value::Array(_) | Value::Object(_))) => pattern,
                 _ => return Err(NjilError::ExecutionError(errortip::control_flow::var_name_requires_string().to_string())),
             };
             
@@ -38,132 +46,95 @@ impl StatementHandler for ForeachLoopHandler {
             };
             
             let body = foreach_obj.get("body").unwrap();
+            let label = loop_label(foreach_obj)?;
             
-            // 执行循环
-            let mut last_result = Value::Null;
+            // 数组按元素遍历，范围和生成器按需产生每个元素，对象的每一项是包含key和value的对象
+            let mut items = match collection_value {
+                Value::Array(arr) => Items::Values(Box::new(arr.into_iter())),
+                ref range if is_range(range) => Items::Values(Box::new(as_range(range).unwrap().iter())),
+                ref generator if generator_id(generator).is_some() => Items::Generator(collection_value),
+                Value::Object(obj) => Items::Values(Box::new(obj.into_iter().map(|(key, value)| {
+                    let mut entry = serde_json::Map::new();
+                    entry.insert("key".to_string(), Value::String(key));
+                    entry.insert("value".to_string(), value);
+                    Value::Object(entry)
+                }))),
+                _ => return Err(NjilError::ExecutionError(errortip::control_flow::collection_requires_array_or_object().to_string())),
+            };
             
-            match collection_value {
-                Value::Array(arr) => {
-                    // 遍历数组
-                    for (i, item) in arr.iter().enumerate() {
+            // 执行循环，循环变量和索引变量只在循环体的块作用域中可见
+            let mut last_result = Value::Null;
+            let pins = interpreter.pin_mark();
+            let mut i: usize = 0;
+            let mut scope = None;
+            let outcome = loop {
+                let item = match items.next(interpreter) {
+                    Ok(Some(item)) => item,
+                    Ok(None) => break Ok(Completion::Normal(last_result)),
+                    Err(e) => break Err(e),
+                };
+                let finished = bind_loop_var(interpreter, var_pattern, item).and_then(|bindings| {
+                    execute_loop_body(interpreter, label, &mut scope, body, &mut last_result, |interpreter| {
                         // 设置循环变量
-                        interpreter.variables.insert(var_name.clone(), item.clone());
-                        
-                        // 设置索引变量（如果有）
-                        if let Some(ref idx_var) = index_var {
-                            interpreter.variables.insert(idx_var.clone(), Value::Number(i.into()));
-                        }
-                        
-                        // 执行循环体
-                        match body {
-                            Value::Array(statements) => {
-                                for stmt in statements {
-                                    // 检查是否有返回值
-                                    if interpreter.is_returning() {
-                                        return Ok(Value::Null);
-                                    }
-                                    
-                                    // 执行语句
-                                    match handle_statement(interpreter, stmt) {
-                                        Ok(result) => {
-                                            last_result = result;
-                                        },
-                                        Err(NjilError::LoopBreak) => {
-                                            return Ok(Value::Null);
-                                        },
-                                        Err(NjilError::LoopContinue) => {
-                                            break;
-                                        },
-                                        Err(e) => return Err(e),
-                                    }
-                                }
-                            },
-                            _ => {
-                                // 执行单个语句
-                                match handle_statement(interpreter, body) {
-                                    Ok(result) => {
-                                        last_result = result;
-                                    },
-                                    Err(NjilError::LoopBreak) => {
-                                        return Ok(Value::Null);
-                                    },
-                                    Err(NjilError::LoopContinue) => {
-                                        continue;
-                                    },
-                                    Err(e) => return Err(e),
-                                }
-                            },
+                        for (name, value) in bindings {
+                            interpreter.declare_variable(name, value);
                         }
-                    }
-                },
-                Value::Object(obj) => {
-                    // 遍历对象
-                    for (i, (key, value)) in obj.iter().enumerate() {
-                        // 创建包含键和值的对象
-                        let mut entry = serde_json::Map::new();
-                        entry.insert("key".to_string(), Value::String(key.clone()));
-                        entry.insert("value".to_string(), value.clone());
-                        
-                        // 设置循环变量
-                        interpreter.variables.insert(var_name.clone(), Value::Object(entry));
-                        
+                    
                         // 设置索引变量（如果有）
                         if let Some(ref idx_var) = index_var {
-                            interpreter.variables.insert(idx_var.clone(), Value::Number(i.into()));
-                        }
-                        
-                        // 执行循环体
-                        match body {
-                            Value::Array(statements) => {
-                                for stmt in statements {
-                                    // 检查是否有返回值
-                                    if interpreter.is_returning() {
-                                        return Ok(Value::Null);
-                                    }
-                                    
-                                    // 执行语句
-                                    match handle_statement(interpreter, stmt) {
-                                        Ok(result) => {
-                                            last_result = result;
-                                        },
-                                        Err(NjilError::LoopBreak) => {
-                                            return Ok(Value::Null);
-                                        },
-                                        Err(NjilError::LoopContinue) => {
-                                            break;
-                                        },
-                                        Err(e) => return Err(e),
-                                    }
-                                }
-                            },
-                            _ => {
-                                // 执行单个语句
-                                match handle_statement(interpreter, body) {
-                                    Ok(result) => {
-                                        last_result = result;
-                                    },
-                                    Err(NjilError::LoopBreak) => {
-                                        return Ok(Value::Null);
-                                    },
-                                    Err(NjilError::LoopContinue) => {
-                                        continue;
-                                    },
-                                    Err(e) => return Err(e),
-                                }
-                            },
+                            interpreter.declare_variable(idx_var.clone(), Value::Number(i.into()));
                         }
-                    }
-                },
-                _ => return Err(NjilError::ExecutionError(errortip::control_flow::collection_requires_array_or_object().to_string())),
-            }
+                    })
+                });
+                
+                match finished {
+                    Ok(None) => {
+                        interpreter.release_pins(pins, Some(&last_result));
+                        i += 1;
+                    },
+                    Ok(Some(completion)) => break Ok(completion),
+                    Err(e) => break Err(e),
+                }
+            };
             
-            Ok(last_result)
+            // 循环提前结束时关闭生成器，执行生成器中的finally和defer；循环体的错误优先
+            if let Items::Generator(generator) = &items {
+                let closed = interpreter.close_generator(generator);
+                return outcome.and_then(|completion| closed.map(|_| completion));
+            }
+            outcome
         } else {
             Err(NjilError::ExecutionError(errortip::control_flow::foreach_requires_object().to_string()))
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "loop.foreach"
     }
-} 
\ No newline at end of file
+}
+
+// 辅助函数：按循环变量的模式解构当前元素
+fn bind_loop_var(interpreter: &mut Interpreter, pattern: &Value, item: Value) -> Result<Vec<(String, Value)>, NjilError> {
+    let mut bindings = Vec::new();
+    destructure(interpreter, pattern, Some(item), &mut bindings)?;
+    Ok(bindings)
+}
+
+// 循环依次取出的元素：数组、范围或对象的元素，或者生成器产生的值
+enum Items {
+    Values(Box<dyn Iterator<Item = Value>>),
+    Generator(Value),
+}
+
+impl Items {
+    // 取出下一个元素，没有更多元素时返回None
+    fn next(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, NjilError> {
+        match self {
+            Items::Values(values) => Ok(values.next()),
+            Items::Generator(generator) => match interpreter.resume_generator(generator)? {
+                GeneratorStep::Yielded(value) => Ok(Some(value)),
+                GeneratorStep::Returned(_) => Ok(None),
+            },
+        }
+    }
+}
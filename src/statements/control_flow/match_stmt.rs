@@ -0,0 +1,360 @@
+use crate::completion::Completion;
+use crate::error::NjilError;
+use serde_json::{Map, Value};
+use crate::interpreter::Interpreter;
+use crate::errortip::control_flow as tip;
+use crate::scope::ScopeKind;
+use crate::statements::{StatementHandler, block_statements, execute_block, execute_statements};
+use crate::statements::pattern::{self, ArrayPattern, REST_PREFIX};
+use crate::utils::type_convert;
+use super::if_stmt::evaluate_condition;
+
+/// 模式匹配语句处理器
+///
+/// 依次尝试cases中的分支，执行第一个模式匹配且when条件成立的分支，
+/// 都不匹配时执行default。模式绑定的变量只在分支中可见。
+pub struct MatchHandler;
+
+// 静态实例
+pub static MATCH_HANDLER: MatchHandler = MatchHandler;
+
+impl StatementHandler for MatchHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
+        let Value::Object(match_obj) = value else {
+            return Err(NjilError::ExecutionError(tip::match_requires_object().to_string()));
+        };
+        let (Some(subject), Some(Value::Array(cases))) = (match_obj.get("value"), match_obj.get("cases")) else {
+            return Err(NjilError::ExecutionError(tip::match_missing_fields().to_string()));
+        };
+        
+        let subject = interpreter.evaluate_value(subject)?;
+        
+        for case in cases {
+            let Some(pattern) = case.get("pattern") else {
+                return Err(NjilError::ExecutionError(tip::match_case_requires_pattern().to_string()));
+            };
+            
+            let mut bindings = Vec::new();
+            if !match_pattern(interpreter, pattern, &subject, &mut bindings)? {
+                continue;
+            }
+            
+            // 在块作用域中声明绑定的变量，然后检查when条件并执行分支
+            let matched = interpreter.with_scope(ScopeKind::Block, |interpreter| {
+                for (name, value) in bindings {
+                    interpreter.declare_variable(name, value);
+                }
+                
+                if let Some(guard) = case.get("when") {
+                    if !evaluate_condition(interpreter, guard)? {
+                        return Ok(None);
+                    }
+                }
+                
+                match case.get("body") {
+                    Some(body) => execute_statements(interpreter, block_statements(body)).map(Some),
+                    None => Ok(Some(Completion::Normal(Value::Null))),
+                }
+            })?;
+            
+            if let Some(result) = matched {
+                return Ok(result);
+            }
+        }
+        
+        // 没有匹配的分支，执行default分支
+        match match_obj.get("default") {
+            Some(default) => execute_block(interpreter, default),
+            None => Ok(Completion::Normal(Value::Null)),
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "match"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["switch"]
+    }
+}
+
+/// 检查值是否匹配模式，匹配过程中绑定的变量追加到bindings
+///
+/// - 标量字面量：值相等（数字按数值比较）
+/// - 数组：逐项匹配，最后一项可以是 `"...变量名"` 或 `{"$rest": "变量名"}`，收集剩余的元素
+/// - 普通对象：值是对象，且包含模式中的每个字段，字段值逐个匹配，键 `"..."` 收集其余字段
+/// - 所有键都以 `$` 开头的对象：模式操作符，多个操作符需要同时满足
+///
+/// 剩余元素和 `$default` 的写法与解构赋值相同：缺少的元素或字段的模式带有 `$default` 时，
+/// 用默认值代替缺少的值继续匹配。
+fn match_pattern(interpreter: &mut Interpreter, pattern: &Value, value: &Value, bindings: &mut Vec<(String, Value)>) -> Result<bool, NjilError> {
+    match pattern {
+        Value::Object(operators) if is_operator_pattern(operators) => {
+            for (operator, argument) in operators {
+                if !match_operator(interpreter, operator, argument, value, bindings)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        },
+        Value::Object(fields) => {
+            let Value::Object(object) = value else {
+                return Ok(false);
+            };
+            for (key, field_pattern) in fields.iter().filter(|(key, _)| *key != REST_PREFIX) {
+                let matched = match object.get(key) {
+                    Some(field) => match_pattern(interpreter, field_pattern, field, bindings)?,
+                    None => match_missing(interpreter, field_pattern, bindings)?,
+                };
+                if !matched {
+                    return Ok(false);
+                }
+            }
+            
+            // 其余字段收集到一个对象中，字符串是变量名，其他模式与这个对象匹配
+            match fields.get(REST_PREFIX) {
+                Some(rest) => {
+                    let others: Map<String, Value> = object.iter()
+                        .filter(|(key, _)| !fields.contains_key(*key))
+                        .map(|(key, field)| (key.clone(), field.clone()))
+                        .collect();
+                    match rest {
+                        Value::String(name) => {
+                            bindings.push((name.clone(), Value::Object(others)));
+                            Ok(true)
+                        },
+                        rest => match_pattern(interpreter, rest, &Value::Object(others), bindings),
+                    }
+                },
+                None => Ok(true),
+            }
+        },
+        Value::Array(items) => {
+            let Value::Array(values) = value else {
+                return Ok(false);
+            };
+            
+            // 最后一项为剩余项时收集剩余元素
+            let ArrayPattern { items, rest } = pattern::split_array(items)?;
+            if rest.is_none() && values.len() > items.len() {
+                return Ok(false);
+            }
+            
+            for (i, item_pattern) in items.iter().enumerate() {
+                let matched = match values.get(i) {
+                    Some(item) => match_pattern(interpreter, item_pattern, item, bindings)?,
+                    None => match_missing(interpreter, item_pattern, bindings)?,
+                };
+                if !matched {
+                    return Ok(false);
+                }
+            }
+            
+            if let Some(Some(name)) = rest {
+                bindings.push((name.to_string(), Value::Array(values.get(items.len()..).unwrap_or_default().to_vec())));
+            }
+            Ok(true)
+        },
+        literal => Ok(values_equal(literal, value)),
+    }
+}
+
+// 辅助函数：匹配缺少的数组元素或对象字段，只有带 `$default` 的模式用默认值继续匹配
+fn match_missing(interpreter: &mut Interpreter, pattern: &Value, bindings: &mut Vec<(String, Value)>) -> Result<bool, NjilError> {
+    match pattern::default_of(pattern) {
+        Some(default) => {
+            let default = interpreter.evaluate_value(default)?;
+            match_pattern(interpreter, pattern, &default, bindings)
+        },
+        None => Ok(false),
+    }
+}
+
+// 辅助函数：判断对象是否是模式操作符
+fn is_operator_pattern(obj: &Map<String, Value>) -> bool {
+    !obj.is_empty() && obj.keys().all(|key| key.starts_with('$'))
+}
+
+// 辅助函数：检查单个模式操作符
+fn match_operator(interpreter: &mut Interpreter, operator: &str, argument: &Value, value: &Value, bindings: &mut Vec<(String, Value)>) -> Result<bool, NjilError> {
+    match operator {
+        // 匹配任意值并绑定到变量
+        "$bind" => {
+            let Value::String(name) = argument else {
+                return Err(NjilError::ExecutionError(tip::pattern_name_requires_string("$bind")));
+            };
+            bindings.push((name.clone(), value.clone()));
+            Ok(true)
+        },
+        "$any" => Ok(true),
+        // 嵌套模式，通常与$default一起使用
+        "$pattern" => match_pattern(interpreter, argument, value, bindings),
+        // 默认值只在值不存在时使用，见match_missing
+        "$default" => Ok(true),
+        // 与表达式的值比较
+        "$eq" => {
+            let expected = interpreter.evaluate_value(argument)?;
+            Ok(values_equal(&expected, value))
+        },
+        "$type" => {
+            let types = match argument {
+                Value::Array(types) => types.as_slice(),
+                _ => std::slice::from_ref(argument),
+            };
+            for expected in types {
+                let expected = expected.as_str().unwrap_or_default();
+                match type_convert::has_type(value, expected) {
+                    Some(true) => return Ok(true),
+                    Some(false) => {},
+                    None => return Err(NjilError::ExecutionError(tip::pattern_unknown_type(expected))),
+                }
+            }
+            Ok(false)
+        },
+        // 闭区间，边界为null时不限制
+        "$range" => {
+            let Value::Array(bounds) = argument else {
+                return Err(NjilError::ExecutionError(tip::pattern_range_invalid().to_string()));
+            };
+            let [min, max] = bounds.as_slice() else {
+                return Err(NjilError::ExecutionError(tip::pattern_range_invalid().to_string()));
+            };
+            let Some(number) = value.as_f64() else {
+                return Ok(false);
+            };
+            for (bound, is_min) in [(min, true), (max, false)] {
+                match interpreter.evaluate_value(bound)? {
+                    Value::Null => {},
+                    Value::Number(bound) => {
+                        let bound = bound.as_f64().unwrap_or_default();
+                        if (is_min && number < bound) || (!is_min && number > bound) {
+                            return Ok(false);
+                        }
+                    },
+                    _ => return Err(NjilError::ExecutionError(tip::pattern_range_invalid().to_string())),
+                }
+            }
+            Ok(true)
+        },
+        // 正则表达式，命名捕获组绑定到同名变量
+        "$regex" => {
+            let (pattern, flags) = match argument {
+                Value::String(pattern) => (pattern.as_str(), ""),
+                Value::Object(regex) => match (regex.get("pattern"), regex.get("flags")) {
+                    (Some(Value::String(pattern)), None) => (pattern.as_str(), ""),
+                    (Some(Value::String(pattern)), Some(Value::String(flags))) => (pattern.as_str(), flags.as_str()),
+                    _ => return Err(NjilError::ExecutionError(tip::pattern_regex_invalid().to_string())),
+                },
+                _ => return Err(NjilError::ExecutionError(tip::pattern_regex_invalid().to_string())),
+            };
+            let Value::String(text) = value else {
+                return Ok(false);
+            };
+            let regex = interpreter.compile_regex(pattern, flags)?;
+            let Some(captures) = regex.captures(text) else {
+                return Ok(false);
+            };
+            for name in regex.capture_names().flatten() {
+                let captured = captures.name(name).map(|m| Value::String(m.as_str().to_string())).unwrap_or(Value::Null);
+                bindings.push((name.to_string(), captured));
+            }
+            Ok(true)
+        },
+        "$rest" => Err(NjilError::ExecutionError(tip::pattern_rest_not_last().to_string())),
+        _ => Err(NjilError::ExecutionError(tip::unknown_pattern(operator))),
+    }
+}
+
+// 辅助函数：比较字面量，整数和定点小数精确比较，其他数字按数值比较，其他值要求类型和内容都相同
+fn values_equal(expected: &Value, value: &Value) -> bool {
+    if let Some(ordering) = type_convert::integer::compare_integers(expected, value)
+        .or_else(|| type_convert::decimal::compare_decimals(expected, value)) {
+        return ordering.is_eq();
+    }
+    match (expected, value) {
+        (Value::Number(expected), Value::Number(value)) => expected.as_f64() == value.as_f64(),
+        _ => expected == value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    fn run_match(interpreter: &mut Interpreter, subject: Value) -> Value {
+        let statement = json!({
+            "value": subject,
+            "cases": [
+                {"pattern": 0, "body": "零"},
+                {"pattern": {"$range": [1, 9]}, "when": false, "body": "不会匹配"},
+                {"pattern": {"$type": "integer", "$range": [1, 9]}, "body": "个位数"},
+                {"pattern": {"$regex": "^(?P<user>\\w+)@(?P<host>\\w+)$"}, "body": {"string.concat": [{"var": "user"}, " at ", {"var": "host"}]}},
+                {"pattern": ["add", {"$bind": "x"}, {"$rest": "others"}], "body": {"string.concat": ["加 ", {"var": "x"}, " 剩余 ", {"var": "others"}]}},
+                {"pattern": {"kind": "user", "age": {"$bind": "age"}}, "when": {"math.compare": {"left": {"var": "age"}, "op": ">=", "right": 18}}, "body": "成年用户"},
+                {"pattern": {"kind": "user"}, "body": "未成年用户"}
+            ],
+            "default": "其他"
+        });
+        MATCH_HANDLER.handle(interpreter, &statement).unwrap()
+    }
+    
+    #[test]
+    fn test_match_patterns() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(run_match(&mut interpreter, json!(0)), json!("零"));
+        assert_eq!(run_match(&mut interpreter, json!(7)), json!("个位数"));
+        assert_eq!(run_match(&mut interpreter, json!(7.5)), json!("其他"));
+        assert_eq!(run_match(&mut interpreter, json!("alice@example")), json!("alice at example"));
+        assert_eq!(run_match(&mut interpreter, json!(["add", 1, 2, 3])), json!("加 1 剩余 2, 3"));
+        assert_eq!(run_match(&mut interpreter, json!(["add"])), json!("其他"));
+        assert_eq!(run_match(&mut interpreter, json!({"kind": "user", "age": 30, "name": "a"})), json!("成年用户"));
+        assert_eq!(run_match(&mut interpreter, json!({"kind": "user", "age": 3})), json!("未成年用户"));
+        assert_eq!(run_match(&mut interpreter, json!("0")), json!("其他"));
+        
+        // 绑定的变量只在分支中可见
+        assert!(interpreter.get_variable("age").is_none());
+    }
+    
+    #[test]
+    fn test_match_invalid_pattern() {
+        let mut interpreter = Interpreter::new();
+        let statement = json!({"value": 1, "cases": [{"pattern": {"$unknown": 1}}]});
+        assert!(MATCH_HANDLER.handle(&mut interpreter, &statement).is_err());
+        let statement = json!({"value": [1, 1], "cases": [{"pattern": [{"$rest": "r"}, 1]}]});
+        assert!(MATCH_HANDLER.handle(&mut interpreter, &statement).is_err());
+        let statement = json!({"value": [1, 1], "cases": [{"pattern": ["...r", 1]}]});
+        assert!(MATCH_HANDLER.handle(&mut interpreter, &statement).is_err());
+    }
+    
+    #[test]
+    fn test_match_accepts_destructuring_forms() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"match": {"value": [1, 2, 3], "cases": [{"pattern": [1, "...rest"], "body": {"var": "rest"}}]}}
+        ]"#).unwrap();
+        assert_eq!(result, json!([2, 3]));
+        
+        // 与解构赋值相同的模式在match中绑定相同的变量
+        let pattern = json!({"name": {"$bind": "n"}, "tags": [{"$bind": "first"}, "..."], "age": {"$bind": "a", "$default": 18}, "...": "others"});
+        let subject = json!({"name": "alice", "tags": ["x", "y"], "city": "paris"});
+        let summary = json!({"string.concat": [{"var": "n"}, " ", {"var": "first"}, " ", {"var": "a"}, " ", {"json.get": {"object": {"var": "others"}, "key": "city"}}]});
+        let statement = json!({"value": subject, "cases": [{"pattern": pattern, "body": summary}]});
+        assert_eq!(MATCH_HANDLER.handle(&mut interpreter, &statement).unwrap(), json!("alice x 18 paris"));
+        let destructured = interpreter.execute_njis(&[json!({"var.set": {"pattern": pattern, "value": subject}}), summary]).unwrap();
+        assert_eq!(destructured, json!("alice x 18 paris"));
+        
+        let statement = json!({
+            "value": [1],
+            "cases": [
+                {"pattern": [1, {"$bind": "b"}], "body": "缺少元素"},
+                {"pattern": [1, {"$pattern": {"$type": "integer", "$bind": "b"}, "$default": 2}], "body": {"var": "b"}}
+            ]
+        });
+        assert_eq!(MATCH_HANDLER.handle(&mut interpreter, &statement).unwrap(), json!(2));
+    }
+}
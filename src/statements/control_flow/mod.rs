@@ -1,11 +1,21 @@
 // 控制流模块 - 导出所有控制流语句处理器
 
+use serde_json::{Map, Value};
+use crate::completion::Completion;
+use crate::error::NjilError;
+use crate::errortip;
+use crate::interpreter::Interpreter;
+use crate::scope::ScopeRef;
+use crate::statements::{block_statements, execute_statement};
+
 // 子模块
 pub mod if_stmt;
 pub mod while_loop;
 pub mod for_loop;
 pub mod foreach_loop;
 pub mod break_continue;
+pub mod match_stmt;
+pub mod range;
 
 // 重新导出所有控制流语句处理器，方便其他模块使用
 pub use self::if_stmt::IF_HANDLER;
@@ -13,6 +23,8 @@ pub use self::while_loop::WHILE_LOOP_HANDLER;
 pub use self::for_loop::FOR_LOOP_HANDLER;
 pub use self::foreach_loop::FOREACH_LOOP_HANDLER;
 pub use self::break_continue::{BREAK_HANDLER, CONTINUE_HANDLER};
+pub use self::match_stmt::MATCH_HANDLER;
+pub use self::range::RANGE_HANDLER;
 
 // 导出所有处理器的集合，便于注册
 pub fn get_all_handlers() -> Vec<&'static dyn crate::statements::StatementHandler> {
@@ -23,5 +35,60 @@ pub fn get_all_handlers() -> Vec<&'static dyn crate::statements::StatementHandle
         &FOREACH_LOOP_HANDLER,
         &BREAK_HANDLER,
         &CONTINUE_HANDLER,
+        &MATCH_HANDLER,
+        &RANGE_HANDLER,
     ]
-} 
\ No newline at end of file
+} 
+/// 读取循环的可选标签
+pub(crate) fn loop_label(loop_obj: &Map<String, Value>) -> Result<Option<&str>, NjilError> {
+    match loop_obj.get("label") {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(label)) => Ok(Some(label.as_str())),
+        Some(_) => Err(NjilError::ExecutionError(errortip::control_flow::label_requires_string().to_string())),
+    }
+}
+
+/// 在块作用域中执行一次循环体
+///
+/// `label` 是这个循环的标签，`scope` 保存循环体的块作用域，没有被捕获时在迭代之间复用，
+/// `declare` 用于在块作用域中声明循环变量。正常结束的语句的值写入 `last_result`。
+/// 返回 `None` 表示继续下一次迭代（包括指向这个循环的 loop.continue）；返回 `Some` 表示循环应当结束：
+/// 指向这个循环的 loop.break 使循环以null正常结束，return 和指向外层循环的信号继续交给外层处理。
+pub(crate) fn execute_loop_body<F>(
+    interpreter: &mut Interpreter,
+    label: Option<&str>,
+    scope: &mut Option<ScopeRef>,
+    body: &Value,
+    last_result: &mut Value,
+    declare: F,
+) -> Result<Option<Completion>, NjilError>
+where
+    F: FnOnce(&mut Interpreter),
+{
+    // 每次迭代都计入执行策略的语句数，避免空循环体绕过限制
+    interpreter.check_step()?;
+    
+    interpreter.with_loop_scope(scope, |interpreter| {
+        declare(interpreter);
+        
+        for stmt in block_statements(body) {
+            match execute_statement(interpreter, stmt)? {
+                Completion::Normal(result) => {
+                    *last_result = result;
+                },
+                Completion::Continue(target) if targets(target.as_deref(), label) => break,
+                Completion::Break(target) if targets(target.as_deref(), label) => {
+                    return Ok(Some(Completion::Normal(Value::Null)));
+                },
+                abrupt => return Ok(Some(abrupt)),
+            }
+        }
+        
+        Ok(None)
+    })
+}
+
+// 辅助函数：loop.break或loop.continue是否指向这个循环，没有标签时指向最内层的循环
+fn targets(target: Option<&str>, label: Option<&str>) -> bool {
+    target.is_none() || target == label
+}
@@ -0,0 +1,117 @@
+use crate::error::NjilError;
+use serde_json::{Map, Value};
+use crate::interpreter::Interpreter;
+use crate::errortip;
+use crate::errortip::range as tip;
+use crate::statements::StatementHandler;
+use crate::utils::type_convert::{Range, range_value};
+
+/// 范围语句处理器，返回惰性的等差数列，可以交给loop.foreach和数组操作使用
+///
+/// 参数可以是元素个数、`[起点, 终点]`、`[起点, 终点, 步长]`，
+/// 或包含from、to、step、inclusive、reverse字段的对象。终点默认不包含在内。
+pub struct RangeHandler;
+
+// 静态实例
+pub static RANGE_HANDLER: RangeHandler = RangeHandler;
+
+impl StatementHandler for RangeHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let range = match value {
+            Value::Object(obj) if !crate::statements::is_statement(interpreter, obj) => build_range(interpreter, obj)?,
+            Value::Array(bounds) => {
+                let bounds = bounds.iter()
+                    .map(|bound| interpreter.evaluate_value(bound))
+                    .collect::<Result<Vec<Value>, NjilError>>()?;
+                match bounds.as_slice() {
+                    [from, to] => Range::new(from, to, None, false)?,
+                    [from, to, step] => Range::new(from, to, Some(step), false)?,
+                    _ => return Err(NjilError::ExecutionError(tip::arguments().to_string())),
+                }
+            },
+            other => match interpreter.evaluate_value(other)? {
+                Value::Number(count) => Range::count(count.as_u64().ok_or_else(|| NjilError::ExecutionError(tip::arguments().to_string()))?),
+                _ => return Err(NjilError::ExecutionError(tip::arguments().to_string())),
+            },
+        };
+        Ok(range_value(&range))
+    }
+    
+    fn name(&self) -> &str {
+        "range"
+    }
+}
+
+/// 按from、to、step、inclusive、reverse字段（或count字段）创建范围，loop.for也使用这个函数
+///
+/// from默认为0；没有step时，to小于from则倒序计数。reverse为true时按相反的顺序产生同样的元素。
+pub(crate) fn build_range(interpreter: &mut Interpreter, obj: &Map<String, Value>) -> Result<Range, NjilError> {
+    let range = if let Some(count) = obj.get("count") {
+        match interpreter.evaluate_value(count)? {
+            Value::Number(n) => Range::count(n.as_u64().unwrap_or(0)),
+            _ => return Err(NjilError::ExecutionError(errortip::control_flow::count_requires_number().to_string())),
+        }
+    } else {
+        let Some(to) = obj.get("to") else {
+            return Err(NjilError::ExecutionError(tip::arguments().to_string()));
+        };
+        let from = match obj.get("from") {
+            Some(from) => interpreter.evaluate_value(from)?,
+            None => Value::from(0),
+        };
+        let to = interpreter.evaluate_value(to)?;
+        let step = match obj.get("step") {
+            Some(step) => Some(interpreter.evaluate_value(step)?),
+            None => None,
+        };
+        let inclusive = evaluate_flag(interpreter, obj, "inclusive")?;
+        Range::new(&from, &to, step.as_ref(), inclusive)?
+    };
+    
+    if evaluate_flag(interpreter, obj, "reverse")? {
+        Ok(range.reversed())
+    } else {
+        Ok(range)
+    }
+}
+
+// 辅助函数：求值可选的布尔字段，默认为false
+fn evaluate_flag(interpreter: &mut Interpreter, obj: &Map<String, Value>, name: &str) -> Result<bool, NjilError> {
+    match obj.get(name) {
+        None => Ok(false),
+        Some(flag) => match interpreter.evaluate_value(flag)? {
+            Value::Bool(flag) => Ok(flag),
+            Value::Null => Ok(false),
+            _ => Err(NjilError::ExecutionError(tip::flag_requires_bool(name))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_for_and_foreach_over_ranges() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "items", "value": ""}},
+            {"loop.for": {"var": "i", "from": 10, "to": 0, "step": -3, "body": [
+                {"var.set": {"name": "items", "value": {"string.concat": [{"var": "items"}, {"var": "i"}, " "]}}}
+            ]}},
+            {"loop.for": {"var": "x", "from": 0, "to": 1, "step": 0.5, "inclusive": true, "reverse": true, "body": [
+                {"var.set": {"name": "items", "value": {"string.concat": [{"var": "items"}, {"var": "x"}, " "]}}}
+            ]}},
+            {"loop.foreach": {"collection": {"range": [1, 1000000000000]}, "var": "n", "body": [
+                {"if": {"condition": {"expr": "n > 2"}, "then": [{"loop.break": null}]}},
+                {"var.set": {"name": "items", "value": {"string.concat": [{"var": "items"}, {"var": "n"}, " "]}}}
+            ]}},
+            {"return": {"var": "items"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("10 7 4 1 1 0.5 0 1 2 "));
+        
+        let error = interpreter.run_njis_str(r#"[{"loop.for": {"from": 0, "to": 3, "step": 0, "body": []}}]"#).unwrap_err();
+        assert!(error.message().contains("step不能为0"));
+    }
+}
@@ -1,9 +1,10 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::errortip;
 use crate::statements::StatementHandler;
-use crate::statements::handle_statement;
+use super::{execute_loop_body, loop_label};
 
 /// while循环语句处理器
 pub struct WhileLoopHandler;
@@ -13,6 +14,10 @@ pub static WHILE_LOOP_HANDLER: WhileLoopHandler = WhileLoopHandler;
 
 impl StatementHandler for WhileLoopHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
         if let Value::Object(while_obj) = value {
             // 检查必要的字段
             if !while_obj.contains_key("condition") || !while_obj.contains_key("body") {
@@ -23,9 +28,12 @@ impl StatementHandler for WhileLoopHandler {
 
             let condition = while_obj.get("condition").unwrap();
             let body = while_obj.get("body").unwrap();
+            let label = loop_label(while_obj)?;
             
             // 执行循环
             let mut last_result = Value::Null;
+            let pins = interpreter.pin_mark();
+            let mut scope = None;
             loop {
                 // 检查条件
                 let condition_result = interpreter.evaluate_value(condition)?;
@@ -43,54 +51,19 @@ impl StatementHandler for WhileLoopHandler {
                 }
                 
                 // 执行循环体
-                match body {
-                    Value::Array(statements) => {
-                        for stmt in statements {
-                            // 检查是否有返回值
-                            if interpreter.is_returning() {
-                                return Ok(Value::Null);
-                            }
-                            
-                            // 执行语句
-                            match handle_statement(interpreter, stmt) {
-                                Ok(result) => {
-                                    last_result = result;
-                                },
-                                Err(NjilError::LoopBreak) => {
-                                    return Ok(Value::Null);
-                                },
-                                Err(NjilError::LoopContinue) => {
-                                    break;
-                                },
-                                Err(e) => return Err(e),
-                            }
-                        }
-                    },
-                    _ => {
-                        // 执行单个语句
-                        match handle_statement(interpreter, body) {
-                            Ok(result) => {
-                                last_result = result;
-                            },
-                            Err(NjilError::LoopBreak) => {
-                                return Ok(Value::Null);
-                            },
-                            Err(NjilError::LoopContinue) => {
-                                continue;
-                            },
-                            Err(e) => return Err(e),
-                        }
-                    },
+                if let Some(completion) = execute_loop_body(interpreter, label, &mut scope, body, &mut last_result, |_| {})? {
+                    return Ok(completion);
                 }
+                interpreter.release_pins(pins, Some(&last_result));
             }
             
-            Ok(last_result)
+            Ok(Completion::Normal(last_result))
         } else {
             Err(NjilError::ExecutionError(errortip::control_flow::while_requires_object().to_string()))
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "loop.while"
     }
 } 
\ No newline at end of file
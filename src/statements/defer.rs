@@ -0,0 +1,61 @@
+use crate::error::NjilError;
+use crate::interpreter::Interpreter;
+use crate::statements::StatementHandler;
+use serde_json::Value;
+
+/// Defer语句处理器，登记在当前函数退出时执行的语句块
+///
+/// 同一函数中登记的语句块按后进先出的顺序执行，无论函数是正常返回还是出错退出。
+pub struct DeferHandler;
+
+// 静态实例
+pub static DEFER_HANDLER: DeferHandler = DeferHandler;
+
+impl StatementHandler for DeferHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        interpreter.defer(value)?;
+        Ok(Value::Null)
+    }
+    
+    fn name(&self) -> &str {
+        "defer"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_defer_runs_in_lifo_order() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load_njil_str(r#"{"program": {
+            "work": {"params": ["fail"], "body": [
+                {"global": "log"},
+                {"var.set": {"name": "step", "value": "开始"}},
+                {"defer": {"var.set": {"name": "log", "value": {"string.concat": [{"var": "log"}, "A"]}}}},
+                {"defer": [{"var.set": {"name": "log", "value": {"string.concat": [{"var": "log"}, {"var": "step"}]}}}]},
+                {"var.set": {"name": "step", "value": "B"}},
+                {"if": {"condition": {"var": "fail"}, "then": [{"throw": "失败"}]}},
+                {"return": "完成"}
+            ]}
+        }}"#).unwrap();
+        
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "log", "value": ""}},
+            {"var.set": {"name": "first", "value": {"function.call": {"name": "work", "args": [false]}}}},
+            {"try": {"try": {"function.call": {"name": "work", "args": [true]}}, "catch": {"var": "e"}}},
+            {"return": {"string.concat": [{"var": "first"}, {"var": "log"}]}}
+        ]"#).unwrap();
+        
+        // 语句块在函数退出时才执行，看到的是变量最后的值；出错退出时也会执行
+        assert_eq!(result, json!("完成BABA"));
+    }
+    
+    #[test]
+    fn test_defer_requires_function() {
+        let mut interpreter = Interpreter::new();
+        assert!(DEFER_HANDLER.handle(&mut interpreter, &json!({"print": "x"})).is_err());
+    }
+}
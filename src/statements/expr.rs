@@ -0,0 +1,58 @@
+use crate::error::NjilError;
+use serde_json::Value;
+use crate::interpreter::Interpreter;
+use crate::errortip;
+use crate::statements::StatementHandler;
+
+/// 中缀表达式处理器：{"expr": "a * (b + 2) > limit"}
+///
+/// 解析结果缓存在解释器中，循环中重复执行同一个表达式时不会重新解析。
+pub struct ExprHandler;
+
+// 静态实例
+pub static EXPR_HANDLER: ExprHandler = ExprHandler;
+
+impl StatementHandler for ExprHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        match value {
+            Value::String(source) => interpreter.evaluate_expr(source),
+            _ => Err(NjilError::ExecutionError(errortip::expr::expr_requires_string().to_string())),
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "expr"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_expr_in_program() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load_njil_str(r#"{"program": {
+            "square": {"params": ["n"], "body": [{"return": {"expr": "n * n"}}]}
+        }}"#).unwrap();
+        
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "total", "value": 0}},
+            {"loop.for": {"var": "i", "count": 4, "body": [
+                {"var.set": {"name": "total", "value": {"expr": "total + square(i)"}}}
+            ]}},
+            {"if": {"condition": {"expr": "total > 10 && missing_flag"}, "then": [{"return": "不会执行"}]}},
+            {"return": {"expr": "'总和: ' + total"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("总和: 14"));
+        
+        // 同一个表达式只解析一次
+        let first = interpreter.parse_expr("total + square(i)").unwrap();
+        assert!(std::sync::Arc::ptr_eq(&first, &interpreter.parse_expr("total + square(i)").unwrap()));
+        
+        // 解析错误的类型为parse
+        let error = interpreter.evaluate_expr("1 +").unwrap_err();
+        assert_eq!(error.error_type(), "parse");
+    }
+}
@@ -2,6 +2,7 @@ use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::debug_println;
+use crate::errortip;
 use super::StatementHandler;
 
 /// 函数调用处理器
@@ -24,18 +25,14 @@ impl StatementHandler for FunctionCallHandler {
             },
             Value::Object(obj) => {
                 // 高级形式：提供函数名和参数
-                // 检查必要的字段
-                if !obj.contains_key("name") {
-                    return Err(NjilError::ExecutionError("函数调用需要name字段".to_string()));
-                }
-                
-                // 获取函数名
-                let function_name = match obj.get("name") {
-                    Some(Value::String(name)) => name,
-                    _ => return Err(NjilError::ExecutionError("函数名必须是字符串".to_string())),
+                // 获取被调用者：函数名字符串，或者求值为闭包的表达式
+                let callee = match obj.get("name") {
+                    Some(Value::String(name)) => Value::String(name.clone()),
+                    Some(expr) => interpreter.evaluate_value(expr)?,
+                    None => return Err(NjilError::ExecutionError("函数调用需要name字段".to_string())),
                 };
                 
-                debug_println!("高级函数调用: {}", function_name);
+                debug_println!("高级函数调用: {}", callee);
                 
                 // 获取参数（如果有）
                 let args = match obj.get("args") {
@@ -54,17 +51,30 @@ impl StatementHandler for FunctionCallHandler {
                     None => Vec::new(),
                 };
                 
+                // 获取命名参数（如果有）
+                let kwargs = match obj.get("kwargs") {
+                    Some(Value::Object(kwargs)) => {
+                        let mut evaluated_kwargs = serde_json::Map::new();
+                        for (key, arg) in kwargs {
+                            evaluated_kwargs.insert(key.clone(), interpreter.evaluate_value(arg)?);
+                        }
+                        evaluated_kwargs
+                    },
+                    Some(_) => return Err(NjilError::ExecutionError(errortip::function::kwargs_requires_object().to_string())),
+                    None => serde_json::Map::new(),
+                };
+                
                 // 调用函数
-                debug_println!("调用函数 {} 参数: {}", function_name, serde_json::to_string_pretty(&args).unwrap());
-                let result = interpreter.call_function(function_name, &args);
-                debug_println!("函数 {} 调用结果: {:?}", function_name, result);
+                debug_println!("调用函数 {} 参数: {}", callee, serde_json::to_string_pretty(&args).unwrap());
+                let result = interpreter.call_value(&callee, &args, &kwargs);
+                debug_println!("函数 {} 调用结果: {:?}", callee, result);
                 result
             },
             _ => Err(NjilError::ExecutionError("函数调用需要字符串或对象参数".to_string())),
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "function.call"
     }
     
@@ -0,0 +1,186 @@
+use crate::error::NjilError;
+use std::sync::Arc;
+use serde_json::Value;
+use crate::interpreter::Interpreter;
+use crate::types::Function;
+use crate::errortip;
+use super::StatementHandler;
+
+/// 函数字面量处理器，求值结果是一个捕获当前作用域的闭包
+pub struct FnHandler;
+
+// 静态实例
+pub static FN_HANDLER: FnHandler = FnHandler;
+
+impl StatementHandler for FnHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        match value {
+            // 引用已定义的函数：{"fn": "square"}
+            Value::String(function_name) => interpreter.function_value(function_name),
+            // 匿名函数：{"fn": {"params": [...], "body": [...]}}
+            Value::Object(_) => {
+                let mut function: Function = serde_json::from_value(value.clone())
+                    .map_err(|e| NjilError::ExecutionError(format!("{}: {}", errortip::function::invalid_fn_literal(), e)))?;
+                // 函数体中的语句沿用字面量在源码中的位置
+                function.spans = interpreter.spans_of(value);
+                Ok(interpreter.create_closure(Arc::new(function)))
+            },
+            _ => Err(NjilError::ExecutionError(errortip::function::invalid_fn_literal().to_string())),
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "fn"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["lambda"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NjilProgram;
+    use serde_json::json;
+    
+    fn run_program(program: Value) -> Result<Value, NjilError> {
+        let program: NjilProgram = serde_json::from_value(program).unwrap();
+        Interpreter::new().execute(&program)
+    }
+    
+    #[test]
+    fn test_closure_captures_defining_scope() {
+        let result = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"var.set": {"name": "add5", "value": {"function.call": {"name": "make_adder", "args": [5]}}}},
+                    {"return": {"function.call": {"name": "add5", "args": [10]}}}
+                ]},
+                "make_adder": {
+                    "params": ["n"],
+                    "body": [
+                        {"return": {"fn": {"params": ["x"], "body": [
+                            {"return": {"add": [{"var": "x"}, {"var": "n"}]}}
+                        ]}}}
+                    ]
+                }
+            }
+        }));
+        
+        assert_eq!(result.unwrap(), json!(15));
+    }
+    
+    #[test]
+    fn test_pass_function_as_argument() {
+        let result = run_program(json!({
+            "program": {
+                "main": {"body": [
+                    {"return": {"function.call": {"name": "apply_twice", "args": [{"fn": "double"}, 3]}}}
+                ]},
+                "apply_twice": {
+                    "params": ["f", "x"],
+                    "body": [
+                        {"var.set": {"name": "once", "value": {"function.call": {"name": {"var": "f"}, "args": [{"var": "x"}]}}}},
+                        {"return": {"function.call": {"name": "f", "args": [{"var": "once"}]}}}
+                    ]
+                },
+                "double": {
+                    "params": ["x"],
+                    "body": [{"return": {"multiply": [{"var": "x"}, 2]}}]
+                }
+            }
+        }));
+        
+        assert_eq!(result.unwrap(), json!(12));
+    }
+    
+    #[test]
+    fn test_unused_closures_are_reclaimed() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "total", "value": 0}},
+            {"loop.for": {"var": "i", "count": 3000, "body": [
+                {"var.set": {"name": "f", "value": {"fn": {"params": ["x"], "body": [{"return": {"add": [{"var": "x"}, {"var": "i"}]}}]}}}},
+                {"var.set": {"name": "total", "value": {"function.call": {"name": "f", "args": [1]}}}}
+            ]}},
+            {"return": {"var": "total"}}
+        ]"#).unwrap();
+        
+        assert_eq!(result, json!(3000));
+        assert!(interpreter.closures.len() < 300);
+    }
+    
+    #[test]
+    fn test_closures_in_use_survive_collection() {
+        let mut interpreter = Interpreter::new();
+        // array.map的结果在赋值之前只存在于临时值中，期间创建的闭包会触发回收
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "make", "value": {"fn": {"params": ["n"], "body": [
+                {"return": {"fn": {"params": ["x"], "body": [{"return": {"add": [{"var": "x"}, {"var": "n"}]}}]}}}
+            ]}}}},
+            {"var.set": {"name": "adders", "value": {"array.map": {"array": {"range": [0, 1000]}, "fn": {"var": "make"}}}}},
+            {"var.set": {"name": "churn", "value": {"fn": {"body": [
+                {"loop.for": {"count": 1000, "body": [{"fn": {"body": []}}]}},
+                {"return": 0}
+            ]}}}},
+            {"return": {"expr": "[make(7), churn()][0](1) + adders[999](1) + adders[0](1)"}}
+        ]"#).unwrap();
+        
+        assert_eq!(result, json!(1009));
+        assert!(interpreter.closures.len() < 2000);
+    }
+    
+    #[test]
+    fn test_closures_in_running_frames_survive_collection() {
+        let mut interpreter = Interpreter::new();
+        // h只保存在use的函数帧中，没有闭包捕获这个函数帧
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "make", "value": {"fn": {"params": ["n"], "body": [{"return": {"fn": {"body": [{"return": {"var": "n"}}]}}}]}}}},
+            {"var.set": {"name": "churn", "value": {"fn": {"body": [
+                {"loop.for": {"count": 600, "body": [{"var.set": {"name": "tmp", "value": {"fn": {"body": []}}}}]}},
+                {"return": 0}
+            ]}}}},
+            {"var.set": {"name": "use", "value": {"fn": {"body": [
+                {"var.set": {"name": "h", "value": {"function.call": {"name": "make", "args": [42]}}}},
+                {"function.call": "churn"},
+                {"return": {"function.call": "h"}}
+            ]}}}},
+            {"return": {"function.call": "use"}}
+        ]"#).unwrap();
+        
+        assert_eq!(result, json!(42));
+    }
+    
+    #[test]
+    fn test_closures_passed_between_threads_survive_collection() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "makers", "value": {"fn": {"body": [
+                {"loop.for": {"var": "n", "count": 600, "body": [{"yield": {"fn": {"body": [{"return": {"var": "n"}}]}}}]}}
+            ]}}}},
+            {"var.set": {"name": "sum", "value": 0}},
+            {"loop.foreach": {"collection": {"function.call": "makers"}, "var": "f", "body": [
+                {"var.set": {"name": "sum", "value": {"add": [{"var": "sum"}, {"function.call": "f"}]}}}
+            ]}},
+            {"var.set": {"name": "results", "value": {"parallel": [
+                {"fn": {"body": [{"return": 5}]}},
+                [{"loop.for": {"count": 1000, "body": [{"fn": {"body": []}}]}}, {"return": 0}]
+            ]}}},
+            {"return": {"add": [{"var": "sum"}, {"function.call": {"name": {"var": "results[0]"}}}]}}
+        ]"#).unwrap();
+        
+        assert_eq!(result, json!(179705));
+    }
+    
+    #[test]
+    fn test_closure_markers_cannot_be_forged() {
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "secret", "value": {"fn": {"body": [{"return": "secret"}]}}}},
+            {"return": {"function.call": {"name": {"$closure": 0}}}}
+        ]"#).unwrap_err();
+        
+        assert!(error.to_string().contains("无效的闭包"));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::completion::Completion;
+use crate::error::NjilError;
+use crate::generator::Resume;
+use serde_json::Value;
+use crate::interpreter::Interpreter;
+use super::StatementHandler;
+
+/// yield语句处理器，交出一个值并暂停生成器，直到调用者再次恢复
+///
+/// 生成器被关闭时，暂停处的yield像return一样结束函数体，函数体中的finally和defer照常执行。
+pub struct YieldHandler;
+
+// 静态实例
+pub static YIELD_HANDLER: YieldHandler = YieldHandler;
+
+impl StatementHandler for YieldHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
+        let result = interpreter.evaluate_value(value)?;
+        match interpreter.yield_value(result)? {
+            Resume::Next => Ok(Completion::Normal(Value::Null)),
+            Resume::Close => Ok(Completion::Return(Value::Null)),
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "yield"
+    }
+}
+
+/// gen.next语句处理器，恢复生成器并返回 `{"value": 值, "done": 是否结束}`
+pub struct GenNextHandler;
+
+// 静态实例
+pub static GEN_NEXT_HANDLER: GenNextHandler = GenNextHandler;
+
+impl StatementHandler for GenNextHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let generator = interpreter.evaluate_value(value)?;
+        Ok(interpreter.resume_generator(&generator)?.to_value())
+    }
+    
+    fn name(&self) -> &str {
+        "gen.next"
+    }
+}
+
+/// gen.close语句处理器，提前结束生成器并执行其中的finally和defer
+pub struct GenCloseHandler;
+
+// 静态实例
+pub static GEN_CLOSE_HANDLER: GenCloseHandler = GenCloseHandler;
+
+impl StatementHandler for GenCloseHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let generator = interpreter.evaluate_value(value)?;
+        interpreter.close_generator(&generator)?;
+        Ok(Value::Null)
+    }
+    
+    fn name(&self) -> &str {
+        "gen.close"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_generators_are_lazy_and_clean_up() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "log", "value": ""}},
+            {"var.set": {"name": "count_up", "value": {"fn": {"params": ["limit"], "body": [
+                {"global": "log"},
+                {"defer": {"var.set": {"name": "log", "value": {"string.concat": [{"var": "log"}, "closed "]}}}},
+                {"var.set": {"name": "i", "value": 0}},
+                {"loop.while": {"condition": {"expr": "i < limit"}, "body": [
+                    {"var.set": {"name": "log", "value": {"string.concat": [{"var": "log"}, "y", {"var": "i"}, " "]}}},
+                    {"yield": {"var": "i"}},
+                    {"var.set": {"name": "i", "value": {"expr": "i + 1"}}}
+                ]}},
+                {"return": "end"}
+            ]}}}},
+            {"var.set": {"name": "g", "value": {"function.call": {"name": "count_up", "args": [2]}}}},
+            {"var.set": {"name": "first", "value": {"gen.next": {"var": "g"}}}},
+            {"var.set": {"name": "second", "value": {"gen.next": {"var": "g"}}}},
+            {"var.set": {"name": "last", "value": {"gen.next": {"var": "g"}}}},
+            {"var.set": {"name": "after", "value": {"gen.next": {"var": "g"}}}},
+            {"loop.foreach": {"collection": {"function.call": {"name": "count_up", "args": [1000000000]}}, "var": "n", "body": [
+                {"if": {"condition": {"expr": "n == 1"}, "then": [{"loop.break": null}]}}
+            ]}},
+            {"return": {"var": "log"}}
+        ]"#).unwrap();
+        // 函数体在第一次gen.next时才开始执行，loop.break提前结束循环时执行defer
+        assert_eq!(result, json!("y0 y1 closed y0 y1 closed "));
+        
+        let steps: Vec<Value> = ["first", "second", "last", "after"].iter()
+            .map(|name| interpreter.get_global(name).unwrap())
+            .collect();
+        assert_eq!(steps, vec![
+            json!({"value": 0, "done": false}),
+            json!({"value": 1, "done": false}),
+            json!({"value": "end", "done": true}),
+            json!({"value": null, "done": true}),
+        ]);
+    }
+    
+    #[test]
+    fn test_generator_errors() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "broken", "value": {"fn": {"body": [
+                {"yield": 1},
+                {"throw": "坏掉了"}
+            ]}}}},
+            {"var.set": {"name": "g", "value": {"function.call": "broken"}}},
+            {"gen.next": {"var": "g"}},
+            {"try": {
+                "try": [{"gen.next": {"var": "g"}}],
+                "catch": {"var": "e", "body": [{"return": {"var": "e.message"}}]}
+            }}
+        ]"#).unwrap();
+        assert_eq!(result, json!("坏掉了"));
+        
+        let error = interpreter.run_njis_str(r#"[{"yield": 1}]"#).unwrap_err();
+        assert!(error.message().contains("yield只能在函数中使用"));
+        let error = interpreter.run_njis_str(r#"[{"gen.next": [1, 2]}]"#).unwrap_err();
+        assert!(error.message().contains("不是生成器"));
+    }
+    
+    #[test]
+    fn test_generators_share_the_statement_limit() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(crate::policy::ExecutionPolicy::new().max_statements(300));
+        // 每个生成器单独执行的语句数都不超过限制，但加起来超过了
+        let error = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "busy", "value": {"fn": {"body": [
+                {"loop.for": {"var": "i", "from": 0, "to": 50, "body": [{"var.set": {"name": "x", "value": {"var": "i"}}}]}},
+                {"yield": 1}
+            ]}}}},
+            {"loop.for": {"var": "n", "from": 0, "to": 10, "body": [{"gen.next": {"function.call": "busy"}}]}}
+        ]"#).unwrap_err();
+        assert!(matches!(error.into_root(), NjilError::LimitExceeded(msg) if msg.contains("语句数")));
+    }
+    
+    #[test]
+    fn test_abandoned_generators_are_reclaimed() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(crate::policy::ExecutionPolicy::new().max_generators(8));
+        // 循环中的生成器恢复一次后不再被引用，达到上限时被回收
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "counter", "value": {"fn": {"params": ["n"], "body": [
+                {"loop.while": {"condition": true, "body": [{"yield": {"var": "n"}}, {"var.set": {"name": "n", "value": {"add": [{"var": "n"}, 1]}}}]}}
+            ]}}}},
+            {"var.set": {"name": "kept", "value": {"function.call": {"name": "counter", "args": [100]}}}},
+            {"gen.next": {"var": "kept"}},
+            {"loop.for": {"var": "i", "count": 50, "body": [{"gen.next": {"function.call": {"name": "counter", "args": [{"var": "i"}]}}}]}},
+            {"return": {"json.get": {"object": {"gen.next": {"var": "kept"}}, "key": "value"}}}
+        ]"#).unwrap();
+        
+        assert_eq!(result, json!(101));
+        assert!(interpreter.closures.live_generators() <= 8);
+        
+        // 每个生成器的函数帧引用上一个生成器，它们都不能被回收
+        let error = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "held", "value": null}},
+            {"loop.for": {"count": 10, "body": [
+                {"var.set": {"name": "held", "value": {"function.call": {"name": "counter", "args": [{"var": "held"}]}}}}
+            ]}}
+        ]"#).unwrap_err();
+        assert!(matches!(error.into_root(), NjilError::LimitExceeded(msg) if msg.contains("生成器数")));
+    }
+    
+    #[test]
+    fn test_reclaimed_generators_finish_cleanup_before_continuing() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(crate::policy::ExecutionPolicy::new().max_generators(1));
+        // 创建新的生成器时回收上一个，等它执行完defer之后才继续
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "log", "value": ""}},
+            {"var.set": {"name": "worker", "value": {"fn": {"body": [
+                {"global": "log"},
+                {"defer": [{"sleep": 50}, {"var.set": {"name": "log", "value": {"string.concat": [{"var": "log"}, "closed "]}}}]},
+                {"yield": 1},
+                {"yield": 2}
+            ]}}}},
+            {"loop.for": {"count": 3, "body": [{"gen.next": {"function.call": "worker"}}]}},
+            {"return": {"var": "log"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("closed closed "));
+    }
+    
+    #[test]
+    fn test_generators_resumed_in_later_runs() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(crate::policy::ExecutionPolicy::new().timeout(std::time::Duration::from_millis(200)).max_statements(200));
+        interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "busy", "value": {"fn": {"body": [
+                {"loop.while": {"condition": true, "body": [
+                    {"loop.for": {"count": 50, "body": []}},
+                    {"yield": 1}
+                ]}}
+            ]}}}},
+            {"var.set": {"name": "g", "value": {"function.call": "busy"}}},
+            {"gen.next": {"var": "g"}},
+            {"gen.next": {"var": "g"}}
+        ]"#).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(250));
+        
+        // 生成器按恢复它的这次运行计算截止时间和语句数
+        let result = interpreter.run_njis_str(r#"[
+            {"gen.next": {"var": "g"}},
+            {"return": {"gen.next": {"var": "g"}}}
+        ]"#).unwrap();
+        assert_eq!(result, json!({"value": 1, "done": false}));
+    }
+    
+    #[test]
+    fn test_yield_in_tasks_does_not_make_a_generator() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "f", "value": {"fn": {"body": [
+                {"return": {"type.of": {"task.spawn": [{"yield": 1}]}}}
+            ]}}}},
+            {"return": {"function.call": "f"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("task"));
+    }
+    
+    #[test]
+    fn test_values_created_inside_generators() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "letters", "value": {"fn": {"body": [{"yield": "a"}, {"yield": "b"}]}}}},
+            {"var.set": {"name": "outer", "value": {"fn": {"body": [
+                {"yield": {"fn": {"params": ["x"], "body": [{"return": {"expr": "x * 2"}}]}}},
+                {"yield": {"function.call": "letters"}}
+            ]}}}},
+            {"var.set": {"name": "g", "value": {"function.call": "outer"}}},
+            {"var.set": {"name": "double", "value": {"json.get": {"object": {"gen.next": {"var": "g"}}, "key": "value"}}}},
+            {"var.set": {"name": "inner", "value": {"json.get": {"object": {"gen.next": {"var": "g"}}, "key": "value"}}}},
+            {"var.set": {"name": "text", "value": ""}},
+            {"loop.foreach": {"collection": {"var": "inner"}, "var": "letter", "body": [
+                {"var.set": {"name": "text", "value": {"string.concat": [{"var": "text"}, {"var": "letter"}]}}}
+            ]}},
+            {"return": {"string.concat": [{"function.call": {"name": "double", "args": [21]}}, {"var": "text"}]}}
+        ]"#).unwrap();
+        // 生成器中创建的闭包和生成器可以在外面使用
+        assert_eq!(result, json!("42ab"));
+        
+        let error = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "selfish", "value": {"fn": {"body": [{"yield": {"gen.next": {"var": "me"}}}]}}}},
+            {"var.set": {"name": "me", "value": {"function.call": "selfish"}}},
+            {"gen.next": {"var": "me"}}
+        ]"#).unwrap_err();
+        assert!(error.message().contains("生成器正在执行"));
+    }
+}
@@ -47,12 +47,12 @@ impl StatementHandler for JsonNewHandler {
             // 其他类型，返回错误
             _ => Err(NjilError::ExecutionError(format!(
                 "json.new 需要null、对象或数组类型参数，但收到了: {}", 
-                value.to_string()
+                value
             )))
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "json.new"
     }
 }
@@ -150,7 +150,7 @@ impl StatementHandler for JsonGetHandler {
                 // 不支持的类型组合
                 _ => return Err(NjilError::ExecutionError(format!(
                     "json.get 不支持的类型组合: 对象类型 {} 和键类型 {}", 
-                    json_obj.to_string(), key.to_string()
+                    json_obj, key
                 ))),
             }
         }
@@ -160,7 +160,7 @@ impl StatementHandler for JsonGetHandler {
         ))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "json.get"
     }
 }
@@ -250,7 +250,7 @@ impl StatementHandler for JsonSetHandler {
                 // 不支持的类型组合
                 _ => return Err(NjilError::ExecutionError(format!(
                     "json.set 不支持的类型组合: 对象类型 {} 和键类型 {}", 
-                    result_obj.to_string(), key.to_string()
+                    result_obj, key
                 ))),
             }
         }
@@ -260,7 +260,7 @@ impl StatementHandler for JsonSetHandler {
         ))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "json.set"
     }
 } 
\ No newline at end of file
@@ -24,15 +24,11 @@ impl StatementHandler for LogicAndHandler {
                 // 评估操作数
                 let operand_value = match interpreter.evaluate_value(operand) {
                     Ok(value) => value,
-                    Err(NjilError::ExecutionError(msg)) => {
-                        // 检查是否是变量未定义错误
-                        if msg.starts_with("未定义的变量:") {
-                            Value::Null // 变量不存在时，视为null（假）
-                        } else {
-                            return Err(NjilError::ExecutionError(msg));
-                        }
+                    Err(e) => match e.root() {
+                        // 检查是否是变量未定义错误，变量不存在时视为null（假）
+                        NjilError::UndefinedVariable(_) => Value::Null,
+                        _ => return Err(e),
                     },
-                    Err(e) => return Err(e),
                 };
 
                 // 检查操作数是否为真
@@ -60,7 +56,7 @@ impl StatementHandler for LogicAndHandler {
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "logic.and"
     }
 }
@@ -85,15 +81,11 @@ impl StatementHandler for LogicOrHandler {
                 // 评估操作数
                 let operand_value = match interpreter.evaluate_value(operand) {
                     Ok(value) => value,
-                    Err(NjilError::ExecutionError(msg)) => {
-                        // 检查是否是变量未定义错误
-                        if msg.starts_with("未定义的变量:") {
-                            Value::Null // 变量不存在时，视为null（假）
-                        } else {
-                            return Err(NjilError::ExecutionError(msg));
-                        }
+                    Err(e) => match e.root() {
+                        // 检查是否是变量未定义错误，变量不存在时视为null（假）
+                        NjilError::UndefinedVariable(_) => Value::Null,
+                        _ => return Err(e),
                     },
-                    Err(e) => return Err(e),
                 };
 
                 // 检查操作数是否为真
@@ -121,7 +113,7 @@ impl StatementHandler for LogicOrHandler {
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "logic.or"
     }
 }
@@ -137,15 +129,11 @@ impl StatementHandler for LogicNotHandler {
         // 评估操作数
         let operand_value = match interpreter.evaluate_value(value) {
             Ok(value) => value,
-            Err(NjilError::ExecutionError(msg)) => {
-                // 检查是否是变量未定义错误
-                if msg.starts_with("未定义的变量:") {
-                    Value::Null // 变量不存在时，视为null（假）
-                } else {
-                    return Err(NjilError::ExecutionError(msg));
-                }
+            Err(e) => match e.root() {
+                // 检查是否是变量未定义错误，变量不存在时视为null（假）
+                NjilError::UndefinedVariable(_) => Value::Null,
+                _ => return Err(e),
             },
-            Err(e) => return Err(e),
         };
 
         // 检查操作数是否为真
@@ -162,7 +150,7 @@ impl StatementHandler for LogicNotHandler {
         Ok(Value::Bool(!is_true))
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "logic.not"
     }
 } 
\ No newline at end of file
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
-use std::sync::{OnceLock, Mutex};
+use std::ops::Deref;
+use std::sync::Arc;
 use serde_json::Value;
+use crate::closure;
+use crate::completion::Completion;
 use crate::error::NjilError;
 use crate::interpreter::Interpreter;
 use crate::errortip;
@@ -8,6 +11,7 @@ use crate::errortip;
 pub mod print;
 pub mod string;
 pub mod var;
+pub mod pattern;
 pub mod return_stmt;
 pub mod json;
 pub mod control_flow;
@@ -15,15 +19,20 @@ pub mod logic;
 pub mod type_convert;
 pub mod arithmetic;
 pub mod function_call;
+pub mod function_def;
 pub mod sleep;
 pub mod constant;
 pub mod throw;
 pub mod try_catch;
+pub mod defer;
+pub mod expr;
+pub mod generator;
+pub mod task;
 
 use print::PRINT_HANDLER;
 use print::PRINTLN_HANDLER;
 use string::{STRING_CONCAT_HANDLER, STRING_SPLIT_HANDLER, STRING_REPLACE_HANDLER, STRING_TRIM_HANDLER, STRING_FORMAT_HANDLER};
-use var::{VAR_HANDLER, VAR_SET_HANDLER, VAR_SET_MULTI_HANDLER};
+use var::{VAR_HANDLER, VAR_SET_HANDLER, VAR_SET_MULTI_HANDLER, VAR_OUTER_HANDLER, GLOBAL_HANDLER};
 use return_stmt::RETURN_HANDLER;
 use json::{JSON_NEW_HANDLER, JSON_GET_HANDLER, JSON_SET_HANDLER};
 use control_flow::get_all_handlers as get_all_control_flow_handlers;
@@ -32,6 +41,10 @@ use sleep::SLEEP_HANDLER;
 use constant::{CONST_HANDLER, CONST_SET_HANDLER, CONST_SET_MULTI_HANDLER, CONST_HAS_HANDLER};
 use throw::THROW_HANDLER;
 use try_catch::TRY_CATCH_HANDLER;
+use defer::DEFER_HANDLER;
+use expr::EXPR_HANDLER;
+use generator::{YIELD_HANDLER, GEN_NEXT_HANDLER, GEN_CLOSE_HANDLER};
+use task::{PARALLEL_HANDLER, TASK_SPAWN_HANDLER, TASK_AWAIT_HANDLER, TASK_ALL_HANDLER};
 
 use logic::{
     LOGIC_AND_HANDLER,
@@ -41,14 +54,23 @@ use logic::{
 use type_convert::{TYPE_CONVERT_HANDLER, TO_BOOL_HANDLER, TO_NUMBER_HANDLER, TO_STRING_HANDLER, TO_ARRAY_HANDLER, TO_OBJECT_HANDLER, TYPE_OF_HANDLER};
 use arithmetic::get_all_handlers as get_all_arithmetic_handlers;
 use function_call::FUNCTION_CALL_HANDLER;
+use function_def::FN_HANDLER;
 
 /// 语句处理器特性
 pub trait StatementHandler: Send + Sync {
     /// 处理语句
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError>;
     
+    /// 执行语句，返回语句的完成状态
+    ///
+    /// 默认把handle的结果作为正常完成。return、loop.break、loop.continue
+    /// 以及执行语句块的控制流语句重写这个方法。
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
+        self.handle(interpreter, value).map(Completion::Normal)
+    }
+    
     /// 获取处理器名称
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
     
     /// 获取处理器别名（可选）
     fn aliases(&self) -> Vec<&'static str> {
@@ -56,9 +78,36 @@ pub trait StatementHandler: Send + Sync {
     }
 }
 
+/// 已注册的语句处理器：内置的静态处理器，或宿主程序注册的共享处理器
+#[derive(Clone)]
+pub enum HandlerRef {
+    Static(&'static dyn StatementHandler),
+    Shared(Arc<dyn StatementHandler>),
+}
+
+impl Deref for HandlerRef {
+    type Target = dyn StatementHandler;
+    
+    fn deref(&self) -> &Self::Target {
+        match self {
+            HandlerRef::Static(handler) => *handler,
+            HandlerRef::Shared(handler) => handler.as_ref(),
+        }
+    }
+}
+
 /// 语句注册表，用于管理语句处理器
+#[derive(Clone)]
 pub struct StatementRegistry {
-    handlers: HashMap<String, &'static dyn StatementHandler>,
+    handlers: HashMap<String, HandlerRef>,
+    // 模块中的处理器所属的模块，键为处理器的主名称
+    modules: HashMap<String, String>,
+}
+
+impl Default for StatementRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StatementRegistry {
@@ -66,6 +115,7 @@ impl StatementRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
             handlers: HashMap::new(),
+            modules: HashMap::new(),
         };
         
         // 注册核心语句处理器
@@ -74,6 +124,8 @@ impl StatementRegistry {
         registry.register_handler(&VAR_HANDLER);
         registry.register_handler(&VAR_SET_HANDLER);
         registry.register_handler(&VAR_SET_MULTI_HANDLER);
+        registry.register_handler(&VAR_OUTER_HANDLER);
+        registry.register_handler(&GLOBAL_HANDLER);
         registry.register_handler(&RETURN_HANDLER);
         registry.register_handler(&STRING_CONCAT_HANDLER);
         registry.register_handler(&SLEEP_HANDLER);
@@ -113,12 +165,28 @@ impl StatementRegistry {
             registry.register_handler(handler);
         }
         
+        // 注册中缀表达式处理器
+        registry.register_handler(&EXPR_HANDLER);
+        
         // 注册函数调用处理器
         registry.register_handler(&FUNCTION_CALL_HANDLER);
+        registry.register_handler(&FN_HANDLER);
         
         // 注册异常处理语句处理器
         registry.register_handler(&THROW_HANDLER);
         registry.register_handler(&TRY_CATCH_HANDLER);
+        registry.register_handler(&DEFER_HANDLER);
+        
+        // 注册生成器语句处理器
+        registry.register_handler(&YIELD_HANDLER);
+        registry.register_handler(&GEN_NEXT_HANDLER);
+        registry.register_handler(&GEN_CLOSE_HANDLER);
+        
+        // 注册并行与任务语句处理器
+        registry.register_handler(&PARALLEL_HANDLER);
+        registry.register_handler(&TASK_SPAWN_HANDLER);
+        registry.register_handler(&TASK_AWAIT_HANDLER);
+        registry.register_handler(&TASK_ALL_HANDLER);
         
         // 注册扩展字符串操作处理器
         registry.register_handler(&STRING_SPLIT_HANDLER);
@@ -131,50 +199,126 @@ impl StatementRegistry {
     
     /// 注册一个语句处理器
     pub fn register_handler(&mut self, handler: &'static dyn StatementHandler) {
-        // 注册主名称
-        self.handlers.insert(handler.name().to_string(), handler);
-        
+        self.register(HandlerRef::Static(handler));
+    }
+    
+    /// 注册一个共享的语句处理器（可以持有宿主程序的状态）
+    pub fn register_shared_handler(&mut self, handler: Arc<dyn StatementHandler>) {
+        self.register(HandlerRef::Shared(handler));
+    }
+    
+    fn register(&mut self, handler: HandlerRef) {
         // 注册别名
         for alias in handler.aliases() {
-            self.handlers.insert(alias.to_string(), handler);
+            self.handlers.insert(alias.to_string(), handler.clone());
         }
+        
+        // 注册主名称
+        self.handlers.insert(handler.name().to_string(), handler);
     }
     
     /// 获取指定名称的处理器
-    pub fn get(&self, name: &str) -> Option<&'static dyn StatementHandler> {
-        self.handlers.get(name).copied()
+    pub fn get(&self, name: &str) -> Option<HandlerRef> {
+        self.handlers.get(name).cloned()
+    }
+    
+    /// 记录处理器属于哪个模块，执行策略禁用模块时据此禁止通过别名调用其中的指令
+    pub fn set_module(&mut self, name: &str, module: &str) {
+        self.modules.insert(name.to_string(), module.to_string());
+    }
+    
+    /// 获取处理器所属的模块，参数是处理器的主名称
+    pub fn module_of(&self, name: &str) -> Option<&str> {
+        self.modules.get(name).map(String::as_str)
+    }
+    
+    /// 检查指定名称的处理器是否存在
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+}
+
+/// 语句块中的语句：语句数组本身，或者单条语句
+pub(crate) fn block_statements(block: &Value) -> &[Value] {
+    match block {
+        Value::Array(statements) => statements.as_slice(),
+        _ => std::slice::from_ref(block),
     }
 }
 
-// 使用Mutex包装StatementRegistry，使其可以安全地修改
-static REGISTRY: OnceLock<Mutex<StatementRegistry>> = OnceLock::new();
+/// 在当前作用域中依次执行语句，遇到return、loop.break或loop.continue时停止
+///
+/// 正常结束时的值是最后一条语句的值。
+pub(crate) fn execute_statements(interpreter: &mut Interpreter, statements: &[Value]) -> Result<Completion, NjilError> {
+    let mut last_result = Value::Null;
+    let pins = interpreter.pin_mark();
+    for stmt in statements {
+        match execute_statement(interpreter, stmt)? {
+            Completion::Normal(result) => {
+                interpreter.release_pins(pins, Some(&result));
+                last_result = result;
+            },
+            abrupt => return Ok(abrupt),
+        }
+    }
+    Ok(Completion::Normal(last_result))
+}
 
-/// 获取全局语句注册表
-fn get_registry() -> &'static Mutex<StatementRegistry> {
-    REGISTRY.get_or_init(|| Mutex::new(StatementRegistry::new()))
+/// 执行语句块（单条语句或语句数组）
+///
+/// 块作用域只保存显式声明的变量，语句块本身不声明变量，所以直接在当前作用域中执行，不创建新的作用域。
+pub(crate) fn execute_block(interpreter: &mut Interpreter, block: &Value) -> Result<Completion, NjilError> {
+    execute_statements(interpreter, block_statements(block))
 }
 
-/// 注册一个语句处理器到全局注册表
-pub fn register_handler(handler: &'static dyn StatementHandler) {
-    // 使用Mutex获取可变引用
-    if let Ok(mut registry) = get_registry().lock() {
-        registry.register_handler(handler);
+/// 检查对象是否为一条已注册的语句（只有一个键且键是已知指令）
+pub fn is_statement(interpreter: &Interpreter, obj: &serde_json::Map<String, Value>) -> bool {
+    if obj.len() != 1 {
+        return false;
     }
+    let key = obj.keys().next().unwrap();
+    interpreter.statement_handlers.contains(key)
+}
+
+/// 执行语句，返回语句的完成状态，出错时在错误中记录语句的源码位置和调用栈
+pub fn execute_statement(interpreter: &mut Interpreter, statement: &Value) -> Result<Completion, NjilError> {
+    let previous = interpreter.enter_statement(statement);
+    let pins = interpreter.pin_mark();
+    let result = dispatch_statement(interpreter, statement)
+        .map_err(|error| interpreter.trace_error(statement, error));
+    interpreter.release_pins(pins, closure::kept_value(result.as_ref().map(Completion::value)));
+    interpreter.leave_statement(previous);
+    result
 }
 
-/// 处理语句
+/// 处理需要值的语句（参数和表达式中的语句），return、loop.break和loop.continue在这里是错误
 pub fn handle_statement(interpreter: &mut Interpreter, statement: &Value) -> Result<Value, NjilError> {
+    let previous = interpreter.enter_statement(statement);
+    let pins = interpreter.pin_mark();
+    let result = dispatch_statement(interpreter, statement)
+        .and_then(Completion::into_value)
+        .map_err(|error| interpreter.trace_error(statement, error));
+    interpreter.release_pins(pins, closure::kept_value(result.as_ref().map(Some)));
+    interpreter.leave_statement(previous);
+    result
+}
+
+// 辅助函数：把语句分派给对应的处理器
+fn dispatch_statement(interpreter: &mut Interpreter, statement: &Value) -> Result<Completion, NjilError> {
     if let Value::Object(obj) = statement {
         if obj.len() == 1 {
             let (key, value) = obj.iter().next().unwrap();
             
+            // 检查执行策略的语句数和时间限制
+            interpreter.check_step()?;
+            
             // 检查是否为变量访问
             if key == "var" && value.is_string() {
                 if let Value::String(var_path) = value {
                     // 特殊处理嵌套变量路径
                     if var_path.contains('.') || var_path.contains('[') {
                         // 使用 VarHandler 处理嵌套变量路径
-                        return VAR_HANDLER.handle(interpreter, value);
+                        return VAR_HANDLER.handle(interpreter, value).map(Completion::Normal);
                     }
                 }
             }
@@ -185,22 +329,21 @@ pub fn handle_statement(interpreter: &mut Interpreter, statement: &Value) -> Res
                     // 特殊处理嵌套常量路径
                     if const_path.contains('.') || const_path.contains('[') {
                         // 使用 ConstHandler 处理嵌套常量路径
-                        return CONST_HANDLER.handle(interpreter, value);
+                        return CONST_HANDLER.handle(interpreter, value).map(Completion::Normal);
                     }
                 }
             }
             
             // 获取语句处理器
-            let handler: Option<&'static dyn StatementHandler> = {
-                if let Ok(registry) = get_registry().lock() {
-                    registry.get(key)
-                } else {
-                    None
-                }
-            };
+            let handler = interpreter.statement_handlers.get(key);
             
             if let Some(handler) = handler {
-                return handler.handle(interpreter, value);
+                interpreter.check_instruction(key, &*handler)?;
+                let completion = handler.execute(interpreter, value)?;
+                if let Completion::Normal(result) | Completion::Return(result) = &completion {
+                    interpreter.check_value_size(result)?;
+                }
+                return Ok(completion);
             }
             
             // 如果找不到处理器，检查是否是嵌套变量路径的一部分
@@ -211,13 +354,13 @@ pub fn handle_statement(interpreter: &mut Interpreter, statement: &Value) -> Res
                     // 检查第一部分是否是变量
                     let base_var = parts[0];
                     
-                    if interpreter.variables.contains_key(base_var) {
+                    if interpreter.has_variable(base_var) {
                         // 这可能是一个嵌套变量访问，而不是未知指令
                         // 将其转换为正确的变量访问形式
                         let mut var_obj = serde_json::Map::new();
                         var_obj.insert("var".to_string(), Value::String(key.to_string()));
                         let var_statement = Value::Object(var_obj);
-                        return interpreter.evaluate_value(&var_statement);
+                        return interpreter.evaluate_value(&var_statement).map(Completion::Normal);
                     }
                     
                     // 检查第一部分是否是常量
@@ -226,7 +369,7 @@ pub fn handle_statement(interpreter: &mut Interpreter, statement: &Value) -> Res
                         let mut const_obj = serde_json::Map::new();
                         const_obj.insert("const".to_string(), Value::String(key.to_string()));
                         let const_statement = Value::Object(const_obj);
-                        return interpreter.evaluate_value(&const_statement);
+                        return interpreter.evaluate_value(&const_statement).map(Completion::Normal);
                     }
                 }
             }
@@ -235,5 +378,5 @@ pub fn handle_statement(interpreter: &mut Interpreter, statement: &Value) -> Res
         }
     }
     
-    Ok(statement.clone())
+    Ok(Completion::Normal(statement.clone()))
 } 
\ No newline at end of file
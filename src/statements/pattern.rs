@@ -0,0 +1,53 @@
+use crate::error::NjilError;
+use serde_json::Value;
+use crate::errortip::control_flow as tip;
+
+// 模式的公共写法 - 解构赋值和match共用
+// 剩余元素写作 "...变量名" 或 {"$rest": "变量名"}，对象模式中的键 "..." 收集其余字段，
+// {"$bind": 变量名} 和 {"$pattern": 模式} 可以带 "$default"，在值不存在时使用默认值。
+
+/// 剩余元素的前缀，如数组模式中的 `"...rest"`，对象模式中的键 `"..."`
+pub(crate) const REST_PREFIX: &str = "...";
+
+/// 数组模式拆分后的结果
+pub(crate) struct ArrayPattern<'a> {
+    /// 逐项匹配的模式
+    pub(crate) items: &'a [Value],
+    /// 收集剩余元素的项，`Some(None)` 表示只收集不绑定（`"..."` 或 `{"$rest": null}`）
+    pub(crate) rest: Option<Option<&'a str>>,
+}
+
+/// 把数组模式拆分为逐项匹配的部分和最后的剩余项，剩余项不在最后时返回错误
+pub(crate) fn split_array(items: &[Value]) -> Result<ArrayPattern<'_>, NjilError> {
+    for (i, item) in items.iter().enumerate() {
+        if let Some(rest) = rest_item(item)? {
+            if i + 1 != items.len() {
+                return Err(NjilError::ExecutionError(tip::pattern_rest_not_last().to_string()));
+            }
+            return Ok(ArrayPattern { items: &items[..i], rest: Some(rest) });
+        }
+    }
+    Ok(ArrayPattern { items, rest: None })
+}
+
+// 辅助函数：如果是剩余项，返回其中的变量名
+fn rest_item(item: &Value) -> Result<Option<Option<&str>>, NjilError> {
+    match item {
+        Value::String(name) => Ok(name.strip_prefix(REST_PREFIX).map(|name| Some(name).filter(|name| !name.is_empty()))),
+        Value::Object(obj) if obj.len() == 1 => match obj.get("$rest") {
+            Some(Value::String(name)) => Ok(Some(Some(name.as_str()))),
+            Some(Value::Null) => Ok(Some(None)),
+            Some(_) => Err(NjilError::ExecutionError(tip::pattern_name_requires_string("$rest"))),
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// 值不存在时使用的默认值表达式：`{"$bind": ..., "$default": 表达式}` 或 `{"$pattern": ..., "$default": 表达式}`
+pub(crate) fn default_of(pattern: &Value) -> Option<&Value> {
+    match pattern {
+        Value::Object(obj) if obj.keys().all(|key| key.starts_with('$')) => obj.get("$default"),
+        _ => None,
+    }
+}
@@ -25,7 +25,7 @@ impl StatementHandler for PrintHandler {
         Ok(Value::Null)
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "print"
     }
 }
@@ -56,7 +56,7 @@ impl StatementHandler for PrintlnHandler {
         Ok(Value::Null)
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "println"
     }
 } 
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
@@ -11,11 +12,15 @@ pub static RETURN_HANDLER: ReturnHandler = ReturnHandler;
 
 impl StatementHandler for ReturnHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
         let result = interpreter.evaluate_value(value)?;
-        Err(NjilError::ReturnValue(result))
+        Ok(Completion::Return(result))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "return"
     }
 } 
\ No newline at end of file
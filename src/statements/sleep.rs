@@ -93,13 +93,22 @@ impl StatementHandler for SleepHandler {
         
         debug_println!("执行延时: {}毫秒", duration_ms);
         
+        // 延时不能超过执行策略剩余的时间
+        let duration = Duration::from_millis(duration_ms);
+        if let Some(remaining) = interpreter.remaining_time() {
+            if duration > remaining {
+                thread::sleep(remaining);
+                return interpreter.check_step().map(|_| Value::Null);
+            }
+        }
+        
         // 执行延时
-        thread::sleep(Duration::from_millis(duration_ms));
+        thread::sleep(duration);
         
         Ok(Value::Null)
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "sleep"
     }
     
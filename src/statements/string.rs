@@ -2,7 +2,7 @@ use crate::error::NjilError;
 use serde_json::Value;
 use crate::interpreter::Interpreter;
 use crate::errortip;
-use super::StatementHandler;
+use super::{StatementHandler, is_statement};
 
 /// 字符串连接处理器，同时处理string.concat和txtlink
 pub struct StringConcatHandler;
@@ -30,7 +30,7 @@ impl StatementHandler for StringConcatHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "string.concat"
     }
     
@@ -68,10 +68,16 @@ impl StatementHandler for StringSplitHandler {
                 _ => interpreter.value_to_string(&separator_value),
             };
             
-            // 分割字符串并返回数组
-            let parts: Vec<Value> = string.split(&separator)
-                .map(|s| Value::String(s.to_string()))
-                .collect();
+            // 分割字符串并返回数组，空分隔符按字符拆分
+            let parts: Vec<Value> = if separator.is_empty() {
+                string.chars()
+                    .map(|c| Value::String(c.to_string()))
+                    .collect()
+            } else {
+                string.split(&separator)
+                    .map(|s| Value::String(s.to_string()))
+                    .collect()
+            };
             
             Ok(Value::Array(parts))
         } else {
@@ -79,7 +85,7 @@ impl StatementHandler for StringSplitHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "string.split"
     }
     
@@ -136,7 +142,7 @@ impl StatementHandler for StringReplaceHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "string.replace"
     }
     
@@ -182,7 +188,7 @@ impl StatementHandler for StringTrimHandler {
         Ok(Value::String(result))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "string.trim"
     }
     
@@ -214,11 +220,15 @@ impl StatementHandler for StringFormatHandler {
             if !obj.contains_key("params") {
                 return Err(NjilError::ExecutionError("string.format需要params参数".to_string()));
             }
-            let params_value = interpreter.evaluate_value(obj.get("params").unwrap())?;
+            // 参数对象本身是字面量时不作为语句执行，参数值在下面逐个评估
+            let params_value = match obj.get("params").unwrap() {
+                Value::Object(raw) if !is_statement(interpreter, raw) => Value::Object(raw.clone()),
+                other => interpreter.evaluate_value(other)?,
+            };
             
             if let Value::Object(params) = params_value {
                 // 使用参数替换模板中的占位符 {name}
-                let mut result = template.clone();
+                let mut result = template;
                 
                 for (key, val) in params {
                     let placeholder = format!("{{{}}}", key);
@@ -239,7 +249,7 @@ impl StatementHandler for StringFormatHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "string.format"
     }
     
@@ -0,0 +1,264 @@
+use crate::error::NjilError;
+use serde_json::{Map, Value};
+use crate::interpreter::Interpreter;
+use crate::errortip::task as tip;
+use crate::task::{self, ErrorPolicy};
+use super::StatementHandler;
+
+/// 并行语句处理器，在各自的线程中同时执行多个语句块，按顺序返回它们的结果
+///
+/// 参数是语句块数组，或 `{"tasks": [语句块...], "errors": "fail_fast"|"collect"}`。
+/// 每个语句块的结果是return的值或最后一条语句的值；语句块可以读取外层的变量，赋值只影响自己的作用域。
+pub struct ParallelHandler;
+
+// 静态实例
+pub static PARALLEL_HANDLER: ParallelHandler = ParallelHandler;
+
+impl StatementHandler for ParallelHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (blocks, errors) = match value {
+            Value::Array(blocks) => (blocks, ErrorPolicy::default()),
+            Value::Object(obj) if !super::is_statement(interpreter, obj) => match obj.get("tasks") {
+                Some(Value::Array(blocks)) => (blocks, error_policy(interpreter, obj)?),
+                _ => return Err(NjilError::ExecutionError(tip::tasks_required(self.name()))),
+            },
+            _ => return Err(NjilError::ExecutionError(tip::tasks_required(self.name()))),
+        };
+        
+        let tasks = blocks.iter()
+            .map(|block| interpreter.spawn_task(block))
+            .collect::<Result<Vec<Value>, NjilError>>()?;
+        let outcomes = interpreter.await_tasks(&tasks, errors)?;
+        results_value(outcomes, errors)
+    }
+    
+    fn name(&self) -> &str {
+        "parallel"
+    }
+}
+
+/// task.spawn语句处理器，在新线程中开始执行语句块，立即返回任务值
+pub struct TaskSpawnHandler;
+
+// 静态实例
+pub static TASK_SPAWN_HANDLER: TaskSpawnHandler = TaskSpawnHandler;
+
+impl StatementHandler for TaskSpawnHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        interpreter.spawn_task(value)
+    }
+    
+    fn name(&self) -> &str {
+        "task.spawn"
+    }
+}
+
+/// task.await语句处理器，等待任务结束并返回它的结果，任务出错时抛出它的错误
+pub struct TaskAwaitHandler;
+
+// 静态实例
+pub static TASK_AWAIT_HANDLER: TaskAwaitHandler = TaskAwaitHandler;
+
+impl StatementHandler for TaskAwaitHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let task = interpreter.evaluate_value(value)?;
+        let mut outcomes = interpreter.await_tasks(&[task], ErrorPolicy::FailFast)?;
+        outcomes.pop().unwrap_or(Ok(Value::Null))
+    }
+    
+    fn name(&self) -> &str {
+        "task.await"
+    }
+}
+
+/// task.all语句处理器，等待一组任务结束，按顺序返回它们的结果
+///
+/// 参数是任务数组，或 `{"tasks": 任务数组, "errors": "fail_fast"|"collect"}`。
+pub struct TaskAllHandler;
+
+// 静态实例
+pub static TASK_ALL_HANDLER: TaskAllHandler = TaskAllHandler;
+
+impl StatementHandler for TaskAllHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let (tasks, errors) = match value {
+            Value::Object(obj) if !super::is_statement(interpreter, obj) => match obj.get("tasks") {
+                Some(tasks) => (evaluate_tasks(interpreter, tasks)?, error_policy(interpreter, obj)?),
+                None => return Err(NjilError::ExecutionError(tip::tasks_required(self.name()))),
+            },
+            other => (evaluate_tasks(interpreter, other)?, ErrorPolicy::default()),
+        };
+        let Some(tasks) = tasks else {
+            return Err(NjilError::ExecutionError(tip::tasks_required(self.name())));
+        };
+        
+        let outcomes = interpreter.await_tasks(&tasks, errors)?;
+        results_value(outcomes, errors)
+    }
+    
+    fn name(&self) -> &str {
+        "task.all"
+    }
+}
+
+// 辅助函数：求值任务数组，数组中的每个元素分别求值，不是数组时返回None
+fn evaluate_tasks(interpreter: &mut Interpreter, value: &Value) -> Result<Option<Vec<Value>>, NjilError> {
+    match value {
+        Value::Array(items) => items.iter()
+            .map(|item| interpreter.evaluate_value(item))
+            .collect::<Result<Vec<Value>, NjilError>>()
+            .map(Some),
+        other => match interpreter.evaluate_value(other)? {
+            Value::Array(items) => Ok(Some(items)),
+            _ => Ok(None),
+        },
+    }
+}
+
+// 辅助函数：求值可选的errors字段，默认为快速失败
+fn error_policy(interpreter: &mut Interpreter, obj: &Map<String, Value>) -> Result<ErrorPolicy, NjilError> {
+    let Some(errors) = obj.get("errors") else {
+        return Ok(ErrorPolicy::default());
+    };
+    let errors = interpreter.evaluate_value(errors)?;
+    errors.as_str()
+        .and_then(ErrorPolicy::parse)
+        .ok_or_else(|| NjilError::ExecutionError(tip::unknown_error_policy(&errors)))
+}
+
+// 辅助函数：快速失败时返回结果数组，收集错误时返回每个任务的结果对象
+fn results_value(outcomes: Vec<Result<Value, NjilError>>, errors: ErrorPolicy) -> Result<Value, NjilError> {
+    match errors {
+        ErrorPolicy::FailFast => outcomes.into_iter().collect::<Result<Vec<Value>, NjilError>>().map(Value::Array),
+        ErrorPolicy::Collect => Ok(Value::Array(outcomes.iter().map(task::outcome_value).collect())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::{Duration, Instant};
+    use crate::policy::ExecutionPolicy;
+    
+    #[test]
+    fn test_parallel_runs_blocks_concurrently() {
+        let mut interpreter = Interpreter::new();
+        // 两个语句块都到达之后才返回true，依次执行时先到达的一方等待超时，返回false
+        let arrivals = Arc::new((Mutex::new(0), Condvar::new()));
+        interpreter.register_instruction("test.meet", move |_, _| {
+            let (count, arrived) = &*arrivals;
+            let mut count = count.lock().unwrap();
+            *count += 1;
+            arrived.notify_all();
+            let (count, _) = arrived.wait_timeout_while(count, Duration::from_secs(5), |count| *count < 2).unwrap();
+            Ok(Value::Bool(*count >= 2))
+        });
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "base", "value": 10}},
+            {"var.set": {"name": "results", "value": {"parallel": [
+                [{"test.meet": null}, {"var.set": {"name": "base", "value": 1}}, {"expr": "base + 1"}],
+                [{"test.meet": null}, {"return": {"expr": "base * 2"}}],
+                {"string.concat": ["x", {"var": "base"}]}
+            ]}}},
+            {"return": {"string.concat": [{"var": "results"}, " ", {"var": "base"}]}}
+        ]"#).unwrap();
+        // 语句块同时执行，赋值只影响语句块自己的作用域
+        assert_eq!(result, json!("2, 20, x10 10"));
+        let result = interpreter.run_njis_str(r#"[{"parallel": [[{"test.meet": null}], [{"test.meet": null}]]}]"#).unwrap();
+        assert_eq!(result, json!([true, true]));
+        
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "square", "value": {"fn": {"params": ["n"], "body": [{"return": {"expr": "n * n"}}]}}}},
+            {"var.set": {"name": "a", "value": {"task.spawn": {"function.call": {"name": "square", "args": [3]}}}}},
+            {"var.set": {"name": "b", "value": {"task.spawn": [{"sleep": 10}, {"function.call": {"name": "square", "args": [4]}}]}}},
+            {"var.set": {"name": "second", "value": {"task.await": {"var": "b"}}}},
+            {"var.set": {"name": "first", "value": {"task.all": [{"var": "a"}]}}},
+            {"return": {"type.of": {"task.spawn": []}}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("task"));
+        assert_eq!(interpreter.get_global::<Value>("first").unwrap(), json!([9]));
+        assert_eq!(interpreter.get_global::<Value>("second").unwrap(), json!(16));
+    }
+    
+    #[test]
+    fn test_task_errors() {
+        let mut interpreter = Interpreter::new();
+        let started = Instant::now();
+        let result = interpreter.run_njis_str(r#"[
+            {"try": {
+                "try": [{"parallel": [
+                    [{"throw": "第一个失败"}],
+                    [{"loop.while": {"condition": true, "body": [{"sleep": 1}]}}]
+                ]}],
+                "catch": {"var": "e", "body": [{"return": {"var": "e.message"}}]}
+            }}
+        ]"#).unwrap();
+        // 快速失败：返回第一个错误，其余的任务被取消
+        assert_eq!(result, json!("第一个失败"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "results", "value": {"parallel": {"errors": "collect", "tasks": [
+                [{"throw": "坏掉了"}],
+                [{"return": 1}]
+            ]}}}},
+            {"return": {"var": "results"}}
+        ]"#).unwrap();
+        assert_eq!(result[0]["ok"], json!(false));
+        assert_eq!(result[0]["error"]["message"], json!("坏掉了"));
+        assert_eq!(result[1], json!({"ok": true, "value": 1}));
+        
+        let error = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "t", "value": {"task.spawn": 1}}},
+            {"task.await": {"var": "t"}},
+            {"task.await": {"var": "t"}}
+        ]"#).unwrap_err();
+        assert!(error.message().contains("已经被等待过"));
+        let error = interpreter.run_njis_str(r#"[{"task.await": [1]}]"#).unwrap_err();
+        assert!(error.message().contains("不是任务"));
+        let error = interpreter.run_njis_str(r#"[{"parallel": {"tasks": [], "errors": "ignore"}}]"#).unwrap_err();
+        assert!(error.message().contains("fail_fast"));
+    }
+    
+    #[test]
+    fn test_tasks_share_the_statement_limit() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(ExecutionPolicy::new().max_statements(1000));
+        let error = interpreter.run_njis_str(r#"[
+            {"parallel": [
+                [{"loop.while": {"condition": true, "body": []}}],
+                [{"loop.while": {"condition": true, "body": []}}]
+            ]}
+        ]"#).unwrap_err();
+        assert!(matches!(error.into_root(), NjilError::LimitExceeded(msg) if msg.contains("语句数")));
+    }
+    
+    #[test]
+    fn test_task_limit() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_policy(ExecutionPolicy::new().max_tasks(2));
+        let result = interpreter.run_njis_str(r#"[{"parallel": [[1], [2]]}, {"parallel": [[3], [4]]}]"#).unwrap();
+        assert_eq!(result, json!([3, 4]));
+        
+        let error = interpreter.run_njis_str(r#"[
+            {"parallel": [[{"sleep": 200}], [{"sleep": 200}], [{"sleep": 200}]]}
+        ]"#).unwrap_err();
+        assert!(matches!(error.into_root(), NjilError::LimitExceeded(msg) if msg.contains("任务数")));
+    }
+    
+    #[test]
+    fn test_unawaited_tasks_are_removed() {
+        let mut interpreter = Interpreter::new();
+        // 没有被等待、任务值也不再被引用的任务在结束后被移出任务表
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "kept", "value": {"task.spawn": [{"sleep": 50}, 42]}}},
+            {"loop.for": {"var": "i", "count": 1000, "body": [{"task.spawn": [{"var": "i"}]}]}},
+            {"return": {"task.await": {"var": "kept"}}}
+        ]"#).unwrap();
+        
+        assert_eq!(result, json!(42));
+        assert!(interpreter.tasks.len() < 500);
+    }
+}
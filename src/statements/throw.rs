@@ -1,6 +1,5 @@
 use crate::error::NjilError;
 use crate::interpreter::Interpreter;
-use crate::errortip;
 use crate::statements::StatementHandler;
 use serde_json::Value;
 
@@ -12,17 +11,14 @@ pub static THROW_HANDLER: ThrowHandler = ThrowHandler;
 
 impl StatementHandler for ThrowHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
-        // 首先评估异常值
-        let exception_value = match interpreter.evaluate_value(value) {
-            Ok(val) => val,
-            Err(e) => return Err(e),
-        };
+        // 首先评估异常值，对象和数组中嵌套的语句也会被求值
+        let exception_value = interpreter.evaluate_deep(value)?;
         
         // 抛出异常
         Err(NjilError::ThrowException(exception_value))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "throw"
     }
     
@@ -67,4 +63,19 @@ mod tests {
             panic!("应该抛出ThrowException类型的异常");
         }
     }
+    
+    #[test]
+    fn test_throw_evaluates_nested_statements() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "reason", "value": "超时"}},
+            {"try": {
+                "try": [{"throw": {"origin": "inner", "message": {"var": "reason"}, "tags": [{"string.concat": ["a", "b"]}]}}],
+                "catch": {"var": "e", "body": [{"return": {"var": "e"}}]}
+            }}
+        ]"#).unwrap();
+        assert_eq!(result["message"], json!("超时"));
+        assert_eq!(result["origin"], json!("inner"));
+        assert_eq!(result["tags"], json!(["ab"]));
+    }
 } 
\ No newline at end of file
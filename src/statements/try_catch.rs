@@ -1,8 +1,12 @@
+use crate::completion::Completion;
 use crate::error::NjilError;
 use crate::interpreter::Interpreter;
 use crate::errortip;
 use crate::statements::StatementHandler;
-use crate::statements::handle_statement;
+use crate::statements::{self, block_statements, execute_block, execute_statements};
+use crate::utils::type_convert::to_bool;
+use crate::scope::ScopeKind;
+use crate::stack::stack_to_value;
 use serde_json::Value;
 
 /// Try/Catch语句处理器，用于捕获异常
@@ -13,6 +17,10 @@ pub static TRY_CATCH_HANDLER: TryCatchHandler = TryCatchHandler;
 
 impl StatementHandler for TryCatchHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        self.execute(interpreter, value)?.into_value()
+    }
+    
+    fn execute(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Completion, NjilError> {
         if let Value::Object(try_catch_obj) = value {
             // 检查必要的字段
             if !try_catch_obj.contains_key("try") {
@@ -24,55 +32,36 @@ impl StatementHandler for TryCatchHandler {
             // 获取try块
             let try_block = try_catch_obj.get("try").unwrap();
             
-            // 执行try块（可以是单个语句或语句数组）
-            let try_result = match try_block {
-                Value::Array(statements) => {
-                    let mut last_result = Value::Null;
-                    for stmt in statements {
-                        match handle_statement(interpreter, stmt) {
-                            Ok(result) => {
-                                // 检查是否有返回值或中断
-                                if interpreter.is_returning() {
-                                    return Ok(Value::Null);
-                                }
-                                last_result = result;
-                            },
-                            Err(e) => {
-                                // 如果是异常，检查是否有catch块
-                                if let Some(catch_block) = try_catch_obj.get("catch") {
-                                    return handle_catch_block(interpreter, catch_block, e);
-                                } else {
-                                    // 没有catch块，异常继续向上抛出
-                                    return Err(e);
-                                }
-                            }
-                        }
-                    }
-                    Ok(last_result)
-                },
-                _ => {
-                    match handle_statement(interpreter, try_block) {
-                        Ok(result) => Ok(result),
-                        Err(e) => {
-                            // 如果是异常，检查是否有catch块
-                            if let Some(catch_block) = try_catch_obj.get("catch") {
-                                handle_catch_block(interpreter, catch_block, e)
-                            } else {
-                                // 没有catch块，异常继续向上抛出
-                                Err(e)
-                            }
-                        }
+            // 在块作用域中执行try块（可以是单个语句或语句数组），
+            // return/break/continue不是异常，不会被catch捕获
+            let result = match execute_block(interpreter, try_block) {
+                Ok(completion) => Ok(completion),
+                Err(e) => {
+                    // 如果是异常，检查是否有catch块
+                    if let Some(catch_block) = try_catch_obj.get("catch") {
+                        handle_catch_block(interpreter, catch_block, e)
+                    } else {
+                        // 没有catch块，异常继续向上抛出
+                        Err(e)
                     }
                 }
             };
             
-            try_result
+            // finally块总会执行，包括return、loop.break和继续抛出的异常；
+            // finally块自身出错或以return、loop.break、loop.continue结束时替换原来的结果
+            match try_catch_obj.get("finally") {
+                Some(finally_block) => match execute_block(interpreter, finally_block)? {
+                    Completion::Normal(_) => result,
+                    abrupt => Ok(abrupt),
+                },
+                None => result,
+            }
         } else {
             Err(NjilError::ExecutionError(errortip::exception::try_catch_requires_object().to_string()))
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "try"
     }
     
@@ -81,69 +70,98 @@ impl StatementHandler for TryCatchHandler {
     }
 }
 
-/// 处理catch块
-fn handle_catch_block(interpreter: &mut Interpreter, catch_block: &Value, error: NjilError) -> Result<Value, NjilError> {
-    // 检查是否是用户抛出的异常
-    let error_value = match error {
-        NjilError::ThrowException(value) => value,
-        // 对于其他类型的错误，转换为字符串异常值
-        other => Value::String(other.to_string()),
+// 辅助函数：判断对象是否是catch子句（而不是一条语句）
+fn is_catch_clause(interpreter: &Interpreter, value: &Value) -> bool {
+    match value {
+        Value::Object(obj) => !statements::is_statement(interpreter, obj)
+            && CLAUSE_FIELDS.iter().any(|field| obj.contains_key(*field)),
+        _ => false,
+    }
+}
+
+/// catch子句可以包含的字段
+const CLAUSE_FIELDS: [&str; 5] = ["var", "stack", "type", "when", "body"];
+
+/// 处理catch块，catch块在独立的块作用域中执行，异常变量不会泄漏到外层
+///
+/// catch可以是一个子句对象、子句数组或语句数组。
+/// 子句依次匹配，没有子句匹配时异常继续向上抛出。
+fn handle_catch_block(interpreter: &mut Interpreter, catch_block: &Value, error: NjilError) -> Result<Completion, NjilError> {
+    let clauses: Vec<&Value> = match catch_block {
+        Value::Array(items) if items.first().is_some_and(|item| is_catch_clause(interpreter, item)) => items.iter().collect(),
+        Value::Object(_) => vec![catch_block],
+        // catch块是语句数组或单条语句
+        _ => return execute_block(interpreter, catch_block),
     };
     
-    // 检查catch块类型
-    match catch_block {
-        Value::Object(catch_obj) => {
-            // 检查是否有var字段，用于存储异常值
-            if let Some(var_value) = catch_obj.get("var") {
+    let error_value = error.to_value();
+    let stack = stack_to_value(error.stack());
+    // 抛出的对象可以用自己的type字段指定异常类型
+    let error_type = error_value.get("type").and_then(Value::as_str).unwrap_or(error.error_type());
+    
+    for clause in clauses {
+        let Value::Object(clause) = clause else {
+            return Err(NjilError::ExecutionError(errortip::exception::catch_clause_requires_object().to_string()));
+        };
+        if !clause_matches_type(clause, error_type)? {
+            continue;
+        }
+        
+        let handled = interpreter.with_scope(ScopeKind::Block, |interpreter| {
+            // 检查是否有var字段，用于存储异常对象
+            if let Some(var_value) = clause.get("var") {
                 if let Value::String(var_name) = var_value {
-                    // 将异常值设置到变量中 - 需要克隆值
-                    interpreter.set_variable(var_name.clone(), error_value.clone());
+                    // 将异常对象声明到catch块的作用域中
+                    interpreter.declare_variable(var_name.clone(), error_value.clone());
                 } else {
                     return Err(NjilError::ExecutionError(errortip::exception::catch_var_requires_string().to_string()));
                 }
             }
             
-            // 执行catch块的body
-            if let Some(body) = catch_obj.get("body") {
-                // 执行catch块的主体
-                match body {
-                    Value::Array(statements) => {
-                        let mut last_result = Value::Null;
-                        for stmt in statements {
-                            // 检查是否有返回值或中断
-                            if interpreter.is_returning() {
-                                return Ok(Value::Null);
-                            }
-                            
-                            last_result = handle_statement(interpreter, stmt)?;
-                        }
-                        Ok(last_result)
-                    },
-                    _ => handle_statement(interpreter, body)
+            // 检查是否有stack字段，用于存储异常发生时的调用栈
+            if let Some(stack_var) = clause.get("stack") {
+                if let Value::String(stack_var) = stack_var {
+                    interpreter.declare_variable(stack_var.clone(), stack.clone());
+                } else {
+                    return Err(NjilError::ExecutionError(errortip::exception::catch_stack_requires_string().to_string()));
                 }
-            } else {
-                // 如果没有body，返回异常值本身
-                Ok(error_value)
             }
-        },
-        Value::Array(statements) => {
-            // catch块是语句数组
-            let mut last_result = Value::Null;
-            for stmt in statements {
-                // 检查是否有返回值或中断
-                if interpreter.is_returning() {
-                    return Ok(Value::Null);
+            
+            // when条件在异常变量声明之后求值，不满足时尝试下一个子句
+            if let Some(condition) = clause.get("when") {
+                let condition = interpreter.evaluate_value(condition)?;
+                if !to_bool(&condition) {
+                    return Ok(None);
                 }
-                
-                last_result = handle_statement(interpreter, stmt)?;
             }
-            Ok(last_result)
-        },
-        _ => {
-            // catch块是单条语句
-            handle_statement(interpreter, catch_block)
+            
+            // 执行catch块的body
+            match clause.get("body") {
+                Some(body) => execute_statements(interpreter, block_statements(body)).map(Some),
+                // 如果没有body，返回异常对象本身
+                None => Ok(Some(Completion::Normal(error_value.clone()))),
+            }
+        })?;
+        
+        if let Some(result) = handled {
+            return Ok(result);
         }
     }
+    
+    // 没有匹配的子句，异常继续向上抛出
+    Err(error)
+}
+
+// 辅助函数：检查子句的type字段是否匹配错误类型
+fn clause_matches_type(clause: &serde_json::Map<String, Value>, error_type: &str) -> Result<bool, NjilError> {
+    match clause.get("type") {
+        None => Ok(true),
+        Some(Value::String(expected)) => Ok(expected == error_type),
+        Some(Value::Array(types)) if types.iter().all(Value::is_string) => {
+            Ok(types.iter().any(|expected| expected.as_str() == Some(error_type)))
+        },
+        Some(_) => Err(NjilError::ExecutionError(errortip::exception::catch_type_invalid().to_string())),
+    }
 }
 
 #[cfg(test)]
@@ -164,7 +182,7 @@ mod tests {
         
         let result = TRY_CATCH_HANDLER.handle(&mut interpreter, &try_catch_value);
         assert!(result.is_ok());
-        assert_eq!(interpreter.get_variable("result").unwrap(), &json!("success"));
+        assert_eq!(interpreter.get_variable("result").unwrap(), json!("success"));
         
         // 测试捕获异常的情况
         let try_catch_value = json!({
@@ -174,14 +192,102 @@ mod tests {
             "catch": {
                 "var": "error",
                 "body": [
-                    {"var.set": {"name": "result", "value": "caught"}}
+                    {"var.set": {"name": "result", "value": "caught"}},
+                    {"var.set": {"name": "caught_error", "value": {"var": "error"}}}
                 ]
             }
         });
         
         let result = TRY_CATCH_HANDLER.handle(&mut interpreter, &try_catch_value);
         assert!(result.is_ok());
-        assert_eq!(interpreter.get_variable("error").unwrap(), &json!("测试异常"));
-        assert_eq!(interpreter.get_variable("result").unwrap(), &json!("caught"));
+        let caught_error = interpreter.get_variable("caught_error").unwrap();
+        assert_eq!(caught_error["type"], json!("user"));
+        assert_eq!(caught_error["message"], json!("测试异常"));
+        assert_eq!(caught_error["data"], json!("测试异常"));
+        assert_eq!(interpreter.get_variable("result").unwrap(), json!("caught"));
+        
+        // 异常变量只在catch块中可见
+        assert!(interpreter.get_variable("error").is_none());
+    }
+    
+    #[test]
+    fn test_catch_clauses_filter_by_type() {
+        let mut interpreter = Interpreter::new();
+        
+        // 按类型选择子句，内置错误也是对象
+        let value = json!({
+            "try": {"var": "missing"},
+            "catch": [
+                {"type": "user", "body": {"var.set": {"name": "handled_by", "value": "user"}}},
+                {"type": ["io", "undefined_variable"], "var": "e", "body": [
+                    {"var.set": {"name": "handled_by", "value": "undefined"}},
+                    {"var.set": {"name": "missing_name", "value": {"var": "e.data.name"}}}
+                ]}
+            ]
+        });
+        TRY_CATCH_HANDLER.handle(&mut interpreter, &value).unwrap();
+        assert_eq!(interpreter.get_variable("handled_by").unwrap(), json!("undefined"));
+        assert_eq!(interpreter.get_variable("missing_name").unwrap(), json!("missing"));
+        
+        // when条件可以访问异常变量，抛出的对象保留自己的字段
+        let value = json!({
+            "try": {"throw": {"type": "http", "code": 404, "message": "未找到"}},
+            "catch": [
+                {"var": "e", "when": {"math.compare": {"left": {"var": "e.code"}, "op": ">=", "right": 500}}, "body": "server"},
+                {"var": "e", "when": {"math.compare": {"left": {"var": "e.code"}, "op": "==", "right": 404}}, "body": {"var": "e.message"}}
+            ]
+        });
+        assert_eq!(TRY_CATCH_HANDLER.handle(&mut interpreter, &value).unwrap(), json!("未找到"));
+    }
+    
+    #[test]
+    fn test_unmatched_catch_rethrows() {
+        let mut interpreter = Interpreter::new();
+        let value = json!({
+            "try": {"throw": "原始异常"},
+            "catch": {"type": "io", "body": "不会执行"}
+        });
+        match TRY_CATCH_HANDLER.handle(&mut interpreter, &value).map_err(NjilError::into_root) {
+            Err(NjilError::ThrowException(thrown)) => assert_eq!(thrown, json!("原始异常")),
+            other => panic!("异常应当继续抛出: {:?}", other),
+        }
+        
+        // return不会被catch捕获
+        let value = json!({
+            "try": {"return": 1},
+            "catch": {"body": "不会执行"}
+        });
+        assert_eq!(TRY_CATCH_HANDLER.execute(&mut interpreter, &value).unwrap(), Completion::Return(json!(1)));
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_finally_always_runs() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load_njil_str(r#"{"program": {
+            "cleanup": {"params": ["mode"], "body": [
+                {"global": "log"},
+                {"loop.while": {"condition": true, "body": [
+                    {"try": {
+                        "try": [
+                            {"if": {"condition": {"math.compare": {"left": {"var": "mode"}, "op": "==", "right": "return"}}, "then": [{"return": "returned"}]}},
+                            {"if": {"condition": {"math.compare": {"left": {"var": "mode"}, "op": "==", "right": "break"}}, "then": [{"loop.break": null}]}},
+                            {"throw": "rethrown"}
+                        ],
+                        "catch": {"type": "io"},
+                        "finally": {"var.set": {"name": "log", "value": {"string.concat": [{"var": "log"}, {"var": "mode"}, ";"]}}}
+                    }}
+                ]}},
+                {"return": "broke"}
+            ]}
+        }}"#).unwrap();
+        
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "log", "value": ""}},
+            {"var.set": {"name": "a", "value": {"function.call": {"name": "cleanup", "args": ["return"]}}}},
+            {"var.set": {"name": "b", "value": {"function.call": {"name": "cleanup", "args": ["break"]}}}},
+            {"try": {"try": {"function.call": {"name": "cleanup", "args": ["throw"]}}, "catch": {"var": "e"}}},
+            {"return": {"string.concat": [{"var": "a"}, ",", {"var": "b"}, ",", {"var": "log"}]}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("returned,broke,return;break;throw;"));
+    }
+}
@@ -29,6 +29,11 @@ impl StatementHandler for TypeConvertHandler {
                 _ => return Err(NjilError::ExecutionError("type字段必须是字符串".to_string())),
             };
             
+            // 范围转换为数组时先检查长度
+            if let (Some(range), "array" | "list") = (type_convert::as_range(&source_value), target_type.to_lowercase().as_str()) {
+                return range.to_vec().map(Value::Array);
+            }
+            
             // 执行类型转换
             Ok(type_convert::convert_to_type(&source_value, target_type))
         } else {
@@ -38,7 +43,7 @@ impl StatementHandler for TypeConvertHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.convert"
     }
     
@@ -59,7 +64,7 @@ impl StatementHandler for ToBoolHandler {
         Ok(Value::Bool(type_convert::to_bool(&evaluated)))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.bool"
     }
     
@@ -88,7 +93,7 @@ impl StatementHandler for ToNumberHandler {
         Ok(Value::Null)
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.number"
     }
     
@@ -109,7 +114,7 @@ impl StatementHandler for ToStringHandler {
         Ok(Value::String(type_convert::to_string(&evaluated)))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.string"
     }
     
@@ -127,10 +132,13 @@ pub static TO_ARRAY_HANDLER: ToArrayHandler = ToArrayHandler;
 impl StatementHandler for ToArrayHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
         let evaluated = interpreter.evaluate_value(value)?;
+        if let Some(range) = type_convert::as_range(&evaluated) {
+            return range.to_vec().map(Value::Array);
+        }
         Ok(Value::Array(type_convert::to_array(&evaluated)))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.array"
     }
     
@@ -151,7 +159,7 @@ impl StatementHandler for ToObjectHandler {
         Ok(Value::Object(type_convert::to_object(&evaluated)))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.object"
     }
     
@@ -170,19 +178,10 @@ impl StatementHandler for TypeOfHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
         let evaluated = interpreter.evaluate_value(value)?;
         
-        let type_name = match evaluated {
-            Value::Null => "null",
-            Value::Bool(_) => "boolean",
-            Value::Number(_) => "number",
-            Value::String(_) => "string",
-            Value::Array(_) => "array",
-            Value::Object(_) => "object",
-        };
-        
-        Ok(Value::String(type_name.to_string()))
+        Ok(Value::String(type_convert::type_name(&evaluated).to_string()))
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "type.of"
     }
     
@@ -4,7 +4,9 @@ use crate::interpreter::Interpreter;
 use crate::errortip;
 use crate::debug_println;
 use super::StatementHandler;
+use super::pattern::{self, ArrayPattern, REST_PREFIX};
 use crate::utils::path::{self, PathPart};
+use crate::utils::type_convert;
 
 /// 变量设置语句处理器
 pub struct VarSetHandler;
@@ -15,11 +17,20 @@ pub static VAR_SET_HANDLER: VarSetHandler = VarSetHandler;
 impl StatementHandler for VarSetHandler {
     fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
         if let Value::Object(var_obj) = value {
-            // 检查是否有name和value字段
-            if !var_obj.contains_key("name") || !var_obj.contains_key("value") {
-                return Err(NjilError::ExecutionError(
-                    "var.set需要name和value字段".to_string()
-                ));
+            // 检查是否有name（或pattern）和value字段
+            if !(var_obj.contains_key("name") || var_obj.contains_key("pattern")) || !var_obj.contains_key("value") {
+                return Err(NjilError::ExecutionError(errortip::var::var_set_missing_fields().to_string()));
+            }
+            
+            // 解构赋值：按模式把值拆分后逐个赋值
+            if let Some(pattern) = var_obj.get("pattern") {
+                let var_value = interpreter.evaluate_value(var_obj.get("value").unwrap())?;
+                let mut bindings = Vec::new();
+                destructure(interpreter, pattern, Some(var_value), &mut bindings)?;
+                for (var_name, value) in bindings {
+                    interpreter.set_variable_path(&var_name, value)?;
+                }
+                return Ok(Value::Null);
             }
             
             // 获取变量名
@@ -34,14 +45,9 @@ impl StatementHandler for VarSetHandler {
             // 获取变量值
             let var_value = interpreter.evaluate_value(var_obj.get("value").unwrap())?;
             debug_println!("变量 {} 的值为: {}", var_name, serde_json::to_string_pretty(&var_value).unwrap());
-                
-                // 检查是否为嵌套路径
-            if var_name.contains('.') || var_name.contains('[') {
-                path::set_nested_value(&mut interpreter.variables, &var_name, var_value)?;
-                } else {
-                    // 普通变量设置
-                interpreter.variables.insert(var_name, var_value);
-            }
+            
+            // 赋值到变量所在的作用域，支持嵌套路径
+            interpreter.set_variable_path(&var_name, var_value)?;
             
             Ok(Value::Null)
         } else {
@@ -49,11 +55,75 @@ impl StatementHandler for VarSetHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "var.set"
     }
 }
 
+/// 解构：按模式把值拆分为若干变量绑定，追加到bindings
+///
+/// - 字符串：变量名
+/// - 数组：按位置解构数组，最后一项可以是 `"...rest"` 或 `{"$rest": "rest"}`，收集剩余的元素
+/// - 对象：键为字段名，值为该字段的模式，键 `"..."` 收集其余字段
+/// - `{"$bind": "变量名", "$default": 表达式}` 或 `{"$pattern": 模式, "$default": 表达式}`：缺少值时使用默认值
+///
+/// value为None表示值不存在（数组越界或缺少字段），没有默认值时绑定为null。
+pub(crate) fn destructure(interpreter: &mut Interpreter, pattern: &Value, value: Option<Value>, bindings: &mut Vec<(String, Value)>) -> Result<(), NjilError> {
+    match pattern {
+        Value::String(name) => {
+            bindings.push((name.clone(), value.unwrap_or(Value::Null)));
+            Ok(())
+        },
+        Value::Object(obj) if !obj.is_empty() && obj.keys().all(|key| key.starts_with('$')) => {
+            let target = match (obj.get("$bind"), obj.get("$pattern")) {
+                (Some(target), None) | (None, Some(target)) => target,
+                _ => return Err(NjilError::ExecutionError(errortip::var::destructure_invalid_pattern(&pattern.to_string()))),
+            };
+            if let Some(key) = obj.keys().find(|key| !matches!(key.as_str(), "$bind" | "$pattern" | "$default")) {
+                return Err(NjilError::ExecutionError(errortip::var::destructure_invalid_pattern(key)));
+            }
+            let value = match (value, obj.get("$default")) {
+                (None, Some(default)) => Some(interpreter.evaluate_value(default)?),
+                (value, _) => value,
+            };
+            destructure(interpreter, target, value, bindings)
+        },
+        Value::Array(targets) => {
+            let items = match value {
+                Some(Value::Array(items)) => items,
+                None => Vec::new(),
+                Some(other) => return Err(NjilError::TypeError(errortip::var::destructure_type_mismatch("数组", type_convert::type_name(&other)))),
+            };
+            let ArrayPattern { items: targets, rest } = pattern::split_array(targets)?;
+            for (i, target) in targets.iter().enumerate() {
+                destructure(interpreter, target, items.get(i).cloned(), bindings)?;
+            }
+            if let Some(Some(rest)) = rest {
+                bindings.push((rest.to_string(), Value::Array(items.get(targets.len()..).unwrap_or_default().to_vec())));
+            }
+            Ok(())
+        },
+        Value::Object(fields) => {
+            let mut object = match value {
+                Some(Value::Object(object)) => object,
+                None => serde_json::Map::new(),
+                Some(other) => return Err(NjilError::TypeError(errortip::var::destructure_type_mismatch("对象", type_convert::type_name(&other)))),
+            };
+            for (key, target) in fields {
+                if key != REST_PREFIX {
+                    destructure(interpreter, target, object.remove(key), bindings)?;
+                }
+            }
+            // 其余字段收集到一个对象中
+            if let Some(rest) = fields.get(REST_PREFIX) {
+                destructure(interpreter, rest, Some(Value::Object(object)), bindings)?;
+            }
+            Ok(())
+        },
+        _ => Err(NjilError::ExecutionError(errortip::var::destructure_invalid_pattern(&pattern.to_string()))),
+    }
+}
+
 /// 多变量设置语句处理器 - 使用简洁的键值对格式
 pub struct VarSetMultiHandler;
 
@@ -71,13 +141,8 @@ impl StatementHandler for VarSetMultiHandler {
                 let var_value = interpreter.evaluate_value(var_value_raw)?;
                 debug_println!("设置变量 {} 的值: {}", var_name, serde_json::to_string_pretty(&var_value).unwrap());
                 
-                // 检查是否为嵌套路径
-                if var_name.contains('.') || var_name.contains('[') {
-                    path::set_nested_value(&mut interpreter.variables, var_name, var_value)?;
-                } else {
-                    // 普通变量设置
-                    interpreter.variables.insert(var_name.clone(), var_value);
-                }
+                // 赋值到变量所在的作用域，支持嵌套路径
+                interpreter.set_variable_path(var_name, var_value)?;
             }
             
             Ok(Value::Null)
@@ -86,7 +151,7 @@ impl StatementHandler for VarSetMultiHandler {
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "var.set.m"
     }
     
@@ -95,6 +160,86 @@ impl StatementHandler for VarSetMultiHandler {
     }
 }
 
+/// 全局变量声明处理器，声明后函数中对这些变量的读写都作用于全局作用域
+pub struct GlobalHandler;
+
+// 静态实例
+pub static GLOBAL_HANDLER: GlobalHandler = GlobalHandler;
+
+impl StatementHandler for GlobalHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        let names = match value {
+            Value::String(name) => vec![name.clone()],
+            Value::Array(items) => {
+                let mut names = Vec::with_capacity(items.len());
+                for item in items {
+                    match item {
+                        Value::String(name) => names.push(name.clone()),
+                        _ => return Err(NjilError::ExecutionError(errortip::var::global_requires_names().to_string())),
+                    }
+                }
+                names
+            },
+            _ => return Err(NjilError::ExecutionError(errortip::var::global_requires_names().to_string())),
+        };
+        
+        for name in names {
+            debug_println!("声明全局变量: {}", name);
+            interpreter.declare_global(name);
+        }
+        
+        Ok(Value::Null)
+    }
+    
+    fn name(&self) -> &str {
+        "global"
+    }
+    
+    fn aliases(&self) -> Vec<&'static str> {
+        vec!["var.global"]
+    }
+}
+
+/// 外层变量设置处理器，给当前函数帧之外的变量赋值
+pub struct VarOuterHandler;
+
+// 静态实例
+pub static VAR_OUTER_HANDLER: VarOuterHandler = VarOuterHandler;
+
+impl StatementHandler for VarOuterHandler {
+    fn handle(&self, interpreter: &mut Interpreter, value: &Value) -> Result<Value, NjilError> {
+        if let Value::Object(var_obj) = value {
+            // 检查是否有name和value字段
+            if !var_obj.contains_key("name") || !var_obj.contains_key("value") {
+                return Err(NjilError::ExecutionError(
+                    "var.outer需要name和value字段".to_string()
+                ));
+            }
+            
+            // 获取变量名
+            let name_value = interpreter.evaluate_value(var_obj.get("name").unwrap())?;
+            let var_name = match name_value {
+                Value::String(s) => s,
+                _ => return Err(NjilError::ExecutionError("变量名必须是字符串".to_string())),
+            };
+            
+            // 获取变量值
+            let var_value = interpreter.evaluate_value(var_obj.get("value").unwrap())?;
+            debug_println!("设置外层变量 {} 的值为: {}", var_name, serde_json::to_string_pretty(&var_value).unwrap());
+            
+            interpreter.set_outer_variable(&var_name, var_value)?;
+            
+            Ok(Value::Null)
+        } else {
+            Err(NjilError::ExecutionError("var.outer需要一个对象参数".to_string()))
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "var.outer"
+    }
+}
+
 /// 变量获取语句处理器
 pub struct VarHandler;
 
@@ -106,21 +251,18 @@ impl StatementHandler for VarHandler {
         if let Value::String(var_path) = value {
             // 解析变量路径
             if var_path.contains('.') || var_path.contains('[') {
-                return get_nested_variable(interpreter, var_path);
+                get_nested_variable(interpreter, var_path)
             } else {
                 // 普通变量访问
-                if let Some(var_value) = interpreter.variables.get(var_path) {
-                    return Ok(var_value.clone());
-                } else {
-                    return Err(NjilError::ExecutionError(errortip::var::undefined_variable(var_path)));
-                }
+                interpreter.get_variable(var_path)
+                    .ok_or_else(|| NjilError::UndefinedVariable(var_path.clone()))
             }
         } else {
             Err(NjilError::ExecutionError(errortip::var::var_requires_string().to_string()))
         }
     }
     
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "var"
     }
 }
@@ -136,19 +278,53 @@ pub fn get_nested_variable(interpreter: &Interpreter, var_path: &str) -> Result<
         _ => return Err(NjilError::ExecutionError("变量名不能是数组索引".to_string())),
     };
     
-    // 获取基础变量
-    if let Some(base_var) = interpreter.variables.get(base_var_name) {
-        // 如果路径只有一个部分，直接返回基础变量
-        if path_parts.len() == 1 {
-            return Ok(base_var.clone());
-        }
+    // 借用基础变量，只复制路径指向的值
+    interpreter.read_variable(base_var_name, |base_var| {
+        path::get_nested_value(base_var, &path_parts[1..]).cloned().unwrap_or(Value::Null)
+    }).ok_or_else(|| NjilError::UndefinedVariable(base_var_name.clone()))
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_destructuring_assignment() {
+        let mut interpreter = Interpreter::new();
+        let statement = json!({
+            "pattern": {
+                "name": "n",
+                "age": {"$bind": "a", "$default": 18},
+                "tags": ["first", "...others"],
+                "...": "extra"
+            },
+            "value": {"name": "小明", "tags": ["x", "y", "z"], "city": "北京"}
+        });
+        VAR_SET_HANDLER.handle(&mut interpreter, &statement).unwrap();
+        assert_eq!(interpreter.get_variable("n").unwrap(), json!("小明"));
+        assert_eq!(interpreter.get_variable("a").unwrap(), json!(18));
+        assert_eq!(interpreter.get_variable("first").unwrap(), json!("x"));
+        assert_eq!(interpreter.get_variable("others").unwrap(), json!(["y", "z"]));
+        assert_eq!(interpreter.get_variable("extra").unwrap(), json!({"city": "北京"}));
         
-        // 获取嵌套值
-        match path::get_nested_value(base_var, &path_parts[1..]) {
-            Ok(nested_value) => return Ok(nested_value.clone()),
-            Err(e) => return Err(e),
-        }
-    } else {
-        return Err(NjilError::ExecutionError(errortip::var::undefined_variable(base_var_name)));
+        // 模式与值的类型不匹配
+        let statement = json!({"pattern": ["a"], "value": "text"});
+        assert!(matches!(VAR_SET_HANDLER.handle(&mut interpreter, &statement), Err(NjilError::TypeError(_))));
+    }
+    
+    #[test]
+    fn test_foreach_destructuring() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.run_njis_str(r#"[
+            {"var.set": {"name": "text", "value": ""}},
+            {"loop.foreach": {"collection": {"json.new": {"a": 1, "b": 2}}, "var": {"key": "k", "value": "v"}, "body": [
+                {"var.set": {"name": "text", "value": {"string.concat": [{"var": "text"}, {"var": "k"}, "=", {"var": "v"}, ";"]}}}
+            ]}},
+            {"loop.foreach": {"collection": [["x", 1], ["y"]], "var": ["name", {"$bind": "count", "$default": 0}], "body": [
+                {"var.set": {"name": "text", "value": {"string.concat": [{"var": "text"}, {"var": "name"}, {"var": "count"}]}}}
+            ]}},
+            {"return": {"var": "text"}}
+        ]"#).unwrap();
+        assert_eq!(result, json!("a=1;b=2;x1y0"));
     }
-} 
\ No newline at end of file
+}
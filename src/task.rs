@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use serde_json::{Map, Value};
+use crate::closure::{self, ClosureTable, Pins};
+use crate::error::NjilError;
+use crate::errortip::task as tip;
+use crate::interpreter::Interpreter;
+use crate::policy;
+use crate::stack::Code;
+
+/// 任务值在JSON中的标记键
+///
+/// 任务以 `{"$task": id}` 的形式作为普通JSON值存储和传递，编号来自闭包表，
+/// 任务的结果保存在解释器及其子解释器共享的任务表中。
+pub const TASK_KEY: &str = "$task";
+
+/// 任务线程的栈大小，与主线程相同，避免任务中的递归过早溢出
+const TASK_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// 任务表中的任务数达到这个值之前不清理不再被引用的任务
+const MIN_PRUNE_THRESHOLD: usize = 64;
+
+/// 等待多个任务时的错误处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// 任意一个任务出错时立即返回这个错误，并取消其余的任务
+    #[default]
+    FailFast,
+    /// 等待所有任务结束，每个任务的结果是 `{"ok": true, "value": 值}` 或 `{"ok": false, "error": 异常对象}`
+    Collect,
+}
+
+impl ErrorPolicy {
+    /// 解析errors字段：fail_fast或collect
+    pub fn parse(name: &str) -> Option<ErrorPolicy> {
+        match name {
+            "fail_fast" => Some(ErrorPolicy::FailFast),
+            "collect" => Some(ErrorPolicy::Collect),
+            _ => None,
+        }
+    }
+}
+
+/// 取消标记，包括外层任务的标记，外层任务被取消时其中创建的任务也会停止
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CancelToken {
+    flags: Vec<Arc<AtomicBool>>,
+}
+
+impl CancelToken {
+    /// 在当前标记的基础上增加一个新任务的标记
+    fn child(&self, flag: Arc<AtomicBool>) -> CancelToken {
+        let mut flags = self.flags.clone();
+        flags.push(flag);
+        CancelToken { flags }
+    }
+    
+    /// 是否已经被取消
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.flags.iter().any(|flag| flag.load(Ordering::Relaxed))
+    }
+}
+
+// 任务表中的一项，pins固定结果中的闭包，由等待任务的解释器接管
+struct TaskEntry {
+    outcome: Option<Result<Value, NjilError>>,
+    pins: Option<Pins>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// 任务表，任务结束时写入结果并唤醒等待的线程
+///
+/// 已经结束的任务在被等待或者任务值不再被引用（闭包表中没有这个任务）之前保留结果。
+#[derive(Default)]
+pub(crate) struct TaskTable {
+    tasks: Mutex<HashMap<u64, TaskEntry>>,
+    finished: Condvar,
+    running: AtomicUsize,
+    threshold: AtomicUsize,
+}
+
+impl TaskTable {
+    /// 在新线程中用子解释器执行语句块，id是任务在闭包表中的编号
+    ///
+    /// 语句块在子解释器的当前作用域中执行，结果是return的值或最后一条语句的值。
+    /// 任务结束或者不能开始执行时设置done；执行中的任务数达到执行策略的上限时返回错误。
+    pub(crate) fn spawn(self: &Arc<Self>, mut child: Interpreter, block: Arc<Value>, code: Option<Code>, id: u64, done: Arc<AtomicBool>) -> Result<(), NjilError> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        child.cancel = child.cancel.child(cancel.clone());
+        {
+            let mut tasks = self.tasks.lock().unwrap();
+            if let Err(error) = policy::check_tasks(child.policy(), self.running.load(Ordering::Relaxed)) {
+                done.store(true, Ordering::Relaxed);
+                return Err(error);
+            }
+            self.prune(&mut tasks, &child.closures);
+            tasks.insert(id, TaskEntry { outcome: None, pins: None, cancel });
+            self.running.fetch_add(1, Ordering::Relaxed);
+        }
+        
+        let table = self.clone();
+        let task_done = done.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("task {}", id))
+            .stack_size(TASK_STACK_SIZE)
+            .spawn(move || {
+                let result = child.run_task(&block, code);
+                let pins = child.transfer_pins(closure::kept_value(result.as_ref().map(Some)));
+                table.finish(id, result, pins, &task_done);
+            });
+        if let Err(e) = spawned {
+            self.tasks.lock().unwrap().remove(&id);
+            self.running.fetch_sub(1, Ordering::Relaxed);
+            done.store(true, Ordering::Relaxed);
+            return Err(NjilError::ExecutionError(tip::spawn_failed(&e.to_string())));
+        }
+        Ok(())
+    }
+    
+    // 辅助函数：记录任务的结果，已经被取消或清理而移出任务表的任务的结果被丢弃
+    fn finish(&self, id: u64, result: Result<Value, NjilError>, pins: Pins, done: &AtomicBool) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(entry) = tasks.get_mut(&id) {
+            entry.outcome = Some(result);
+            entry.pins = Some(pins);
+        }
+        self.running.fetch_sub(1, Ordering::Relaxed);
+        done.store(true, Ordering::Relaxed);
+        self.finished.notify_all();
+    }
+    
+    // 辅助函数：任务数达到阈值时，移除已经结束但任务值不再被引用的任务，它们的结果不会再被等待
+    fn prune(&self, tasks: &mut HashMap<u64, TaskEntry>, closures: &ClosureTable) {
+        if tasks.len() < self.threshold.load(Ordering::Relaxed) {
+            return;
+        }
+        tasks.retain(|id, entry| entry.outcome.is_none() || closures.contains_task(*id));
+        self.threshold.store((tasks.len() * 2).max(MIN_PRUNE_THRESHOLD), Ordering::Relaxed);
+    }
+    
+    /// 任务表中的任务数
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.tasks.lock().unwrap().len()
+    }
+    
+    /// 等待一组任务结束，按任务的顺序返回它们的结果，结果取走后任务从任务表中移除
+    ///
+    /// 快速失败时，一旦有任务出错就取消其余的任务并返回这个错误，不再等待它们结束。
+    /// 结果中的闭包固定到pins中。
+    pub(crate) fn wait(&self, ids: &[u64], errors: ErrorPolicy, pins: &mut Pins) -> Result<Vec<Result<Value, NjilError>>, NjilError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut seen = HashSet::new();
+        for id in ids {
+            if !seen.insert(*id) || !tasks.contains_key(id) {
+                return Err(NjilError::ExecutionError(tip::unknown_task(*id)));
+            }
+        }
+        
+        loop {
+            if errors == ErrorPolicy::FailFast {
+                let failed = ids.iter().find(|id| matches!(tasks[id].outcome, Some(Err(_))));
+                if let Some(failed) = failed {
+                    let entry = tasks.remove(failed).unwrap();
+                    pins.adopt(entry.pins.unwrap());
+                    let error = entry.outcome.unwrap().unwrap_err();
+                    for id in ids {
+                        if let Some(entry) = tasks.remove(id) {
+                            entry.cancel.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    return Err(error);
+                }
+            }
+            
+            if ids.iter().all(|id| tasks[id].outcome.is_some()) {
+                return Ok(ids.iter().map(|id| {
+                    let entry = tasks.remove(id).unwrap();
+                    pins.adopt(entry.pins.unwrap());
+                    entry.outcome.unwrap()
+                }).collect());
+            }
+            tasks = self.finished.wait(tasks).unwrap();
+        }
+    }
+    
+    /// 取消所有尚未结束的任务
+    pub(crate) fn cancel_all(&self) {
+        for entry in self.tasks.lock().unwrap().values() {
+            entry.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 把任务的结果转换为收集错误时的结果对象
+pub fn outcome_value(outcome: &Result<Value, NjilError>) -> Value {
+    let mut result = Map::new();
+    match outcome {
+        Ok(value) => {
+            result.insert("ok".to_string(), Value::Bool(true));
+            result.insert("value".to_string(), value.clone());
+        },
+        Err(error) => {
+            result.insert("ok".to_string(), Value::Bool(false));
+            result.insert("error".to_string(), error.to_value());
+        },
+    }
+    Value::Object(result)
+}
+
+/// 创建指向任务表中指定任务的JSON值
+pub fn task_value(id: u64) -> Value {
+    let mut marker = Map::new();
+    marker.insert(TASK_KEY.to_string(), Value::from(id));
+    Value::Object(marker)
+}
+
+/// 如果值是任务，返回其在任务表中的编号
+pub fn task_id(value: &Value) -> Option<u64> {
+    match value {
+        Value::Object(obj) if obj.len() == 1 => obj.get(TASK_KEY)?.as_u64(),
+        _ => None,
+    }
+}
@@ -1,11 +1,16 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use crate::source::{SourceInfo, Spans};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct NjilProgram {
     pub import: Option<Vec<Value>>,
     pub program: Program,
+    /// 源码位置信息，从源码加载时才有
+    #[serde(skip)]
+    pub source: Option<Arc<SourceInfo>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -16,7 +21,90 @@ pub struct Program {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Function {
+    /// 参数声明（可选，未声明时只能通过 $1、$2、$args 访问参数）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Param>,
     pub body: Vec<Value>,
+    /// 函数定义（包括params和body）在源码中的位置，从源码加载时才有
+    #[serde(skip)]
+    pub spans: Option<Arc<Spans>>,
+}
+
+impl Function {
+    /// 函数体中是否有yield语句（不包括嵌套的函数字面量和任务），这样的函数调用后返回生成器
+    pub fn is_generator(&self) -> bool {
+        self.body.iter().any(contains_yield)
+    }
+}
+
+// 辅助函数：递归查找yield语句，跳过嵌套的函数字面量以及在其他线程中执行的task.spawn和parallel语句块
+fn contains_yield(value: &Value) -> bool {
+    match value {
+        Value::Object(obj) if obj.len() == 1 && obj.contains_key("yield") => true,
+        Value::Object(obj) if obj.len() == 1 && ["fn", "lambda", "task.spawn", "parallel"].iter().any(|key| obj.contains_key(*key)) => false,
+        Value::Object(obj) => obj.values().any(contains_yield),
+        Value::Array(items) => items.iter().any(contains_yield),
+        _ => false,
+    }
+}
+
+/// 函数参数声明
+///
+/// 可以写成参数名字符串（`"name"`，`"...rest"` 表示剩余参数），
+/// 也可以写成完整的对象形式：`{"name": "x", "default": 1, "type": "number"}`。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(from = "ParamDecl")]
+pub struct Param {
+    /// 参数名
+    pub name: String,
+
+    /// 默认值，调用时未提供该参数则使用默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+
+    /// 是否为剩余参数，收集其余所有位置参数
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rest: bool,
+
+    /// 参数类型（string、number、integer、boolean、array、object、null、any）
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub param_type: Option<String>,
+}
+
+/// 参数声明的两种书写形式
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ParamDecl {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(default, deserialize_with = "deserialize_some")]
+        default: Option<Value>,
+        #[serde(default)]
+        rest: bool,
+        #[serde(default, rename = "type")]
+        param_type: Option<String>,
+    },
+}
+
+/// 让显式写出的 `"default": null` 也被视为提供了默认值
+fn deserialize_some<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+impl From<ParamDecl> for Param {
+    fn from(decl: ParamDecl) -> Self {
+        match decl {
+            ParamDecl::Name(name) => match name.strip_prefix("...") {
+                Some(rest_name) => Param { name: rest_name.to_string(), default: None, rest: true, param_type: None },
+                None => Param { name, default: None, rest: false, param_type: None },
+            },
+            ParamDecl::Full { name, default, rest, param_type } => Param { name, default, rest, param_type },
+        }
+    }
 }
 
 /// NeoJi模块(NJIM)的结构定义
@@ -47,6 +135,10 @@ pub struct NjimModule {
     /// 该模块导入的其他模块
     #[serde(default)]
     pub imports: Option<Vec<Value>>,
+    
+    /// 源码位置信息
+    #[serde(skip)]
+    pub source: Option<Arc<SourceInfo>>,
 }
 
 /// 模块导出的内容
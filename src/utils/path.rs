@@ -64,7 +64,7 @@ pub fn parse_path(path: &str) -> Result<Vec<PathPart>, NjilError> {
 pub fn get_nested_value<'a>(base_value: &'a Value, path_parts: &[PathPart]) -> Result<&'a Value, NjilError> {
     let mut current_value = base_value;
     
-    for (_i, part) in path_parts.iter().enumerate() {
+    for part in path_parts.iter() {
         match part {
             PathPart::ArrayIndex(idx) => {
                 // 数组索引访问
@@ -228,15 +228,13 @@ pub fn set_json_nested_value(json_obj: &mut Value, path_parts: &[PathPart], valu
                 }
                 
                 // 如果数组元素是null，替换为对象或数组，取决于下一个路径部分
-                if arr[*idx].is_null() {
-                    if i < path_parts.len() - 2 {
-                        match &path_parts[i + 1] {
-                            PathPart::ObjectProperty(_) => {
-                                arr[*idx] = Value::Object(serde_json::Map::new());
-                            },
-                            PathPart::ArrayIndex(_) => {
-                                arr[*idx] = Value::Array(Vec::new());
-                            }
+                if arr[*idx].is_null() && i < path_parts.len() - 2 {
+                    match &path_parts[i + 1] {
+                        PathPart::ObjectProperty(_) => {
+                            arr[*idx] = Value::Object(serde_json::Map::new());
+                        },
+                        PathPart::ArrayIndex(_) => {
+                            arr[*idx] = Value::Array(Vec::new());
                         }
                     }
                 }
@@ -307,6 +305,41 @@ pub fn set_json_nested_value(json_obj: &mut Value, path_parts: &[PathPart], valu
     Ok(())
 }
 
+/// 查找嵌套值，路径不存在时返回None（与值为null区分开）
+pub fn find_nested_value<'a>(base_value: &'a Value, path_parts: &[PathPart]) -> Option<&'a Value> {
+    let mut current_value = base_value;
+    
+    for part in path_parts {
+        current_value = match (part, current_value) {
+            (PathPart::ArrayIndex(idx), Value::Array(arr)) => arr.get(*idx)?,
+            (PathPart::ObjectProperty(prop), Value::Object(obj)) => obj.get(prop)?,
+            _ => return None,
+        };
+    }
+    
+    Some(current_value)
+}
+
+/// 删除嵌套值，返回被删除的值（路径不存在时返回None）
+pub fn remove_nested_value(base_value: &mut Value, path_parts: &[PathPart]) -> Option<Value> {
+    let (last, parents) = path_parts.split_last()?;
+    
+    let mut current_value = base_value;
+    for part in parents {
+        current_value = match (part, current_value) {
+            (PathPart::ArrayIndex(idx), Value::Array(arr)) => arr.get_mut(*idx)?,
+            (PathPart::ObjectProperty(prop), Value::Object(obj)) => obj.get_mut(prop)?,
+            _ => return None,
+        };
+    }
+    
+    match (last, current_value) {
+        (PathPart::ArrayIndex(idx), Value::Array(arr)) if *idx < arr.len() => Some(arr.remove(*idx)),
+        (PathPart::ObjectProperty(prop), Value::Object(obj)) => obj.remove(prop),
+        _ => None,
+    }
+}
+
 /// 获取嵌套值的路径字符串表示
 pub fn path_to_string(path_parts: &[PathPart]) -> String {
     let mut result = String::new();
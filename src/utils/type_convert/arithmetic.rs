@@ -1,9 +1,27 @@
 use serde_json::Value;
+use crate::error::NjilError;
+use crate::errortip::math as tip;
 use super::string_convert::to_string;
 use super::number_convert::to_number;
+use super::integer::{integer_arithmetic, IntegerOp};
+use super::decimal::decimal_arithmetic;
 
 /// 执行两个值之间的加法运算，根据类型自动转换
-pub fn add(left: &Value, right: &Value) -> Value {
+///
+/// 有定点小数参与时结果是精确的定点小数；两个整数的结果仍是整数，超出64位整数范围时报错；
+/// 其他有浮点数参与的情况按浮点数计算。
+pub fn add(left: &Value, right: &Value) -> Result<Value, NjilError> {
+    if let Some(result) = decimal_arithmetic(IntegerOp::Add, left, right)? {
+        return Ok(result);
+    }
+    match integer_arithmetic(IntegerOp::Add, left, right)? {
+        Some(result) => Ok(result),
+        None => Ok(add_values(left, right)),
+    }
+}
+
+// 辅助函数：非整数的加法运算
+fn add_values(left: &Value, right: &Value) -> Value {
     match (left, right) {
         // 数字 + 数字 = 数字
         (Value::Number(l), Value::Number(r)) => {
@@ -77,7 +95,21 @@ pub fn add(left: &Value, right: &Value) -> Value {
 }
 
 /// 执行两个值之间的减法运算，根据类型自动转换
-pub fn subtract(left: &Value, right: &Value) -> Value {
+///
+/// 有定点小数参与时结果是精确的定点小数；两个整数的结果仍是整数，超出64位整数范围时报错；
+/// 其他有浮点数参与的情况按浮点数计算。
+pub fn subtract(left: &Value, right: &Value) -> Result<Value, NjilError> {
+    if let Some(result) = decimal_arithmetic(IntegerOp::Sub, left, right)? {
+        return Ok(result);
+    }
+    match integer_arithmetic(IntegerOp::Sub, left, right)? {
+        Some(result) => Ok(result),
+        None => Ok(subtract_values(left, right)),
+    }
+}
+
+// 辅助函数：非整数的减法运算
+fn subtract_values(left: &Value, right: &Value) -> Value {
     match (left, right) {
         // 数字 - 数字 = 数字
         (Value::Number(l), Value::Number(r)) => {
@@ -125,7 +157,21 @@ pub fn subtract(left: &Value, right: &Value) -> Value {
 }
 
 /// 执行两个值之间的乘法运算，根据类型自动转换
-pub fn multiply(left: &Value, right: &Value) -> Value {
+///
+/// 有定点小数参与时结果是精确的定点小数；两个整数的结果仍是整数，超出64位整数范围时报错；
+/// 其他有浮点数参与的情况按浮点数计算。
+pub fn multiply(left: &Value, right: &Value) -> Result<Value, NjilError> {
+    if let Some(result) = decimal_arithmetic(IntegerOp::Mul, left, right)? {
+        return Ok(result);
+    }
+    match integer_arithmetic(IntegerOp::Mul, left, right)? {
+        Some(result) => Ok(result),
+        None => Ok(multiply_values(left, right)),
+    }
+}
+
+// 辅助函数：非整数的乘法运算
+fn multiply_values(left: &Value, right: &Value) -> Value {
     match (left, right) {
         // 数字 * 数字 = 数字
         (Value::Number(l), Value::Number(r)) => {
@@ -204,7 +250,20 @@ pub fn multiply(left: &Value, right: &Value) -> Value {
 }
 
 /// 执行两个值之间的除法运算，根据类型自动转换
-pub fn divide(left: &Value, right: &Value) -> Value {
+///
+/// 有定点小数参与时结果是定点小数；两个整数能整除时结果是整数，否则按浮点数计算。
+pub fn divide(left: &Value, right: &Value) -> Result<Value, NjilError> {
+    if let Some(result) = decimal_arithmetic(IntegerOp::Div, left, right)? {
+        return Ok(result);
+    }
+    match integer_arithmetic(IntegerOp::Div, left, right)? {
+        Some(result) => Ok(result),
+        None => Ok(divide_values(left, right)),
+    }
+}
+
+// 辅助函数：非整数的除法运算
+fn divide_values(left: &Value, right: &Value) -> Value {
     match (left, right) {
         // 数字 / 数字 = 数字
         (Value::Number(l), Value::Number(r)) => {
@@ -270,4 +329,46 @@ pub fn divide(left: &Value, right: &Value) -> Value {
             Value::Null
         }
     }
-} 
\ No newline at end of file
+}
+
+/// 执行两个值之间的取模运算，操作数会转换为数字，除数为零或无法转换时返回null
+///
+/// 两个整数取模的结果是整数，符号与被除数相同。
+pub fn modulo(left: &Value, right: &Value) -> Result<Value, NjilError> {
+    if let Some(result) = decimal_arithmetic(IntegerOp::Rem, left, right)? {
+        return Ok(result);
+    }
+    if let Some(result) = integer_arithmetic(IntegerOp::Rem, left, right)? {
+        return Ok(result);
+    }
+    Ok(match (to_number(left), to_number(right)) {
+        (Some(dividend), Some(divisor)) if divisor != 0.0 => {
+            serde_json::Number::from_f64(dividend % divisor).map(Value::Number).unwrap_or(Value::Null)
+        },
+        _ => Value::Null,
+    })
+}
+
+/// 整数除法，商向零取整
+///
+/// 两个整数的结果是整数；有浮点数参与时结果是取整后的浮点数。除数为零或无法转换为数字时返回null。
+pub fn integer_divide(left: &Value, right: &Value) -> Result<Value, NjilError> {
+    if let Some(result) = decimal_arithmetic(IntegerOp::IntDiv, left, right)? {
+        return Ok(result);
+    }
+    if let Some(result) = integer_arithmetic(IntegerOp::IntDiv, left, right)? {
+        return Ok(result);
+    }
+    Ok(match (to_number(left), to_number(right)) {
+        (Some(dividend), Some(divisor)) if divisor != 0.0 => {
+            serde_json::Number::from_f64((dividend / divisor).trunc()).map(Value::Number).unwrap_or(Value::Null)
+        },
+        _ => Value::Null,
+    })
+}
+
+/// 位运算（按位与、或、异或和位移），操作数必须是整数
+pub fn bitwise(op: IntegerOp, left: &Value, right: &Value) -> Result<Value, NjilError> {
+    integer_arithmetic(op, left, right)?
+        .ok_or_else(|| NjilError::TypeError(tip::integer_required(op.name())))
+}
@@ -1,7 +1,13 @@
 use serde_json::Value;
+use super::range::as_range;
 
 /// 将值转换为数组类型
+///
+/// 范围会生成所有元素，调用者需要先用 `Range::to_vec` 检查范围的长度。
 pub fn to_array(value: &Value) -> Vec<Value> {
+    if let Some(range) = as_range(value) {
+        return range.iter().collect();
+    }
     match value {
         Value::Array(a) => a.clone(),
         Value::String(s) => {
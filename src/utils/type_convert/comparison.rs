@@ -2,9 +2,19 @@ use serde_json::Value;
 use super::bool_convert::to_bool;
 use super::number_convert::to_number;
 use super::string_convert::to_string;
+use super::integer::{compare_integers, is_bigint};
+use super::decimal::compare_decimals;
 
 /// 比较两个值是否相等
 pub fn is_equal(left: &Value, right: &Value) -> bool {
+    // 整数和定点小数精确比较，大整数和浮点数按数值比较
+    if let Some(ordering) = compare_integers(left, right).or_else(|| compare_decimals(left, right)) {
+        return ordering == std::cmp::Ordering::Equal;
+    }
+    if is_bigint(left) || is_bigint(right) {
+        return matches!((to_number_if_numeric(left), to_number_if_numeric(right)), (Some(l), Some(r)) if l == r);
+    }
+    
     match (left, right) {
         // 相同类型直接比较
         (Value::Null, Value::Null) => true,
@@ -61,6 +71,13 @@ pub fn is_equal(left: &Value, right: &Value) -> bool {
 
 /// 比较两个值的大小关系
 pub fn compare(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    if let Some(ordering) = compare_integers(left, right).or_else(|| compare_decimals(left, right)) {
+        return Some(ordering);
+    }
+    if is_bigint(left) || is_bigint(right) {
+        return to_number_if_numeric(left)?.partial_cmp(&to_number_if_numeric(right)?);
+    }
+    
     match (left, right) {
         // 数字比较
         (Value::Number(l), Value::Number(r)) => {
@@ -111,4 +128,13 @@ pub fn compare(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
     
     // 无法比较时返回None
     None
-} 
\ No newline at end of file
+}
+
+// 辅助函数：数字和大整数转换为浮点数，其他值返回None
+fn to_number_if_numeric(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        _ if is_bigint(value) => to_number(value),
+        _ => None,
+    }
+}
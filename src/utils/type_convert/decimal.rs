@@ -0,0 +1,348 @@
+use std::cmp::Ordering;
+use std::fmt;
+use num_bigint::BigInt;
+use num_traits::{One, Signed, Zero};
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::errortip::math as tip;
+use super::integer::{self, IntegerOp};
+
+/// 定点小数在值中的表示：`{"$decimal": "十进制数字"}`
+pub const DECIMAL_KEY: &str = "$decimal";
+
+/// 小数位数的上限
+pub const MAX_SCALE: u32 = 1000;
+
+/// 没有指定小数位数时，除法结果保留的小数位数
+pub const DEFAULT_DIVISION_SCALE: u32 = 16;
+
+/// 舍入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// 四舍五入，0.5远离零舍入
+    #[default]
+    HalfUp,
+    /// 银行家舍入，0.5舍入到偶数
+    HalfEven,
+    /// 向负无穷舍入
+    Floor,
+}
+
+impl Rounding {
+    /// 解析舍入方式的名称：half_up、half_even、floor（也可以用连字符）
+    pub fn parse(name: &str) -> Option<Rounding> {
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "half_up" => Some(Rounding::HalfUp),
+            "half_even" => Some(Rounding::HalfEven),
+            "floor" => Some(Rounding::Floor),
+            _ => None,
+        }
+    }
+}
+
+/// 定点小数，值为 mantissa / 10^scale
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    mantissa: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: BigInt, scale: u32) -> Self {
+        Decimal { mantissa, scale }
+    }
+    
+    /// 小数位数
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+    
+    /// 解析十进制字符串，支持符号、小数点和指数，例如 `-12.50`、`1.5e-3`
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let text = text.trim();
+        let (number, exponent) = match text.find(['e', 'E']) {
+            Some(index) => (&text[..index], text[index + 1..].parse::<i64>().ok()?),
+            None => (text, 0),
+        };
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        
+        if exponent.unsigned_abs() > MAX_SCALE as u64 {
+            return None;
+        }
+        
+        let mantissa: BigInt = format!("{}{}{}", sign, whole, fraction).parse().ok()?;
+        let scale = fraction.len() as i64 - exponent;
+        if scale < 0 {
+            Some(Decimal::new(mantissa * pow10(scale.unsigned_abs() as u32), 0))
+        } else {
+            Some(Decimal::new(mantissa, scale as u32))
+        }
+    }
+    
+    /// 浮点数按最短的十进制表示转换，例如0.1转换为0.1而不是0.1000000000000000055…
+    pub fn from_f64(number: f64) -> Option<Decimal> {
+        if number.is_finite() {
+            Decimal::parse(&number.to_string())
+        } else {
+            None
+        }
+    }
+    
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+    
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+    
+    /// 舍入到指定的小数位数，位数更多时在末尾补零
+    pub fn round(&self, scale: u32, rounding: Rounding) -> Decimal {
+        if scale >= self.scale {
+            return Decimal::new(&self.mantissa * pow10(scale - self.scale), scale);
+        }
+        let mantissa = divide_rounded(&self.mantissa, &pow10(self.scale - scale), rounding);
+        Decimal::new(mantissa, scale)
+    }
+    
+    /// 去掉小数部分末尾的零
+    pub fn normalize(&self) -> Decimal {
+        let ten = BigInt::from(10);
+        let mut result = self.clone();
+        while result.scale > 0 && (&result.mantissa % &ten).is_zero() {
+            result.mantissa /= &ten;
+            result.scale -= 1;
+        }
+        result
+    }
+    
+    pub fn add(&self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.rescaled(scale) + other.rescaled(scale), scale)
+    }
+    
+    pub fn sub(&self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.rescaled(scale) - other.rescaled(scale), scale)
+    }
+    
+    pub fn mul(&self, other: &Decimal) -> Decimal {
+        Decimal::new(&self.mantissa * &other.mantissa, self.scale + other.scale)
+    }
+    
+    /// 除法，结果舍入到指定的小数位数，除数为零时返回None
+    pub fn div(&self, other: &Decimal, scale: u32, rounding: Rounding) -> Option<Decimal> {
+        if other.is_zero() {
+            return None;
+        }
+        // (a / 10^sa) / (b / 10^sb) = a * 10^(sb + scale) / (b * 10^sa) / 10^scale
+        let numerator = &self.mantissa * pow10(other.scale + scale);
+        let denominator = &other.mantissa * pow10(self.scale);
+        Some(Decimal::new(divide_rounded(&numerator, &denominator, rounding), scale))
+    }
+    
+    pub fn neg(&self) -> Decimal {
+        Decimal::new(-&self.mantissa, self.scale)
+    }
+    
+    pub fn abs(&self) -> Decimal {
+        Decimal::new(self.mantissa.abs(), self.scale)
+    }
+    
+    /// 非负整数次幂，结果是精确的
+    pub fn pow(&self, exponent: u32) -> Decimal {
+        Decimal::new(num_traits::pow(self.mantissa.clone(), exponent as usize), self.scale * exponent)
+    }
+    
+    /// 整数部分（向零取整）
+    pub fn trunc(&self) -> BigInt {
+        &self.mantissa / pow10(self.scale)
+    }
+    
+    // 辅助函数：转换为指定小数位数下的尾数，scale不能小于当前的小数位数
+    fn rescaled(&self, scale: u32) -> BigInt {
+        &self.mantissa * pow10(scale - self.scale)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.abs().to_string();
+        let sign = if self.mantissa.is_negative() { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.rescaled(scale).cmp(&other.rescaled(scale))
+    }
+}
+
+/// 把定点小数包装为值
+pub fn decimal_value(number: &Decimal) -> Value {
+    let mut object = Map::new();
+    object.insert(DECIMAL_KEY.to_string(), Value::String(number.to_string()));
+    Value::Object(object)
+}
+
+/// 判断值是否是定点小数
+pub fn is_decimal(value: &Value) -> bool {
+    as_decimal(value).is_some()
+}
+
+/// 取出定点小数值中的数字
+pub fn as_decimal(value: &Value) -> Option<Decimal> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    match (object.len(), object.get(DECIMAL_KEY)) {
+        (1, Some(Value::String(digits))) => Decimal::parse(digits),
+        _ => None,
+    }
+}
+
+/// 转换为定点小数：数字、大整数、定点小数和十进制数字字符串
+pub fn to_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::Number(number) => match integer::as_integer(value) {
+            Some(integer) => Some(Decimal::new(integer, 0)),
+            None => Decimal::from_f64(number.as_f64()?),
+        },
+        Value::String(text) => Decimal::parse(text),
+        other => as_decimal(other).or_else(|| integer::as_bigint(other).map(|n| Decimal::new(n, 0))),
+    }
+}
+
+/// 比较定点小数和其他数字，双方都不是定点小数或有一方不是数字时返回None
+pub fn compare_decimals(left: &Value, right: &Value) -> Option<Ordering> {
+    if !is_decimal(left) && !is_decimal(right) {
+        return None;
+    }
+    Some(numeric_decimal(left)?.cmp(&numeric_decimal(right)?))
+}
+
+/// 有定点小数参与的算术运算，双方都不是定点小数或有一方不是数字时返回 `Ok(None)`
+///
+/// 加减乘的结果是精确的；除法保留DEFAULT_DIVISION_SCALE位小数后去掉末尾的零。
+/// 除数为零时结果为null，位运算不支持定点小数。
+pub fn decimal_arithmetic(op: IntegerOp, left: &Value, right: &Value) -> Result<Option<Value>, NjilError> {
+    if !is_decimal(left) && !is_decimal(right) {
+        return Ok(None);
+    }
+    let (Some(l), Some(r)) = (numeric_decimal(left), numeric_decimal(right)) else {
+        return Ok(None);
+    };
+    let result = match op {
+        IntegerOp::Add => l.add(&r),
+        IntegerOp::Sub => l.sub(&r),
+        IntegerOp::Mul => l.mul(&r),
+        IntegerOp::Div | IntegerOp::IntDiv | IntegerOp::Rem if r.is_zero() => return Ok(Some(Value::Null)),
+        IntegerOp::Div => l.div(&r, DEFAULT_DIVISION_SCALE, Rounding::HalfUp).unwrap().normalize(),
+        // 整数除法和取模向零取整，与整数的规则相同
+        IntegerOp::IntDiv => truncated_quotient(&l, &r),
+        IntegerOp::Rem => l.sub(&truncated_quotient(&l, &r).mul(&r)),
+        _ => return Err(NjilError::TypeError(tip::integer_required(op.name()))),
+    };
+    Ok(Some(decimal_value(&result)))
+}
+
+/// 检查小数位数参数
+pub fn check_scale(scale: &Value) -> Result<u32, NjilError> {
+    match scale.as_u64() {
+        Some(scale) if scale <= MAX_SCALE as u64 => Ok(scale as u32),
+        _ => Err(NjilError::ExecutionError(tip::decimal_scale_invalid(MAX_SCALE))),
+    }
+}
+
+// 辅助函数：数字类的值转换为定点小数，字符串等其他类型返回None
+fn numeric_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::String(_) => None,
+        other => to_decimal(other),
+    }
+}
+
+// 辅助函数：向零取整的商
+fn truncated_quotient(l: &Decimal, r: &Decimal) -> Decimal {
+    let numerator = &l.mantissa * pow10(r.scale);
+    let denominator = &r.mantissa * pow10(l.scale);
+    Decimal::new(numerator / denominator, 0)
+}
+
+// 辅助函数：整数除法并按舍入方式处理余数
+fn divide_rounded(numerator: &BigInt, denominator: &BigInt, rounding: Rounding) -> BigInt {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder.is_zero() {
+        return quotient;
+    }
+    
+    // 商的符号，截断后的商为零时由被除数和除数的符号决定
+    let negative = numerator.is_negative() != denominator.is_negative();
+    let away_from_zero = if negative { &quotient - BigInt::one() } else { &quotient + BigInt::one() };
+    match rounding {
+        Rounding::Floor => if negative { away_from_zero } else { quotient },
+        Rounding::HalfUp | Rounding::HalfEven => {
+            match (remainder.abs() * 2u32).cmp(&denominator.abs()) {
+                Ordering::Greater => away_from_zero,
+                Ordering::Less => quotient,
+                Ordering::Equal if rounding == Rounding::HalfUp => away_from_zero,
+                Ordering::Equal => if (&quotient % 2u32).is_zero() { quotient } else { away_from_zero },
+            }
+        },
+    }
+}
+
+// 辅助函数：10的n次方
+fn pow10(exponent: u32) -> BigInt {
+    num_traits::pow(BigInt::from(10), exponent as usize)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_decimal_parse_and_display() {
+        let parse = |text: &str| Decimal::parse(text).map(|n| n.to_string());
+        assert_eq!(parse("-0.05").as_deref(), Some("-0.05"));
+        assert_eq!(parse("12.500").as_deref(), Some("12.500"));
+        assert_eq!(parse("1.5e-3").as_deref(), Some("0.0015"));
+        assert_eq!(parse("2.5E2").as_deref(), Some("250"));
+        assert_eq!(parse("+.5").as_deref(), Some("0.5"));
+        assert_eq!(parse("1.2.3"), None);
+        assert_eq!(parse("abc"), None);
+        assert_eq!(Decimal::from_f64(0.1).unwrap().to_string(), "0.1");
+        assert_eq!(Decimal::parse("12.500").unwrap().normalize().to_string(), "12.5");
+    }
+}
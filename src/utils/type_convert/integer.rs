@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+use serde_json::{Map, Number, Value};
+use crate::error::NjilError;
+use crate::errortip::math as tip;
+
+/// 大整数在值中的表示：`{"$bigint": "十进制数字"}`
+pub const BIGINT_KEY: &str = "$bigint";
+
+/// 位移运算允许的最大位数
+const MAX_SHIFT: u64 = 65536;
+
+/// 整数运算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerOp {
+    Add,
+    Sub,
+    Mul,
+    /// 除法，只有整除时结果才是整数
+    Div,
+    /// 整数除法，向零取整
+    IntDiv,
+    /// 取余，符号与被除数相同
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl IntegerOp {
+    /// 运算在错误信息中的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            IntegerOp::Add => "加法",
+            IntegerOp::Sub => "减法",
+            IntegerOp::Mul => "乘法",
+            IntegerOp::Div => "除法",
+            IntegerOp::IntDiv => "整数除法",
+            IntegerOp::Rem => "取模",
+            IntegerOp::BitAnd => "按位与",
+            IntegerOp::BitOr => "按位或",
+            IntegerOp::BitXor => "按位异或",
+            IntegerOp::Shl => "左移",
+            IntegerOp::Shr => "右移",
+        }
+    }
+}
+
+/// 把大整数包装为值
+pub fn bigint_value(number: &BigInt) -> Value {
+    let mut object = Map::new();
+    object.insert(BIGINT_KEY.to_string(), Value::String(number.to_string()));
+    Value::Object(object)
+}
+
+/// 判断值是否是大整数
+pub fn is_bigint(value: &Value) -> bool {
+    as_bigint(value).is_some()
+}
+
+/// 取出大整数值中的数字，普通数字不算大整数
+pub fn as_bigint(value: &Value) -> Option<BigInt> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    match (object.len(), object.get(BIGINT_KEY)) {
+        (1, Some(Value::String(digits))) => digits.parse().ok(),
+        _ => None,
+    }
+}
+
+/// 取出整数值，包括普通整数和大整数
+pub fn as_integer(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Number(number) => small_integer(number).map(BigInt::from),
+        other => as_bigint(other),
+    }
+}
+
+/// 转换为大整数：整数、没有小数部分的浮点数、十进制数字字符串和大整数
+pub fn to_bigint(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Number(number) => match small_integer(number) {
+            Some(integer) => Some(BigInt::from(integer)),
+            None => number.as_f64()
+                .filter(|float| float.is_finite() && float.fract() == 0.0)
+                .and_then(|float| format!("{:.0}", float).parse().ok()),
+        },
+        Value::String(text) => text.trim().parse().ok(),
+        other => as_bigint(other),
+    }
+}
+
+/// 大整数转换为浮点数，超出范围时为无穷大
+pub fn bigint_to_f64(number: &BigInt) -> f64 {
+    number.to_f64().unwrap_or(if number.is_negative() { f64::NEG_INFINITY } else { f64::INFINITY })
+}
+
+/// 大整数在64位整数范围内时转换为普通数字
+pub fn to_small_value(number: &BigInt) -> Option<Value> {
+    number.to_i128().and_then(small_value)
+}
+
+/// 精确比较两个整数，任一方不是整数时返回None
+pub fn compare_integers(left: &Value, right: &Value) -> Option<Ordering> {
+    if let (Value::Number(l), Value::Number(r)) = (left, right) {
+        return Some(small_integer(l)?.cmp(&small_integer(r)?));
+    }
+    Some(as_integer(left)?.cmp(&as_integer(right)?))
+}
+
+/// 对两个整数执行运算
+///
+/// 任一方不是整数时返回 `Ok(None)`，由调用者按浮点数处理；除法不能整除时也返回 `Ok(None)`。
+/// 有大整数参与时结果是大整数，否则结果超出64位整数范围时报错。除数为零时结果为null。
+pub fn integer_arithmetic(op: IntegerOp, left: &Value, right: &Value) -> Result<Option<Value>, NjilError> {
+    // 两个普通整数的常见情况不分配大整数
+    if let (Value::Number(l), Value::Number(r)) = (left, right) {
+        let (Some(l), Some(r)) = (small_integer(l), small_integer(r)) else {
+            return Ok(None);
+        };
+        let result = match op {
+            IntegerOp::Add => l.checked_add(r),
+            IntegerOp::Sub => l.checked_sub(r),
+            IntegerOp::Mul => l.checked_mul(r),
+            IntegerOp::Div | IntegerOp::IntDiv | IntegerOp::Rem if r == 0 => return Ok(Some(Value::Null)),
+            IntegerOp::Div if l % r != 0 => return Ok(None),
+            IntegerOp::Div | IntegerOp::IntDiv => Some(l / r),
+            IntegerOp::Rem => Some(l % r),
+            IntegerOp::BitAnd => Some(l & r),
+            IntegerOp::BitOr => Some(l | r),
+            IntegerOp::BitXor => Some(l ^ r),
+            IntegerOp::Shl | IntegerOp::Shr => None,
+        };
+        return match result {
+            Some(result) => small_value(result).map(Some).ok_or_else(|| overflow(op)),
+            // 位移和i128溢出按大整数计算，再检查范围
+            None => {
+                let result = big_arithmetic(op, &BigInt::from(l), &BigInt::from(r))?;
+                match result.as_ref().map(as_bigint) {
+                    Some(Some(big)) => to_small_value(&big).map(Some).ok_or_else(|| overflow(op)),
+                    _ => Ok(result),
+                }
+            },
+        };
+    }
+    
+    match (as_integer(left), as_integer(right)) {
+        (Some(l), Some(r)) => big_arithmetic(op, &l, &r),
+        _ => Ok(None),
+    }
+}
+
+// 辅助函数：大整数运算，结果包装为大整数值
+fn big_arithmetic(op: IntegerOp, l: &BigInt, r: &BigInt) -> Result<Option<Value>, NjilError> {
+    let result = match op {
+        IntegerOp::Add => l + r,
+        IntegerOp::Sub => l - r,
+        IntegerOp::Mul => l * r,
+        IntegerOp::Div | IntegerOp::IntDiv | IntegerOp::Rem if r.is_zero() => return Ok(Some(Value::Null)),
+        IntegerOp::Div if !(l % r).is_zero() => return Ok(None),
+        IntegerOp::Div | IntegerOp::IntDiv => l / r,
+        IntegerOp::Rem => l % r,
+        IntegerOp::BitAnd => l & r,
+        IntegerOp::BitOr => l | r,
+        IntegerOp::BitXor => l ^ r,
+        IntegerOp::Shl => l << shift_amount(r)?,
+        IntegerOp::Shr => l >> shift_amount(r)?,
+    };
+    Ok(Some(bigint_value(&result)))
+}
+
+// 辅助函数：普通整数，u64也统一为i128
+fn small_integer(number: &Number) -> Option<i128> {
+    number.as_i64().map(i128::from).or_else(|| number.as_u64().map(i128::from))
+}
+
+// 辅助函数：把结果转换为数字值，超出i64和u64范围时返回None
+fn small_value(number: i128) -> Option<Value> {
+    if let Ok(number) = i64::try_from(number) {
+        Some(Value::from(number))
+    } else {
+        u64::try_from(number).ok().map(Value::from)
+    }
+}
+
+// 辅助函数：检查位移的位数
+fn shift_amount(bits: &BigInt) -> Result<usize, NjilError> {
+    match bits.to_u64() {
+        Some(bits) if bits <= MAX_SHIFT => Ok(bits as usize),
+        _ => Err(NjilError::ExecutionError(tip::shift_out_of_range(MAX_SHIFT))),
+    }
+}
+
+// 辅助函数：整数溢出错误
+fn overflow(op: IntegerOp) -> NjilError {
+    NjilError::ExecutionError(tip::integer_overflow(op.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::utils::type_convert::{add, subtract, multiply, divide, modulo, integer_divide, bitwise, is_equal, compare};
+    
+    fn big(digits: &str) -> Value {
+        json!({"$bigint": digits})
+    }
+    
+    #[test]
+    fn test_integer_mixing_rules() {
+        // 整数和整数的结果是整数
+        assert_eq!(add(&json!(2), &json!(3)).unwrap(), json!(5));
+        assert_eq!(multiply(&json!(-4), &json!(3)).unwrap(), json!(-12));
+        // 有浮点数参与时结果是浮点数
+        assert_eq!(add(&json!(2), &json!(0.5)).unwrap(), json!(2.5));
+        assert_eq!(add(&json!(2), &json!(1.0)).unwrap(), json!(3.0));
+        // 整除时结果是整数，否则是浮点数，除数为零时为null
+        assert_eq!(divide(&json!(6), &json!(3)).unwrap(), json!(2));
+        assert_eq!(divide(&json!(7), &json!(2)).unwrap(), json!(3.5));
+        assert_eq!(divide(&json!(7), &json!(0)).unwrap(), Value::Null);
+        // 整数除法和取模向零取整，余数的符号与被除数相同
+        assert_eq!(integer_divide(&json!(-7), &json!(2)).unwrap(), json!(-3));
+        assert_eq!(modulo(&json!(-7), &json!(2)).unwrap(), json!(-1));
+        assert_eq!(integer_divide(&json!(7.5), &json!(2)).unwrap(), json!(3.0));
+        assert_eq!(integer_divide(&json!(7), &json!(0)).unwrap(), Value::Null);
+        // u64范围内的结果仍是精确的整数
+        assert_eq!(add(&json!(i64::MAX), &json!(1)).unwrap(), json!(i64::MAX as u64 + 1));
+    }
+    
+    #[test]
+    fn test_integer_overflow() {
+        assert!(add(&json!(u64::MAX), &json!(1)).is_err());
+        assert!(multiply(&json!(i64::MIN), &json!(4)).is_err());
+        assert!(bitwise(IntegerOp::Shl, &json!(1), &json!(64)).is_err());
+        let error = subtract(&json!(0), &json!(u64::MAX)).unwrap_err();
+        assert!(error.to_string().contains("整数减法溢出"), "{}", error);
+    }
+    
+    #[test]
+    fn test_bigint_arithmetic() {
+        let max = json!(u64::MAX);
+        let result = add(&bigint_value(&to_bigint(&max).unwrap()), &json!(1)).unwrap();
+        assert_eq!(result, big("18446744073709551616"));
+        assert_eq!(multiply(&result, &result).unwrap(), big("340282366920938463463374607431768211456"));
+        // 有大整数参与时结果保持大整数，和浮点数混合时是浮点数
+        assert_eq!(subtract(&big("10"), &json!(3)).unwrap(), big("7"));
+        assert_eq!(add(&big("1"), &json!(0.5)).unwrap(), json!(1.5));
+        assert_eq!(divide(&big("7"), &json!(2)).unwrap(), json!(3.5));
+        assert_eq!(add(&big("12"), &json!("个")).unwrap(), json!("12个"));
+        // 比较按数值进行
+        assert!(is_equal(&big("3"), &json!(3)));
+        assert!(is_equal(&big("3"), &json!(3.0)));
+        assert_eq!(compare(&big("18446744073709551616"), &json!(u64::MAX)), Some(Ordering::Greater));
+        assert_eq!(compare(&json!(9007199254740993_i64), &json!(9007199254740992_i64)), Some(Ordering::Greater));
+    }
+    
+    #[test]
+    fn test_bitwise_operations() {
+        assert_eq!(bitwise(IntegerOp::BitAnd, &json!(12), &json!(10)).unwrap(), json!(8));
+        assert_eq!(bitwise(IntegerOp::BitOr, &json!(12), &json!(10)).unwrap(), json!(14));
+        assert_eq!(bitwise(IntegerOp::BitXor, &json!(12), &json!(10)).unwrap(), json!(6));
+        assert_eq!(bitwise(IntegerOp::Shl, &json!(1), &json!(10)).unwrap(), json!(1024));
+        assert_eq!(bitwise(IntegerOp::Shr, &json!(-16), &json!(2)).unwrap(), json!(-4));
+        assert_eq!(bitwise(IntegerOp::Shl, &big("1"), &json!(70)).unwrap(), big("1180591620717411303424"));
+        // 位运算只接受整数
+        let error = bitwise(IntegerOp::BitAnd, &json!(1.5), &json!(1)).unwrap_err();
+        assert_eq!(error.error_type(), "type_error");
+        assert!(bitwise(IntegerOp::Shr, &json!(1), &json!(-1)).is_err());
+    }
+}
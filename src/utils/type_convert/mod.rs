@@ -8,6 +8,9 @@ pub mod array_convert;
 pub mod object_convert;
 pub mod arithmetic;
 pub mod comparison;
+pub mod integer;
+pub mod decimal;
+pub mod range;
 
 // 重新导出所有功能，方便其他模块使用
 pub use self::bool_convert::to_bool;
@@ -15,20 +18,87 @@ pub use self::number_convert::to_number;
 pub use self::string_convert::to_string;
 pub use self::array_convert::to_array;
 pub use self::object_convert::to_object;
-pub use self::arithmetic::{add, subtract, multiply, divide};
+pub use self::arithmetic::{add, subtract, multiply, divide, modulo, integer_divide, bitwise};
 pub use self::comparison::{is_equal, compare};
+pub use self::integer::{IntegerOp, is_bigint, to_bigint, bigint_value};
+pub use self::decimal::{Decimal, Rounding, is_decimal, to_decimal, decimal_value};
+pub use self::range::{Range, is_range, as_range, range_value};
+use crate::generator::generator_id;
+use crate::task::task_id;
+
+/// 判断值是否是用对象表示的数字（大整数或定点小数）
+pub fn is_tagged_number(value: &serde_json::Value) -> bool {
+    is_bigint(value) || is_decimal(value)
+}
+
+/// 获取值的类型名称
+pub fn type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) if is_bigint(value) => "bigint",
+        serde_json::Value::Object(_) if is_decimal(value) => "decimal",
+        serde_json::Value::Object(_) if is_range(value) => "range",
+        serde_json::Value::Object(_) if generator_id(value).is_some() => "generator",
+        serde_json::Value::Object(_) if task_id(value).is_some() => "task",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/// 检查值是否属于指定的类型，类型名未知时返回None
+///
+/// 支持的类型：string、number、integer、bigint、decimal、boolean（bool）、array、range、generator、task、object、null、any。
+/// 大整数同时属于number和integer，定点小数属于number。
+pub fn has_type(value: &serde_json::Value, expected: &str) -> Option<bool> {
+    let matches = match expected {
+        "any" => true,
+        "string" => value.is_string(),
+        "number" => value.is_number() || is_tagged_number(value),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0) || is_bigint(value),
+        "bigint" => is_bigint(value),
+        "decimal" => is_decimal(value),
+        "boolean" | "bool" => value.is_boolean(),
+        "array" => value.is_array(),
+        "range" => is_range(value),
+        "generator" => generator_id(value).is_some(),
+        "task" => task_id(value).is_some(),
+        "object" => value.is_object() && !is_tagged_number(value) && !is_range(value) && generator_id(value).is_none() && task_id(value).is_none(),
+        "null" => value.is_null(),
+        _ => return None,
+    };
+    Some(matches)
+}
 
 /// 将值转换为指定类型
 pub fn convert_to_type(value: &serde_json::Value, target_type: &str) -> serde_json::Value {
     match target_type.to_lowercase().as_str() {
         "boolean" | "bool" => serde_json::Value::Bool(to_bool(value)),
-        "number" | "int" | "float" => {
+        // 数字保持不变，整数字符串转换为整数，其他值转换为浮点数
+        "number" => match value {
+            serde_json::Value::Number(_) => value.clone(),
+            serde_json::Value::String(s) if s.trim().parse::<i64>().is_ok() => serde_json::Value::from(s.trim().parse::<i64>().unwrap()),
+            _ => convert_to_type(value, "float"),
+        },
+        "float" => {
             if let Some(n) = to_number(value) {
                 serde_json::Value::Number(serde_json::Number::from_f64(n).unwrap_or_else(|| serde_json::Number::from(0)))
             } else {
                 serde_json::Value::Null
             }
         },
+        // 整数向零取整，超出64位整数范围时为null
+        "int" | "integer" => match to_bigint(value) {
+            Some(n) => integer::to_small_value(&n).unwrap_or(serde_json::Value::Null),
+            None => match to_number(value) {
+                Some(n) if n.is_finite() && n.trunc() >= i64::MIN as f64 && n.trunc() < i64::MAX as f64 => serde_json::Value::from(n.trunc() as i64),
+                _ => serde_json::Value::Null,
+            },
+        },
+        "bigint" => to_bigint(value).map(|n| bigint_value(&n)).unwrap_or(serde_json::Value::Null),
+        "decimal" => to_decimal(value).map(|n| decimal_value(&n)).unwrap_or(serde_json::Value::Null),
         "string" | "str" => serde_json::Value::String(to_string(value)),
         "array" | "list" => serde_json::Value::Array(to_array(value)),
         "object" | "map" => serde_json::Value::Object(to_object(value)),
@@ -6,11 +6,7 @@ pub fn to_number(value: &Value) -> Option<f64> {
         Value::Number(n) => n.as_f64(),
         Value::String(s) => {
             // 尝试将字符串解析为数字
-            if let Ok(n) = s.parse::<f64>() {
-                Some(n)
-            } else {
-                None
-            }
+            s.parse::<f64>().ok()
         },
         Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
         Value::Array(a) => {
@@ -24,6 +20,8 @@ pub fn to_number(value: &Value) -> Option<f64> {
                 Some(a.len() as f64)
             }
         },
+        Value::Object(_) if super::integer::is_bigint(value) => super::integer::as_bigint(value).map(|n| super::integer::bigint_to_f64(&n)),
+        Value::Object(_) if super::decimal::is_decimal(value) => super::decimal::as_decimal(value).map(|n| n.to_f64()),
         Value::Object(o) => Some(o.len() as f64), // 对象的键值对数量
         Value::Null => Some(0.0),
     }
@@ -0,0 +1,224 @@
+use serde_json::{Map, Value};
+use crate::error::NjilError;
+use crate::errortip::range as tip;
+
+/// 范围在值中的表示：`{"$range": {"start": 起始值, "step": 步长, "length": 元素个数}}`
+pub const RANGE_KEY: &str = "$range";
+
+/// 范围转换为数组时允许的最大元素个数
+pub const MAX_MATERIALIZED_LEN: u64 = 10_000_000;
+
+/// 浮点数范围计算元素个数时允许的误差（以步长为单位）
+const FLOAT_EPSILON: f64 = 1e-9;
+
+/// 惰性的等差数列，按需计算每个元素，不会生成整个数组
+///
+/// 起始值和步长都是整数时元素是整数，否则是浮点数。第i个元素是 `start + i * step`，
+/// 浮点数范围不会累积误差。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Range {
+    Integer { start: i64, step: i64, len: u64 },
+    Float { start: f64, step: f64, len: u64 },
+}
+
+impl Range {
+    /// 从0开始、步长为1的count个整数
+    pub fn count(count: u64) -> Range {
+        Range::Integer { start: 0, step: 1, len: count }
+    }
+    
+    /// 从from到to的范围，inclusive为true时包含终点
+    ///
+    /// 没有指定步长时，to小于from则步长为-1，否则为1。步长为0或者参数不是数字时报错。
+    pub fn new(from: &Value, to: &Value, step: Option<&Value>, inclusive: bool) -> Result<Range, NjilError> {
+        let number = |value: &Value, name: &str| {
+            value.as_f64().filter(|n| n.is_finite()).ok_or_else(|| NjilError::TypeError(tip::bound_requires_number(name)))
+        };
+        let (from_f, to_f) = (number(from, "from")?, number(to, "to")?);
+        let step = match step {
+            Some(step) if !step.is_null() => step.clone(),
+            _ => Value::from(if to_f < from_f { -1 } else { 1 }),
+        };
+        let step_f = number(&step, "step")?;
+        if step_f == 0.0 {
+            return Err(NjilError::ExecutionError(tip::step_zero().to_string()));
+        }
+        
+        if let (Some(start), Some(end), Some(step)) = (from.as_i64(), to.as_i64(), step.as_i64().filter(|step| *step != i64::MIN)) {
+            let span = end as i128 - start as i128;
+            let step_wide = step as i128;
+            // 把负步长转换为正步长的情况：计算满足 start + k*step 在终点之前的k的个数
+            let (span, step_wide) = if step_wide < 0 { (-span, -step_wide) } else { (span, step_wide) };
+            let len = if inclusive {
+                if span < 0 { 0 } else { span / step_wide + 1 }
+            } else if span <= 0 {
+                0
+            } else {
+                (span + step_wide - 1) / step_wide
+            };
+            if len > u64::MAX as i128 {
+                return Err(NjilError::ExecutionError(tip::too_long().to_string()));
+            }
+            return Ok(Range::Integer { start, step, len: len as u64 });
+        }
+        
+        let steps = (to_f - from_f) / step_f;
+        let len = if inclusive {
+            if steps < -FLOAT_EPSILON { 0.0 } else { (steps + FLOAT_EPSILON).floor() + 1.0 }
+        } else if steps <= FLOAT_EPSILON {
+            0.0
+        } else {
+            (steps - FLOAT_EPSILON).ceil()
+        };
+        if len > u64::MAX as f64 {
+            return Err(NjilError::ExecutionError(tip::too_long().to_string()));
+        }
+        Ok(Range::Float { start: from_f, step: step_f, len: len as u64 })
+    }
+    
+    /// 元素个数
+    pub fn len(&self) -> u64 {
+        match self {
+            Range::Integer { len, .. } | Range::Float { len, .. } => *len,
+        }
+    }
+    
+    /// 是否没有元素
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// 第index个元素，超出范围时返回None
+    pub fn get(&self, index: u64) -> Option<Value> {
+        if index >= self.len() {
+            return None;
+        }
+        match *self {
+            Range::Integer { start, step, .. } => {
+                Some(Value::from((start as i128 + index as i128 * step as i128) as i64))
+            },
+            Range::Float { start, step, .. } => {
+                serde_json::Number::from_f64(start + index as f64 * step).map(Value::Number)
+            },
+        }
+    }
+    
+    /// 顺序相反的范围
+    pub fn reversed(&self) -> Range {
+        match *self {
+            Range::Integer { len: 0, .. } | Range::Float { len: 0, .. } => *self,
+            Range::Integer { start, step, len } => Range::Integer {
+                start: (start as i128 + (len - 1) as i128 * step as i128) as i64,
+                step: -step,
+                len,
+            },
+            Range::Float { start, step, len } => Range::Float {
+                start: start + (len - 1) as f64 * step,
+                step: -step,
+                len,
+            },
+        }
+    }
+    
+    /// 依次产生每个元素的迭代器
+    pub fn iter(&self) -> RangeIter {
+        RangeIter { range: *self, index: 0 }
+    }
+    
+    /// 生成包含所有元素的数组，元素个数超过上限时报错
+    pub fn to_vec(&self) -> Result<Vec<Value>, NjilError> {
+        if self.len() > MAX_MATERIALIZED_LEN {
+            return Err(NjilError::ExecutionError(tip::too_long_to_materialize(self.len(), MAX_MATERIALIZED_LEN)));
+        }
+        Ok(self.iter().collect())
+    }
+}
+
+/// 范围的迭代器
+pub struct RangeIter {
+    range: Range,
+    index: u64,
+}
+
+impl Iterator for RangeIter {
+    type Item = Value;
+    
+    fn next(&mut self) -> Option<Value> {
+        let item = self.range.get(self.index)?;
+        self.index += 1;
+        Some(item)
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.range.len() - self.index.min(self.range.len())) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// 把范围包装为值
+pub fn range_value(range: &Range) -> Value {
+    let (start, step, len) = match *range {
+        Range::Integer { start, step, len } => (Value::from(start), Value::from(step), len),
+        Range::Float { start, step, len } => (Value::from(start), Value::from(step), len),
+    };
+    let mut fields = Map::new();
+    fields.insert("start".to_string(), start);
+    fields.insert("step".to_string(), step);
+    fields.insert("length".to_string(), Value::from(len));
+    let mut object = Map::new();
+    object.insert(RANGE_KEY.to_string(), Value::Object(fields));
+    Value::Object(object)
+}
+
+/// 判断值是否是范围
+pub fn is_range(value: &Value) -> bool {
+    as_range(value).is_some()
+}
+
+/// 取出值表示的范围
+pub fn as_range(value: &Value) -> Option<Range> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    if object.len() != 1 {
+        return None;
+    }
+    let fields = object.get(RANGE_KEY)?;
+    let len = fields.get("length")?.as_u64()?;
+    let (start, step) = (fields.get("start")?, fields.get("step")?);
+    match (start.as_i64(), step.as_i64()) {
+        (Some(start), Some(step)) => Some(Range::Integer { start, step, len }),
+        _ => Some(Range::Float { start: start.as_f64()?, step: step.as_f64()?, len }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    fn items(from: Value, to: Value, step: Option<Value>, inclusive: bool) -> Vec<Value> {
+        Range::new(&from, &to, step.as_ref(), inclusive).unwrap().iter().collect()
+    }
+    
+    #[test]
+    fn test_range_lengths() {
+        assert_eq!(items(json!(0), json!(5), None, false), vec![json!(0), json!(1), json!(2), json!(3), json!(4)]);
+        assert_eq!(items(json!(1), json!(10), Some(json!(3)), true), vec![json!(1), json!(4), json!(7), json!(10)]);
+        assert_eq!(items(json!(10), json!(0), Some(json!(-4)), false), vec![json!(10), json!(6), json!(2)]);
+        assert_eq!(items(json!(3), json!(0), None, true), vec![json!(3), json!(2), json!(1), json!(0)]);
+        assert_eq!(items(json!(0), json!(5), Some(json!(-1)), false), Vec::<Value>::new());
+        assert_eq!(items(json!(0), json!(1), Some(json!(0.25)), true), vec![json!(0.0), json!(0.25), json!(0.5), json!(0.75), json!(1.0)]);
+        assert_eq!(items(json!(0), json!(0.3), Some(json!(0.1)), false).len(), 3);
+        assert!(Range::new(&json!(0), &json!(5), Some(&json!(0)), false).is_err());
+        
+        // 很长的范围只保存起点、步长和长度
+        let huge = Range::new(&json!(0), &json!(i64::MAX), None, true).unwrap();
+        assert_eq!(huge.len(), i64::MAX as u64 + 1);
+        assert_eq!(huge.get(huge.len() - 1), Some(json!(i64::MAX)));
+        assert!(huge.to_vec().is_err());
+        assert_eq!(as_range(&range_value(&huge)), Some(huge));
+        
+        assert_eq!(Range::count(3).reversed().iter().collect::<Vec<_>>(), vec![json!(2), json!(1), json!(0)]);
+    }
+}
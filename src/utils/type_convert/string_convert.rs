@@ -19,6 +19,14 @@ pub fn to_string(value: &Value) -> String {
             result
         }
         Value::Object(obj) => {
+            // 大整数和定点小数显示为十进制数字
+            if let Some(number) = super::integer::as_bigint(value) {
+                return number.to_string();
+            }
+            if let Some(number) = super::decimal::as_decimal(value) {
+                return number.to_string();
+            }
+            
             // 检查是否是json.new创建的特殊格式
             if obj.contains_key("type") && obj.contains_key("value") && obj.len() == 2 {
                 // 这是json.new创建的值，直接返回value字段的内容